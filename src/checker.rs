@@ -0,0 +1,584 @@
+//! `boof check`, which infers types over a script and reports operations
+//! that would fail when it runs, such as `"a" - 1`.
+//!
+//! Checking is gradual. Parameters without annotations, boofer results
+//! without a `->` type, and names the script doesn't declare, such as
+//! built-in boofers, are `any`, which fits every type. A variable declared
+//! without an annotation has the type of its initializer, unless the script
+//! assigns to that name anywhere, in which case it is `any`.
+
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use crate::{
+    codes::{self, ErrorCode},
+    error::{Error, Span},
+    expr::{Expr, MatchArm},
+    stmt::{FunctionDecl, Stmt},
+    token::{Token, TokenType},
+    types::Type,
+};
+
+/// What the checker knows about a declared name.
+#[derive(Clone)]
+struct Binding {
+    ty: Type,
+    /// The annotation every later assignment must fit.
+    declared: Option<Type>,
+    /// The declaration of a boofer declared by name and never reassigned,
+    /// against which calls are checked.
+    signature: Option<Rc<FunctionDecl>>,
+}
+
+pub struct Checker {
+    /// Scopes, innermost last. The first holds globals.
+    scopes: Vec<HashMap<String, Binding>>,
+    /// Every name the script assigns to with `=`.
+    reassigned: HashSet<String>,
+    /// The `->` type of each enclosing boofer, innermost last.
+    returns: Vec<Option<Type>>,
+    errors: Vec<Error>,
+}
+
+impl Checker {
+    pub fn new() -> Self {
+        Checker {
+            scopes: vec![HashMap::new()],
+            reassigned: HashSet::new(),
+            returns: vec![],
+            errors: vec![],
+        }
+    }
+
+    /// Checks a whole script, returning its type errors in source order.
+    pub fn check(mut self, statements: &[Stmt]) -> Vec<Error> {
+        statements
+            .iter()
+            .for_each(|stmt| assigned_in_statement(stmt, &mut self.reassigned));
+        self.statements(statements);
+        self.errors
+    }
+
+    /// Checks a list of statements. Boofers declared in the list are known
+    /// from its start, so earlier boofers can call later ones.
+    fn statements(&mut self, statements: &[Stmt]) {
+        for stmt in statements {
+            if let Stmt::Function(name, declaration) = stmt {
+                self.declare_function(name, declaration);
+            }
+        }
+        statements.iter().for_each(|stmt| self.statement(stmt));
+    }
+
+    fn statement(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Expression(expr) | Stmt::Throw(_, expr) => {
+                self.expression(expr);
+            }
+            Stmt::Print(exprs) => exprs.iter().for_each(|expr| {
+                self.expression(expr);
+            }),
+            Stmt::Var(name, annotation, initializer) => {
+                let ty = initializer
+                    .as_ref()
+                    .map(|initializer| self.expression(initializer));
+                // Without an initializer the variable gets its first value
+                // by assignment, which is checked then.
+                if let (Some(declared), Some(ty)) = (annotation, ty) {
+                    if !ty.fits(*declared) {
+                        self.error(
+                            codes::DECLARED_TYPE_MISMATCH,
+                            format!(
+                                "'{}' is declared as {declared} but given {ty}.",
+                                name.lexeme
+                            ),
+                            name.span(),
+                        );
+                    }
+                }
+                let ty = match annotation {
+                    Some(declared) => *declared,
+                    None if self.reassigned.contains(&name.lexeme) => Type::Any,
+                    None => ty.unwrap_or(Type::Nil),
+                };
+                self.define(
+                    &name.lexeme,
+                    Binding {
+                        ty,
+                        declared: *annotation,
+                        signature: None,
+                    },
+                );
+            }
+            Stmt::Block(statements) => self.block(statements),
+            Stmt::If(_, condition, then_branch, else_branch) => {
+                self.expression(condition);
+                self.statement(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.statement(else_branch);
+                }
+            }
+            Stmt::While(_, condition, body) => {
+                self.expression(condition);
+                self.statement(body);
+            }
+            Stmt::Function(name, declaration) => {
+                self.declare_function(name, declaration);
+                self.function(declaration);
+            }
+            Stmt::Try(body, handler, finally) => {
+                self.block(body);
+                if let Some(handler) = handler {
+                    self.scopes.push(HashMap::new());
+                    if let Some(name) = &handler.name {
+                        self.define(&name.lexeme, any());
+                    }
+                    self.statements(&handler.body);
+                    self.scopes.pop();
+                }
+                if let Some(finally) = finally {
+                    self.block(finally);
+                }
+            }
+            Stmt::Return(keyword, value) => {
+                let ty = match value {
+                    Some(value) => self.expression(value),
+                    None => Type::Nil,
+                };
+                if let Some(Some(declared)) = self.returns.last().copied() {
+                    if !ty.fits(declared) {
+                        self.error(
+                            codes::RETURN_TYPE_MISMATCH,
+                            format!("Expected to return {declared}, not {ty}."),
+                            keyword.span(),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Infers the type of `expr`, reporting any part that can't run.
+    fn expression(&mut self, expr: &Expr) -> Type {
+        match expr {
+            Expr::Binary(left, op, right) => {
+                let left = self.expression(left);
+                let right = self.expression(right);
+                self.binary(op, left, right)
+            }
+            Expr::Unary(op, operand) => {
+                let ty = self.expression(operand);
+                match op.token_type {
+                    TokenType::Minus => {
+                        if !ty.fits(Type::Number) {
+                            self.error(
+                                codes::OPERAND_TYPE_MISMATCH,
+                                format!("Operand of '-' must be a number, not {ty}."),
+                                op.span(),
+                            );
+                        }
+                        Type::Number
+                    }
+                    _ => Type::Bool,
+                }
+            }
+            Expr::Grouping(inner) => self.expression(inner),
+            Expr::Literal(value) => Type::of(value),
+            Expr::Variable(name, _) => self.lookup(&name.lexeme).map_or(Type::Any, |b| b.ty),
+            Expr::Assign(name, value, _) => {
+                let ty = self.expression(value);
+                let declared = self.lookup(&name.lexeme).and_then(|b| b.declared);
+                if let Some(declared) = declared {
+                    if !ty.fits(declared) {
+                        self.error(
+                            codes::DECLARED_TYPE_MISMATCH,
+                            format!(
+                                "'{}' is declared as {declared} but assigned {ty}.",
+                                name.lexeme
+                            ),
+                            name.span(),
+                        );
+                    }
+                }
+                ty
+            }
+            Expr::Logical(left, _, right) => {
+                let left = self.expression(left);
+                let right = self.expression(right);
+                if left == right {
+                    left
+                } else {
+                    Type::Any
+                }
+            }
+            Expr::Call(callee, paren, args) => self.call(callee, paren, args),
+            Expr::Get(object, _) => {
+                self.expression(object);
+                Type::Any
+            }
+            Expr::Lambda(declaration) => {
+                self.function(declaration);
+                Type::Boofer
+            }
+            Expr::List(items) => {
+                items.iter().for_each(|item| {
+                    self.expression(item);
+                });
+                Type::List
+            }
+            Expr::Map(entries) => {
+                entries.iter().for_each(|(_, value)| {
+                    self.expression(value);
+                });
+                Type::Map
+            }
+            Expr::Match(_, subject, arms) => {
+                self.expression(subject);
+                let types: Vec<Type> = arms.iter().map(|arm| self.arm(arm)).collect();
+                match types.split_first() {
+                    Some((first, rest)) if rest.iter().all(|ty| ty == first) => *first,
+                    _ => Type::Any,
+                }
+            }
+        }
+    }
+
+    /// The type of a binary operation, following the rules of
+    /// `Interpreter::binary`.
+    fn binary(&mut self, op: &Token, left: Type, right: Type) -> Type {
+        let text = |expected: &str| {
+            format!(
+                "Operands of '{}' must be {expected}, not {left} and {right}.",
+                op.lexeme
+            )
+        };
+        match op.token_type {
+            TokenType::EqualEqual | TokenType::BangEqual => Type::Bool,
+            TokenType::Plus => match (left, right) {
+                (Type::Any, Type::Any) => Type::Any,
+                (Type::Number | Type::String, Type::Any) => left,
+                (Type::Any, Type::Number | Type::String) => right,
+                (Type::Number, Type::Number) | (Type::String, Type::String) => left,
+                _ => {
+                    self.error(
+                        codes::OPERAND_TYPE_MISMATCH,
+                        text("two numbers or two strings"),
+                        op.span(),
+                    );
+                    Type::Any
+                }
+            },
+            _ => {
+                if !left.fits(Type::Number) || !right.fits(Type::Number) {
+                    self.error(codes::OPERAND_TYPE_MISMATCH, text("numbers"), op.span());
+                }
+                match op.token_type {
+                    TokenType::Minus | TokenType::Star | TokenType::Slash => Type::Number,
+                    _ => Type::Bool,
+                }
+            }
+        }
+    }
+
+    /// Checks a call against the callee's signature when it is a boofer
+    /// declared by name.
+    fn call(&mut self, callee: &Expr, paren: &Token, args: &[Expr]) -> Type {
+        let callee_type = self.expression(callee);
+        let types: Vec<Type> = args.iter().map(|arg| self.expression(arg)).collect();
+        if !callee_type.fits(Type::Boofer) {
+            self.error(
+                codes::NOT_CALLABLE,
+                format!("Can only call boofers, not {callee_type}."),
+                paren.span(),
+            );
+            return Type::Any;
+        }
+        let Expr::Variable(name, _) = callee else {
+            return Type::Any;
+        };
+        let Some(declaration) = self.lookup(&name.lexeme).and_then(|b| b.signature.clone()) else {
+            return Type::Any;
+        };
+        if args.len() != declaration.params.len() {
+            self.error(
+                codes::ARGUMENT_COUNT_MISMATCH,
+                format!(
+                    "'{}' expects {} argument{} but got {}.",
+                    name.lexeme,
+                    declaration.params.len(),
+                    if declaration.params.len() == 1 {
+                        ""
+                    } else {
+                        "s"
+                    },
+                    args.len()
+                ),
+                paren.span(),
+            );
+        }
+        let params = declaration.params.iter().zip(&declaration.param_types);
+        for ((param, declared), ty) in params.zip(types) {
+            if let Some(declared) = declared {
+                if !ty.fits(*declared) {
+                    self.error(
+                        codes::ARGUMENT_TYPE_MISMATCH,
+                        format!(
+                            "Argument '{}' of '{}' must be {declared}, not {ty}.",
+                            param.lexeme, name.lexeme
+                        ),
+                        paren.span(),
+                    );
+                }
+            }
+        }
+        declaration.return_type.unwrap_or(Type::Any)
+    }
+
+    /// Checks a boofer body in a scope holding its parameters.
+    fn function(&mut self, declaration: &FunctionDecl) {
+        let scope = declaration
+            .params
+            .iter()
+            .zip(&declaration.param_types)
+            .map(|(param, declared)| {
+                let binding = Binding {
+                    ty: declared.unwrap_or(Type::Any),
+                    declared: *declared,
+                    signature: None,
+                };
+                (param.lexeme.clone(), binding)
+            })
+            .collect();
+        self.scopes.push(scope);
+        self.returns.push(declaration.return_type);
+        self.statements(&declaration.body);
+        self.returns.pop();
+        self.scopes.pop();
+    }
+
+    fn declare_function(&mut self, name: &Token, declaration: &Rc<FunctionDecl>) {
+        let signature = (!self.reassigned.contains(&name.lexeme)).then(|| Rc::clone(declaration));
+        self.define(
+            &name.lexeme,
+            Binding {
+                ty: Type::Boofer,
+                declared: None,
+                signature,
+            },
+        );
+    }
+
+    /// Checks a match arm, whose pattern bindings are `any`, and returns the
+    /// type of its body.
+    fn arm(&mut self, arm: &MatchArm) -> Type {
+        let mut names = vec![];
+        arm.pattern.bindings(&mut names);
+        self.scopes
+            .push(names.into_iter().map(|name| (name, any())).collect());
+        if let Some(guard) = &arm.guard {
+            self.expression(guard);
+        }
+        let ty = self.expression(&arm.body);
+        self.scopes.pop();
+        ty
+    }
+
+    fn block(&mut self, statements: &[Stmt]) {
+        self.scopes.push(HashMap::new());
+        self.statements(statements);
+        self.scopes.pop();
+    }
+
+    fn define(&mut self, name: &str, binding: Binding) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), binding);
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Binding> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    fn error(&mut self, code: &'static ErrorCode, message: String, span: Span) {
+        self.errors.push(Error::checking(code, message, span));
+    }
+}
+
+impl Default for Checker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn any() -> Binding {
+    Binding {
+        ty: Type::Any,
+        declared: None,
+        signature: None,
+    }
+}
+
+/// Adds every name assigned to with `=` in `stmt` to `names`.
+fn assigned_in_statement(stmt: &Stmt, names: &mut HashSet<String>) {
+    let statements = |statements: &[Stmt], names: &mut HashSet<String>| {
+        statements
+            .iter()
+            .for_each(|stmt| assigned_in_statement(stmt, names))
+    };
+    match stmt {
+        Stmt::Expression(expr) | Stmt::Throw(_, expr) => assigned_in(expr, names),
+        Stmt::Print(exprs) => exprs.iter().for_each(|expr| assigned_in(expr, names)),
+        Stmt::Var(_, _, initializer) | Stmt::Return(_, initializer) => {
+            if let Some(initializer) = initializer {
+                assigned_in(initializer, names);
+            }
+        }
+        Stmt::Block(body) => statements(body, names),
+        Stmt::If(_, condition, then_branch, else_branch) => {
+            assigned_in(condition, names);
+            assigned_in_statement(then_branch, names);
+            if let Some(else_branch) = else_branch {
+                assigned_in_statement(else_branch, names);
+            }
+        }
+        Stmt::While(_, condition, body) => {
+            assigned_in(condition, names);
+            assigned_in_statement(body, names);
+        }
+        Stmt::Function(_, declaration) => statements(&declaration.body, names),
+        Stmt::Try(body, handler, finally) => {
+            statements(body, names);
+            if let Some(handler) = handler {
+                statements(&handler.body, names);
+            }
+            if let Some(finally) = finally {
+                statements(finally, names);
+            }
+        }
+    }
+}
+
+fn assigned_in(expr: &Expr, names: &mut HashSet<String>) {
+    match expr {
+        Expr::Assign(name, value, _) => {
+            names.insert(name.lexeme.clone());
+            assigned_in(value, names);
+        }
+        Expr::Binary(left, _, right) | Expr::Logical(left, _, right) => {
+            assigned_in(left, names);
+            assigned_in(right, names);
+        }
+        Expr::Grouping(expr) | Expr::Unary(_, expr) | Expr::Get(expr, _) => {
+            assigned_in(expr, names)
+        }
+        Expr::Literal(_) | Expr::Variable(..) => (),
+        Expr::Call(callee, _, args) => {
+            assigned_in(callee, names);
+            args.iter().for_each(|arg| assigned_in(arg, names));
+        }
+        Expr::Lambda(declaration) => declaration
+            .body
+            .iter()
+            .for_each(|stmt| assigned_in_statement(stmt, names)),
+        Expr::List(items) => items.iter().for_each(|item| assigned_in(item, names)),
+        Expr::Map(entries) => entries
+            .iter()
+            .for_each(|(_, value)| assigned_in(value, names)),
+        Expr::Match(_, subject, arms) => {
+            assigned_in(subject, names);
+            for arm in arms {
+                if let Some(guard) = &arm.guard {
+                    assigned_in(guard, names);
+                }
+                assigned_in(&arm.body, names);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer::Lexer, parser::Parser};
+
+    /// The code and message of each error `boof check` reports for `source`.
+    fn errors(source: &str) -> Vec<(&'static str, String)> {
+        let tokens = Lexer::from(source.to_string()).scan().unwrap();
+        let statements = Parser::from(tokens).parse().unwrap();
+        Checker::new()
+            .check(&statements)
+            .iter()
+            .map(|error| {
+                let diagnostic = error.diagnostic().unwrap();
+                (diagnostic.code.id, diagnostic.message.clone())
+            })
+            .collect()
+    }
+
+    fn codes(source: &str) -> Vec<&'static str> {
+        errors(source).into_iter().map(|(code, _)| code).collect()
+    }
+
+    #[test]
+    fn operand_type_mismatch() {
+        assert_eq!(
+            codes("boof label = \"total: \"; print label + 3;"),
+            ["B0701"]
+        );
+        assert!(codes("boof label = \"total: \"; print label + str(3);").is_empty());
+    }
+
+    #[test]
+    fn declared_type_mismatch() {
+        assert_eq!(
+            codes("boof count: number = \"3\"; count = nil;"),
+            ["B0702", "B0702"]
+        );
+        assert!(codes("boof count: number = 3; count = 0;").is_empty());
+    }
+
+    #[test]
+    fn declaration_without_initializer_is_checked_at_assignment() {
+        assert!(codes("boof z: number; z = 1;").is_empty());
+        assert_eq!(codes("boof z: number; z = \"one\";"), ["B0702"]);
+    }
+
+    #[test]
+    fn argument_type_mismatch() {
+        let shout = "boofer shout(text: string) -> string { return text.upper() + \"!\"; }";
+        assert_eq!(codes(&format!("{shout} print shout(42);")), ["B0703"]);
+        assert!(codes(&format!("{shout} print shout(\"hey\");")).is_empty());
+    }
+
+    #[test]
+    fn argument_count_mismatch() {
+        let add = "boofer add(a, b) { return a + b; }";
+        assert_eq!(
+            errors(&format!("{add} print add(1, 2, 3);")),
+            [("B0704", "'add' expects 2 arguments but got 3.".to_string())]
+        );
+        assert_eq!(
+            errors("boofer twice(n) { return n * 2; } print twice();"),
+            [("B0704", "'twice' expects 1 argument but got 0.".to_string())]
+        );
+        assert!(codes(&format!("{add} print add(1, 2);")).is_empty());
+    }
+
+    #[test]
+    fn return_type_mismatch() {
+        assert_eq!(
+            codes("boofer half(n: number) -> number { if (n == 0) return; return n / 2; }"),
+            ["B0705"]
+        );
+        assert!(
+            codes("boofer half(n: number) -> number { if (n == 0) return 0; return n / 2; }")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn not_callable() {
+        assert_eq!(codes("boof width = 3; print width(2 + 1);"), ["B0706"]);
+        assert!(codes("boof width = 3; print width * (2 + 1);").is_empty());
+    }
+}
@@ -0,0 +1,1045 @@
+//! Stable codes for every diagnostic boof reports, with the explanations
+//! printed by `boof --explain`.
+//!
+//! Codes never change meaning once released. `B00xx` codes come from the
+//! lexer, `B01xx` from the parser, `B02xx` from the resolver, `B03xx` from
+//! running a script, `B04xx` from reading input, `B05xx` from the command
+//! line, `B06xx` from `boof lint` and `B07xx` from `boof check`. `B0112`
+//! kept its code when the check moved to the resolver.
+
+/// A diagnostic's code, a short title and a longer explanation with
+/// examples of wrong and corrected code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorCode {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub explanation: &'static str,
+}
+
+impl ErrorCode {
+    /// Looks up a code by its id, such as `"B0102"`.
+    pub fn lookup(id: &str) -> Option<&'static ErrorCode> {
+        ALL.iter()
+            .copied()
+            .find(|code| code.id.eq_ignore_ascii_case(id))
+    }
+}
+
+pub const UNTERMINATED_STRING: &ErrorCode = &ErrorCode {
+    id: "B0001",
+    title: "unterminated string",
+    explanation: "\
+A string literal was opened with `\"` but the file ended before the
+closing `\"`. Strings may span lines, so the error is reported where the
+string starts.
+
+Wrong:
+
+    print \"hello;
+
+Correct:
+
+    print \"hello\";",
+};
+
+pub const UNEXPECTED_CHARACTER: &ErrorCode = &ErrorCode {
+    id: "B0002",
+    title: "unexpected character",
+    explanation: "\
+The source contains a character that isn't part of any token. Outside of
+strings and comments boof only uses letters, digits, `_`, whitespace and
+its operators and punctuation.
+
+Wrong:
+
+    boof price = 5 @ 2;
+
+Correct:
+
+    boof price = 5 * 2;",
+};
+
+pub const UNKNOWN_ESCAPE: &ErrorCode = &ErrorCode {
+    id: "B0003",
+    title: "unknown escape sequence",
+    explanation: "\
+A backslash in a string must start one of the escapes `\\n`, `\\t`, `\\r`,
+`\\0`, `\\\"` or `\\\\`. To write a backslash itself, double it.
+
+Wrong:
+
+    print \"C:\\temp\\data\";
+
+Correct:
+
+    print \"C:\\\\temp\\\\data\";",
+};
+
+pub const INVALID_NUMBER: &ErrorCode = &ErrorCode {
+    id: "B0004",
+    title: "invalid number",
+    explanation: "\
+A number literal couldn't be read as a number. Numbers are ASCII digits
+with an optional fractional part, such as `42` or `3.14`. Other numeric
+characters, such as superscripts, aren't allowed.
+
+Wrong:
+
+    boof area = 4²;
+
+Correct:
+
+    boof area = 4 * 4;",
+};
+
+pub const UNTERMINATED_REGEX: &ErrorCode = &ErrorCode {
+    id: "B0005",
+    title: "unterminated regex",
+    explanation: "\
+A regex literal was opened with `/` but the line ended before the closing
+`/`. A `/` inside the pattern must be escaped as `\\/` unless it is in a
+character class.
+
+Wrong:
+
+    boof digits = /\\d+;
+
+Correct:
+
+    boof digits = /\\d+/;",
+};
+
+pub const INVALID_REGEX: &ErrorCode = &ErrorCode {
+    id: "B0006",
+    title: "invalid regex",
+    explanation: "\
+A regex literal's pattern or flags are invalid, for example an unclosed
+group or an unknown flag. The supported flags are `i`, `m` and `s`.
+
+Wrong:
+
+    boof word = /(\\w+/g;
+
+Correct:
+
+    boof word = /(\\w+)/i;",
+};
+
+pub const EXPECTED_SEMICOLON: &ErrorCode = &ErrorCode {
+    id: "B0101",
+    title: "expected ';'",
+    explanation: "\
+Statements such as declarations, `print`, `return`, `throw` and
+expression statements end with `;`. The error points at the token found
+where the `;` should be.
+
+Wrong:
+
+    boof count = 1
+    print count;
+
+Correct:
+
+    boof count = 1;
+    print count;",
+};
+
+pub const EXPECTED_RIGHT_PAREN: &ErrorCode = &ErrorCode {
+    id: "B0102",
+    title: "expected ')'",
+    explanation: "\
+A `(` was opened but the matching `)` wasn't found where it was expected,
+such as after a call's arguments, a boofer's parameters or an `if`
+condition.
+
+Wrong:
+
+    print len([1, 2];
+
+Correct:
+
+    print len([1, 2]);",
+};
+
+pub const EXPECTED_LEFT_PAREN: &ErrorCode = &ErrorCode {
+    id: "B0103",
+    title: "expected '('",
+    explanation: "\
+Conditions of `if`, `while` and `for`, and the parameters of a boofer,
+must be wrapped in parentheses.
+
+Wrong:
+
+    boof count = 2;
+    if count > 1 {
+        print count;
+    }
+
+Correct:
+
+    boof count = 2;
+    if (count > 1) {
+        print count;
+    }",
+};
+
+pub const EXPECTED_RIGHT_BRACE: &ErrorCode = &ErrorCode {
+    id: "B0104",
+    title: "expected '}'",
+    explanation: "\
+A block, map literal, `match` or map pattern was opened with `{` but not
+closed. This is often reported at the end of the file when a `}` is
+missing further up.
+
+Wrong:
+
+    boofer greet(name) {
+        print \"hi \" + name;
+
+Correct:
+
+    boofer greet(name) {
+        print \"hi \" + name;
+    }",
+};
+
+pub const EXPECTED_LEFT_BRACE: &ErrorCode = &ErrorCode {
+    id: "B0105",
+    title: "expected '{'",
+    explanation: "\
+Boofer bodies, `try`, `catch` and `finally` blocks and the arms of a
+`match` must be wrapped in braces, even when they hold one statement.
+
+Wrong:
+
+    try print risky();
+    catch (e) print e.message;
+
+Correct:
+
+    try { print risky(); }
+    catch (e) { print e.message; }",
+};
+
+pub const EXPECTED_RIGHT_BRACKET: &ErrorCode = &ErrorCode {
+    id: "B0106",
+    title: "expected ']'",
+    explanation: "\
+A list literal, list pattern or index was opened with `[` but not closed.
+
+Wrong:
+
+    boof primes = [2, 3, 5;
+
+Correct:
+
+    boof primes = [2, 3, 5];",
+};
+
+pub const EXPECTED_COLON: &ErrorCode = &ErrorCode {
+    id: "B0107",
+    title: "expected ':'",
+    explanation: "\
+Each entry of a map literal is a key, a `:` and a value.
+
+Wrong:
+
+    boof point = {x = 1, y = 2};
+
+Correct:
+
+    boof point = {x: 1, y: 2};",
+};
+
+pub const EXPECTED_FAT_ARROW: &ErrorCode = &ErrorCode {
+    id: "B0108",
+    title: "expected '=>'",
+    explanation: "\
+Each `match` arm separates its pattern from its result with `=>`, as
+lambdas do their parameters from their body.
+
+Wrong:
+
+    print match 1 { 1 -> \"one\", _ => \"other\" };
+
+Correct:
+
+    print match 1 { 1 => \"one\", _ => \"other\" };",
+};
+
+pub const EXPECTED_IDENTIFIER: &ErrorCode = &ErrorCode {
+    id: "B0109",
+    title: "expected a name",
+    explanation: "\
+Variable declarations, boofer declarations, parameters and `catch` clauses
+need a name: a letter or `_` followed by letters, digits or `_`. Keywords
+can't be used as names.
+
+Wrong:
+
+    boof 2nd = \"silver\";
+
+Correct:
+
+    boof second = \"silver\";",
+};
+
+pub const EXPECTED_EXPRESSION: &ErrorCode = &ErrorCode {
+    id: "B0110",
+    title: "expected expression",
+    explanation: "\
+The parser needed a value, such as a literal, variable, call or operator
+expression, but found something else. This usually means an operand is
+missing.
+
+Wrong:
+
+    print 1 +;
+
+Correct:
+
+    print 1 + 2;",
+};
+
+pub const INVALID_ASSIGNMENT_TARGET: &ErrorCode = &ErrorCode {
+    id: "B0111",
+    title: "invalid assignment target",
+    explanation: "\
+Only variables can be assigned with `=`. To compare two values, use `==`.
+
+Wrong:
+
+    boof count = 2;
+    if (count + 1 = 3) {
+        print count;
+    }
+
+Correct:
+
+    boof count = 2;
+    if (count + 1 == 3) {
+        print count;
+    }",
+};
+
+pub const RETURN_OUTSIDE_BOOFER: &ErrorCode = &ErrorCode {
+    id: "B0112",
+    title: "return outside a boofer",
+    explanation: "\
+`return` ends the boofer it is written in, so it can't appear in top-level
+code. To stop a script early, call `exit()`.
+
+Wrong:
+
+    if (len(args()) == 0) {
+        return;
+    }
+
+Correct:
+
+    if (len(args()) == 0) {
+        exit(0);
+    }",
+};
+
+pub const TRY_WITHOUT_HANDLER: &ErrorCode = &ErrorCode {
+    id: "B0113",
+    title: "try without catch or finally",
+    explanation: "\
+A `try` block must be followed by a `catch` block, a `finally` block, or
+both.
+
+Wrong:
+
+    try {
+        risky();
+    }
+
+Correct:
+
+    try {
+        risky();
+    } catch (e) {
+        eprint(e.message);
+    }",
+};
+
+pub const EXPECTED_PROPERTY_NAME: &ErrorCode = &ErrorCode {
+    id: "B0114",
+    title: "expected property name",
+    explanation: "\
+A `.` must be followed by the name of a key or method, not a string or
+number.
+
+Wrong:
+
+    boof scores = {first: 10};
+    print scores.\"first\";
+
+Correct:
+
+    boof scores = {first: 10};
+    print scores.first;",
+};
+
+pub const INVALID_MAP_KEY: &ErrorCode = &ErrorCode {
+    id: "B0115",
+    title: "invalid map key",
+    explanation: "\
+Keys in a map literal or map pattern are names or strings. Other values
+can't be keys.
+
+Wrong:
+
+    boof squares = {1: 1, 2: 4};
+
+Correct:
+
+    boof squares = {\"1\": 1, \"2\": 4};",
+};
+
+pub const EXPECTED_PATTERN: &ErrorCode = &ErrorCode {
+    id: "B0116",
+    title: "expected pattern",
+    explanation: "\
+A `match` arm starts with a pattern: a literal, a range, `_`, a name to
+bind, or a list or map pattern. Expressions such as calls aren't
+patterns.
+
+Wrong:
+
+    print match 1 { (1) => \"one\", _ => \"other\" };
+
+Correct:
+
+    print match 1 { 1 => \"one\", _ => \"other\" };",
+};
+
+pub const NON_NUMERIC_RANGE_PATTERN: &ErrorCode = &ErrorCode {
+    id: "B0117",
+    title: "range pattern with non-number bounds",
+    explanation: "\
+Both ends of a range pattern must be number literals.
+
+Wrong:
+
+    print match \"b\" { \"a\"..\"c\" => \"pass\", _ => \"fail\" };
+
+Correct:
+
+    print match 72 { 50..=100 => \"pass\", _ => \"fail\" };",
+};
+
+pub const UNKNOWN_TYPE: &ErrorCode = &ErrorCode {
+    id: "B0118",
+    title: "unknown type",
+    explanation: "\
+A type annotation after `:` or `->` must name one of the types `any`,
+`nil`, `bool`, `number`, `string`, `list`, `map`, `boofer` or `regex`.
+
+Wrong:
+
+    boof count: int = 1;
+
+Correct:
+
+    boof count: number = 1;",
+};
+
+pub const MATCH_WITHOUT_DEFAULT: &ErrorCode = &ErrorCode {
+    id: "B0150",
+    title: "match over literals has no '_' arm",
+    explanation: "\
+This is a warning. A `match` whose arms are all literals can't cover
+every value, and a value that matches no arm is a runtime error. Add a `_`
+arm to say what should happen instead.
+
+Wrong:
+
+    print match 3 { 6 => \"saturday\", 7 => \"sunday\" };
+
+Correct:
+
+    print match 3 { 6 => \"saturday\", 7 => \"sunday\", _ => \"weekday\" };",
+};
+
+pub const READ_IN_OWN_INITIALIZER: &ErrorCode = &ErrorCode {
+    id: "B0201",
+    title: "local variable read in its own initializer",
+    explanation: "\
+A local variable can't be used in the expression that gives it its first
+value, since it doesn't have a value yet. To start from an outer variable
+of the same name, give the new variable a different name.
+
+Wrong:
+
+    boof count = 1;
+    {
+        boof count = count + 1;
+        print count;
+    }
+
+Correct:
+
+    boof count = 1;
+    {
+        boof next = count + 1;
+        print next;
+    }",
+};
+
+pub const DUPLICATE_DECLARATION: &ErrorCode = &ErrorCode {
+    id: "B0202",
+    title: "duplicate declaration",
+    explanation: "\
+A name was declared twice in the same local scope, for example two
+parameters with the same name or a `boof` that repeats a parameter. To
+change a variable's value, assign to it instead. Globals may be declared
+again.
+
+Wrong:
+
+    boofer area(width, width) {
+        return width * width;
+    }
+
+Correct:
+
+    boofer area(width, height) {
+        return width * height;
+    }",
+};
+
+pub const TYPE_ERROR: &ErrorCode = &ErrorCode {
+    id: "B0301",
+    title: "type error",
+    explanation: "\
+An operator or boofer was given a value of the wrong type, such as adding
+a number to a string. Convert the value first, or check its type with
+`type_of()`.
+
+Wrong:
+
+    print \"count: \" + 3;
+
+Correct:
+
+    print \"count: \" + str(3);",
+};
+
+pub const UNDEFINED_NAME: &ErrorCode = &ErrorCode {
+    id: "B0302",
+    title: "undefined name",
+    explanation: "\
+A variable, map key or method was used that doesn't exist. Check the
+spelling, and that variables are declared with `boof` before they are
+used.
+
+Wrong:
+
+    print total;
+    boof total = 3;
+
+Correct:
+
+    boof total = 3;
+    print total;",
+};
+
+pub const ARITY_MISMATCH: &ErrorCode = &ErrorCode {
+    id: "B0303",
+    title: "wrong number of arguments",
+    explanation: "\
+A boofer was called with more or fewer arguments than it takes.
+
+Wrong:
+
+    boofer add(a, b) { return a + b; }
+    print add(1);
+
+Correct:
+
+    boofer add(a, b) { return a + b; }
+    print add(1, 2);",
+};
+
+pub const INVALID_VALUE: &ErrorCode = &ErrorCode {
+    id: "B0304",
+    title: "invalid value",
+    explanation: "\
+A value had the right type but couldn't be used, such as malformed JSON,
+an index out of range or a bad date format.
+
+Wrong:
+
+    print json.parse(\"{x: 1}\");
+
+Correct:
+
+    print json.parse(\"{\\\"x\\\": 1}\");",
+};
+
+pub const RUNTIME_IO: &ErrorCode = &ErrorCode {
+    id: "B0305",
+    title: "input or output failed",
+    explanation: "\
+A file or stream operation failed while the script ran, for example
+because a file doesn't exist or can't be written. Catch the error to
+handle it.
+
+Wrong:
+
+    print fs.read(\"missing.txt\");
+
+Correct:
+
+    try {
+        print fs.read(\"missing.txt\");
+    } catch (e) {
+        eprint(\"no file:\", e.message);
+    }",
+};
+
+pub const UNCAUGHT_THROW: &ErrorCode = &ErrorCode {
+    id: "B0306",
+    title: "uncaught thrown value",
+    explanation: "\
+A value raised with `throw` wasn't caught by any `try`. Catch it where
+the failure can be handled.
+
+Wrong:
+
+    throw \"out of stock\";
+
+Correct:
+
+    try {
+        throw \"out of stock\";
+    } catch (e) {
+        print e;
+    }",
+};
+
+pub const STACK_OVERFLOW: &ErrorCode = &ErrorCode {
+    id: "B0307",
+    title: "stack overflow",
+    explanation: "\
+Boofer calls nested deeper than the interpreter allows, which almost
+always means a recursive boofer has no case that stops it. Calls may nest
+1000 deep, or less when they run out of the stack the host gives them.
+
+Wrong:
+
+    boofer countdown(n) {
+        print n;
+        return countdown(n - 1);
+    }
+    countdown(3);
+
+Correct:
+
+    boofer countdown(n) {
+        if (n < 0) return nil;
+        print n;
+        return countdown(n - 1);
+    }
+    countdown(3);",
+};
+
+pub const FILE_UNREADABLE: &ErrorCode = &ErrorCode {
+    id: "B0401",
+    title: "input can't be read",
+    explanation: "\
+The script file or the prompt's input couldn't be read, for example
+because the path doesn't exist.
+
+Wrong:
+
+    boof mian.boof
+
+Correct:
+
+    boof main.boof",
+};
+
+pub const INVALID_ARGUMENTS: &ErrorCode = &ErrorCode {
+    id: "B0501",
+    title: "invalid command line arguments",
+    explanation: "\
+The command line contained an unknown option or an option with a missing
+or invalid value. Options go before the script's path; everything after
+it is passed to the script.
+
+Wrong:
+
+    boof --seed main.boof
+
+Correct:
+
+    boof --seed 42 main.boof",
+};
+
+pub const UNUSED_VARIABLE: &ErrorCode = &ErrorCode {
+    id: "B0601",
+    title: "unused variable",
+    explanation: "\
+This is a lint reported by `boof lint`. A `boof` variable or a parameter
+is declared but never read, which often means a typo or leftover code.
+Remove it, or start its name with `_` if it must stay, such as a callback
+parameter. `// boof:allow(unused)` silences it for one line.
+
+Wrong:
+
+    boofer greet(name, greeting) {
+        print \"hello \" + name;
+    }
+
+Correct:
+
+    boofer greet(name, _greeting) {
+        print \"hello \" + name;
+    }",
+};
+
+pub const UNREACHABLE_CODE: &ErrorCode = &ErrorCode {
+    id: "B0602",
+    title: "unreachable code",
+    explanation: "\
+This is a lint reported by `boof lint`. Statements after a `return` or
+`throw` in the same block can never run. `// boof:allow(unreachable)`
+silences it for one line.
+
+Wrong:
+
+    boofer half(n) {
+        return n / 2;
+        print \"halved\";
+    }
+
+Correct:
+
+    boofer half(n) {
+        print \"halved\";
+        return n / 2;
+    }",
+};
+
+pub const CONSTANT_CONDITION: &ErrorCode = &ErrorCode {
+    id: "B0603",
+    title: "constant condition",
+    explanation: "\
+This is a lint reported by `boof lint`. The condition of an `if` or
+`while` is a literal, so the same branch always runs. `while (true)` is
+allowed, since it is how loops that end with `return` are written.
+`// boof:allow(constant_condition)` silences it for one line.
+
+Wrong:
+
+    boof debug = false;
+    if (false) {
+        print \"debugging\";
+    }
+
+Correct:
+
+    boof debug = false;
+    if (debug) {
+        print \"debugging\";
+    }",
+};
+
+pub const SELF_ASSIGNMENT: &ErrorCode = &ErrorCode {
+    id: "B0604",
+    title: "self-assignment",
+    explanation: "\
+This is a lint reported by `boof lint`. Assigning a variable to itself
+does nothing, and usually means the wrong name was used on one side.
+`// boof:allow(self_assignment)` silences it for one line.
+
+Wrong:
+
+    boof total = 0;
+    boof subtotal = 5;
+    total = total;
+
+Correct:
+
+    boof total = 0;
+    boof subtotal = 5;
+    total = subtotal;",
+};
+
+pub const NIL_COMPARISON: &ErrorCode = &ErrorCode {
+    id: "B0605",
+    title: "suspicious comparison with nil",
+    explanation: "\
+This is a lint reported by `boof lint`. A comparison with `nil` using `==`
+or `!=` either has a result known in advance, because the other side can
+never be `nil`, or is written with `nil` first instead of the usual
+`value == nil`. `// boof:allow(nil_comparison)` silences it for one line.
+
+Wrong:
+
+    boof found = nil;
+    if (nil == found) {
+        print \"missing\";
+    }
+
+Correct:
+
+    boof found = nil;
+    if (found == nil) {
+        print \"missing\";
+    }",
+};
+
+pub const SHADOWED_VARIABLE: &ErrorCode = &ErrorCode {
+    id: "B0606",
+    title: "shadowed variable",
+    explanation: "\
+This is a lint reported by `boof lint`. A local declaration reuses the
+name of a variable from an outer scope, so the outer one can't be reached
+inside it. Match pattern bindings aren't reported.
+`// boof:allow(shadowing)` silences it for one line.
+
+Wrong:
+
+    boof count = 3;
+    boofer double(count) {
+        return count * 2;
+    }
+    print double(count);
+
+Correct:
+
+    boof count = 3;
+    boofer double(n) {
+        return n * 2;
+    }
+    print double(count);",
+};
+
+pub const OPERAND_TYPE_MISMATCH: &ErrorCode = &ErrorCode {
+    id: "B0701",
+    title: "operator applied to the wrong types",
+    explanation: "\
+Reported by `boof check`. An operator is given values it can't work on,
+so the script would stop with a type error when it got there. `+` takes
+two numbers or two strings, and `-`, `*`, `/`, `<`, `<=`, `>` and `>=`
+take numbers.
+
+Wrong:
+
+    boof label = \"total: \";
+    print label + 3;
+
+Correct:
+
+    boof label = \"total: \";
+    print label + str(3);",
+};
+
+pub const DECLARED_TYPE_MISMATCH: &ErrorCode = &ErrorCode {
+    id: "B0702",
+    title: "value doesn't match the variable's type",
+    explanation: "\
+Reported by `boof check`. A variable declared with a type annotation is
+given a value of another type, either by its initializer or by a later
+assignment. A variable declared without an initializer is checked at
+its first assignment.
+
+Wrong:
+
+    boof count: number = \"3\";
+    count = nil;
+
+Correct:
+
+    boof count: number = 3;
+    count = 0;",
+};
+
+pub const ARGUMENT_TYPE_MISMATCH: &ErrorCode = &ErrorCode {
+    id: "B0703",
+    title: "argument doesn't match the parameter's type",
+    explanation: "\
+Reported by `boof check`. A boofer is called with an argument whose type
+doesn't match the annotation on its parameter.
+
+Wrong:
+
+    boofer shout(text: string) -> string {
+        return text.upper() + \"!\";
+    }
+    print shout(42);
+
+Correct:
+
+    boofer shout(text: string) -> string {
+        return text.upper() + \"!\";
+    }
+    print shout(\"hey\");",
+};
+
+pub const ARGUMENT_COUNT_MISMATCH: &ErrorCode = &ErrorCode {
+    id: "B0704",
+    title: "wrong number of arguments",
+    explanation: "\
+Reported by `boof check`. A boofer declared by name is called with more or
+fewer arguments than it has parameters, which would be an arity error when
+run.
+
+Wrong:
+
+    boofer add(a, b) {
+        return a + b;
+    }
+    print add(1, 2, 3);
+
+Correct:
+
+    boofer add(a, b) {
+        return a + b;
+    }
+    print add(1, 2);",
+};
+
+pub const RETURN_TYPE_MISMATCH: &ErrorCode = &ErrorCode {
+    id: "B0705",
+    title: "returned value doesn't match the return type",
+    explanation: "\
+Reported by `boof check`. A boofer declared with `-> type` returns a value
+of another type. A `return` with no value returns `nil`.
+
+Wrong:
+
+    boofer average(total: number, count: number) -> number {
+        if (count == 0) return;
+        return total / count;
+    }
+
+Correct:
+
+    boofer average(total: number, count: number) -> number {
+        if (count == 0) return 0;
+        return total / count;
+    }",
+};
+
+pub const NOT_CALLABLE: &ErrorCode = &ErrorCode {
+    id: "B0706",
+    title: "call of a value that isn't a boofer",
+    explanation: "\
+Reported by `boof check`. Only boofers can be called, and the value before
+the parentheses is known to be something else, often because an operator
+is missing.
+
+Wrong:
+
+    boof width = 3;
+    print width(2 + 1);
+
+Correct:
+
+    boof width = 3;
+    print width * (2 + 1);",
+};
+
+/// Every code, in order.
+pub const ALL: &[&ErrorCode] = &[
+    UNTERMINATED_STRING,
+    UNEXPECTED_CHARACTER,
+    UNKNOWN_ESCAPE,
+    INVALID_NUMBER,
+    UNTERMINATED_REGEX,
+    INVALID_REGEX,
+    EXPECTED_SEMICOLON,
+    EXPECTED_RIGHT_PAREN,
+    EXPECTED_LEFT_PAREN,
+    EXPECTED_RIGHT_BRACE,
+    EXPECTED_LEFT_BRACE,
+    EXPECTED_RIGHT_BRACKET,
+    EXPECTED_COLON,
+    EXPECTED_FAT_ARROW,
+    EXPECTED_IDENTIFIER,
+    EXPECTED_EXPRESSION,
+    INVALID_ASSIGNMENT_TARGET,
+    RETURN_OUTSIDE_BOOFER,
+    TRY_WITHOUT_HANDLER,
+    EXPECTED_PROPERTY_NAME,
+    INVALID_MAP_KEY,
+    EXPECTED_PATTERN,
+    NON_NUMERIC_RANGE_PATTERN,
+    UNKNOWN_TYPE,
+    MATCH_WITHOUT_DEFAULT,
+    READ_IN_OWN_INITIALIZER,
+    DUPLICATE_DECLARATION,
+    TYPE_ERROR,
+    UNDEFINED_NAME,
+    ARITY_MISMATCH,
+    INVALID_VALUE,
+    RUNTIME_IO,
+    UNCAUGHT_THROW,
+    STACK_OVERFLOW,
+    FILE_UNREADABLE,
+    INVALID_ARGUMENTS,
+    UNUSED_VARIABLE,
+    UNREACHABLE_CODE,
+    CONSTANT_CONDITION,
+    SELF_ASSIGNMENT,
+    NIL_COMPARISON,
+    SHADOWED_VARIABLE,
+    OPERAND_TYPE_MISMATCH,
+    DECLARED_TYPE_MISMATCH,
+    ARGUMENT_TYPE_MISMATCH,
+    ARGUMENT_COUNT_MISMATCH,
+    RETURN_TYPE_MISMATCH,
+    NOT_CALLABLE,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_are_unique_and_in_order() {
+        for pair in ALL.windows(2) {
+            assert!(
+                pair[0].id < pair[1].id,
+                "{} before {}",
+                pair[0].id,
+                pair[1].id
+            );
+        }
+        for code in ALL {
+            let digits = code.id.strip_prefix('B').unwrap();
+            assert!(
+                digits.len() == 4 && digits.chars().all(|c| c.is_ascii_digit()),
+                "{}",
+                code.id
+            );
+        }
+    }
+
+    #[test]
+    fn explanations_show_wrong_and_correct_code() {
+        for code in ALL {
+            assert!(!code.title.is_empty(), "{}", code.id);
+            let wrong = code.explanation.find("\n\nWrong:\n\n");
+            let correct = code.explanation.find("\n\nCorrect:\n\n");
+            assert!(
+                matches!((wrong, correct), (Some(wrong), Some(correct)) if wrong < correct),
+                "{}",
+                code.id
+            );
+        }
+    }
+
+    #[test]
+    fn lookup_ignores_case() {
+        assert_eq!(ErrorCode::lookup("B0307"), Some(STACK_OVERFLOW));
+        assert_eq!(ErrorCode::lookup("b0001"), Some(UNTERMINATED_STRING));
+        assert_eq!(ErrorCode::lookup("B9999"), None);
+        assert_eq!(ErrorCode::lookup(""), None);
+    }
+}
@@ -0,0 +1,388 @@
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+use crate::{
+    error::{ErrorKind, RuntimeError},
+    expr::LiteralValue,
+    function::{Arity, NativeFn},
+    interpreter::Interpreter,
+    regex::Regex,
+};
+
+/// Conversion of a Rust value into a boof value.
+pub trait ToBoof {
+    fn to_boof(self) -> LiteralValue;
+}
+
+/// Conversion of a boof value into a Rust value, failing when the value has
+/// the wrong type.
+pub trait FromBoof: Sized {
+    /// Whether a missing trailing argument may be converted from `nil`
+    /// instead of being a call error.
+    const OPTIONAL: bool = false;
+
+    fn from_boof(value: LiteralValue) -> Result<Self, RuntimeError>;
+}
+
+fn type_error(expected: &str, value: &LiteralValue) -> RuntimeError {
+    RuntimeError::native(format!("expected {expected}, found {}", value.type_name()))
+        .with_kind(ErrorKind::Type)
+}
+
+impl ToBoof for LiteralValue {
+    fn to_boof(self) -> LiteralValue {
+        self
+    }
+}
+
+impl FromBoof for LiteralValue {
+    fn from_boof(value: LiteralValue) -> Result<Self, RuntimeError> {
+        Ok(value)
+    }
+}
+
+impl ToBoof for () {
+    fn to_boof(self) -> LiteralValue {
+        LiteralValue::Nil
+    }
+}
+
+impl ToBoof for bool {
+    fn to_boof(self) -> LiteralValue {
+        if self {
+            LiteralValue::True
+        } else {
+            LiteralValue::False
+        }
+    }
+}
+
+impl FromBoof for bool {
+    fn from_boof(value: LiteralValue) -> Result<Self, RuntimeError> {
+        match value {
+            LiteralValue::True => Ok(true),
+            LiteralValue::False => Ok(false),
+            value => Err(type_error("bool", &value)),
+        }
+    }
+}
+
+impl ToBoof for f64 {
+    fn to_boof(self) -> LiteralValue {
+        LiteralValue::Number(self)
+    }
+}
+
+impl FromBoof for f64 {
+    fn from_boof(value: LiteralValue) -> Result<Self, RuntimeError> {
+        match value {
+            LiteralValue::Number(n) => Ok(n),
+            value => Err(type_error("number", &value)),
+        }
+    }
+}
+
+impl ToBoof for f32 {
+    fn to_boof(self) -> LiteralValue {
+        LiteralValue::Number(self.into())
+    }
+}
+
+impl FromBoof for f32 {
+    fn from_boof(value: LiteralValue) -> Result<Self, RuntimeError> {
+        f64::from_boof(value).map(|n| n as f32)
+    }
+}
+
+/// Integers convert from numbers with no fractional part that fit the type.
+/// The upper bound is `MAX + 1`, a power of two that `f64` holds exactly,
+/// because `MAX` itself rounds up to it for 64-bit types.
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl ToBoof for $t {
+                fn to_boof(self) -> LiteralValue {
+                    LiteralValue::Number(self as f64)
+                }
+            }
+
+            impl FromBoof for $t {
+                fn from_boof(value: LiteralValue) -> Result<Self, RuntimeError> {
+                    let n = f64::from_boof(value)?;
+                    let end = <$t>::MAX as f64 + 1.0;
+                    if n.fract() != 0.0 || n < <$t>::MIN as f64 || n >= end {
+                        return Err(RuntimeError::native(format!(
+                            "expected integer in range of {}, found {n}",
+                            stringify!($t)
+                        )));
+                    }
+                    Ok(n as $t)
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl ToBoof for String {
+    fn to_boof(self) -> LiteralValue {
+        LiteralValue::String(self)
+    }
+}
+
+impl ToBoof for &str {
+    fn to_boof(self) -> LiteralValue {
+        LiteralValue::String(self.to_string())
+    }
+}
+
+impl FromBoof for String {
+    fn from_boof(value: LiteralValue) -> Result<Self, RuntimeError> {
+        match value {
+            LiteralValue::String(s) => Ok(s),
+            value => Err(type_error("string", &value)),
+        }
+    }
+}
+
+impl ToBoof for Rc<Regex> {
+    fn to_boof(self) -> LiteralValue {
+        LiteralValue::Regex(self)
+    }
+}
+
+/// A regex argument may also be given as a pattern string, compiled without
+/// flags.
+impl FromBoof for Rc<Regex> {
+    fn from_boof(value: LiteralValue) -> Result<Self, RuntimeError> {
+        match value {
+            LiteralValue::Regex(regex) => Ok(regex),
+            LiteralValue::String(pattern) => Regex::new(&pattern, "")
+                .map(Rc::new)
+                .map_err(|err| RuntimeError::native(format!("invalid regex: {err}"))),
+            value => Err(type_error("regex", &value)),
+        }
+    }
+}
+
+impl<T: ToBoof> ToBoof for Vec<T> {
+    fn to_boof(self) -> LiteralValue {
+        LiteralValue::List(self.into_iter().map(ToBoof::to_boof).collect())
+    }
+}
+
+impl<T: FromBoof> FromBoof for Vec<T> {
+    fn from_boof(value: LiteralValue) -> Result<Self, RuntimeError> {
+        match value {
+            LiteralValue::List(items) => items.into_iter().map(T::from_boof).collect(),
+            value => Err(type_error("list", &value)),
+        }
+    }
+}
+
+impl<T: ToBoof> ToBoof for HashMap<String, T> {
+    fn to_boof(self) -> LiteralValue {
+        LiteralValue::Map(self.into_iter().map(|(k, v)| (k, v.to_boof())).collect())
+    }
+}
+
+impl<T: FromBoof> FromBoof for HashMap<String, T> {
+    fn from_boof(value: LiteralValue) -> Result<Self, RuntimeError> {
+        match value {
+            LiteralValue::Map(entries) => entries
+                .into_iter()
+                .map(|(k, v)| Ok((k, T::from_boof(v)?)))
+                .collect(),
+            value => Err(type_error("map", &value)),
+        }
+    }
+}
+
+impl<T: ToBoof> ToBoof for BTreeMap<String, T> {
+    fn to_boof(self) -> LiteralValue {
+        LiteralValue::Map(self.into_iter().map(|(k, v)| (k, v.to_boof())).collect())
+    }
+}
+
+impl<T: FromBoof> FromBoof for BTreeMap<String, T> {
+    fn from_boof(value: LiteralValue) -> Result<Self, RuntimeError> {
+        match value {
+            LiteralValue::Map(entries) => entries
+                .into_iter()
+                .map(|(k, v)| Ok((k, T::from_boof(v)?)))
+                .collect(),
+            value => Err(type_error("map", &value)),
+        }
+    }
+}
+
+/// `None` and `nil` convert to each other.
+impl<T: ToBoof> ToBoof for Option<T> {
+    fn to_boof(self) -> LiteralValue {
+        match self {
+            Some(value) => value.to_boof(),
+            None => LiteralValue::Nil,
+        }
+    }
+}
+
+impl<T: FromBoof> FromBoof for Option<T> {
+    const OPTIONAL: bool = true;
+
+    fn from_boof(value: LiteralValue) -> Result<Self, RuntimeError> {
+        match value {
+            LiteralValue::Nil => Ok(None),
+            value => T::from_boof(value).map(Some),
+        }
+    }
+}
+
+/// Return types allowed for closures passed to [`Interpreter::register`]:
+/// any [`ToBoof`] value, or a `Result` of one to raise a runtime error.
+pub trait NativeResult {
+    fn into_result(self) -> Result<LiteralValue, RuntimeError>;
+}
+
+impl<T: ToBoof> NativeResult for T {
+    fn into_result(self) -> Result<LiteralValue, RuntimeError> {
+        Ok(self.to_boof())
+    }
+}
+
+impl<T: ToBoof> NativeResult for Result<T, RuntimeError> {
+    fn into_result(self) -> Result<LiteralValue, RuntimeError> {
+        self.map(ToBoof::to_boof)
+    }
+}
+
+/// Converts the argument at `index` for the native boofer `function`, where a
+/// missing argument is treated as `nil`.
+pub fn argument<T: FromBoof>(
+    function: &str,
+    args: &[LiteralValue],
+    index: usize,
+) -> Result<T, RuntimeError> {
+    let value = args.get(index).cloned().unwrap_or(LiteralValue::Nil);
+    T::from_boof(value).map_err(|err| {
+        RuntimeError::native(format!(
+            "Argument {} to {function}(): {}",
+            index + 1,
+            err.message
+        ))
+        .with_kind(err.kind)
+    })
+}
+
+/// Rust closures that can be registered as native boofers. `Args` is the
+/// tuple of argument types, which lets closures of every arity implement it.
+pub trait IntoNative<Args> {
+    /// Trailing `Option` arguments may be left off by the caller.
+    fn arity() -> Arity;
+
+    fn into_native(self, name: &str) -> Box<NativeFn>;
+}
+
+macro_rules! impl_into_native {
+    ($arity:literal $(, $arg:ident)*) => {
+        impl<F, R, $($arg),*> IntoNative<($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> R + 'static,
+            R: NativeResult,
+            $($arg: FromBoof,)*
+        {
+            fn arity() -> Arity {
+                let optional: [bool; $arity] = [$($arg::OPTIONAL),*];
+                let required = optional.iter().rposition(|o| !o).map_or(0, |i| i + 1);
+                if required == $arity {
+                    Arity::Fixed($arity)
+                } else {
+                    Arity::Range(required, $arity)
+                }
+            }
+
+            #[allow(non_snake_case, unused_variables, unused_mut, unused_assignments)]
+            fn into_native(self, name: &str) -> Box<NativeFn> {
+                let name = name.to_string();
+                Box::new(move |_: &mut Interpreter, args: &[LiteralValue]| {
+                    let mut index = 0;
+                    $(
+                        let $arg = argument::<$arg>(&name, args, index)?;
+                        index += 1;
+                    )*
+                    self($($arg),*).into_result()
+                })
+            }
+        }
+    };
+}
+
+impl_into_native!(0);
+impl_into_native!(1, A);
+impl_into_native!(2, A, B);
+impl_into_native!(3, A, B, C);
+impl_into_native!(4, A, B, C, D);
+impl_into_native!(5, A, B, C, D, E);
+impl_into_native!(6, A, B, C, D, E, G);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number<T: FromBoof>(n: f64) -> Result<T, String> {
+        T::from_boof(LiteralValue::Number(n)).map_err(|err| err.message)
+    }
+
+    #[test]
+    fn integers_convert_up_to_their_bounds() {
+        assert_eq!(number::<u8>(255.0), Ok(255));
+        assert_eq!(number::<i8>(-128.0), Ok(-128));
+        assert_eq!(number::<i32>(-2147483648.0), Ok(i32::MIN));
+        assert_eq!(number::<i64>(-9223372036854775808.0), Ok(i64::MIN));
+        assert_eq!(number::<u64>(0.0), Ok(0));
+        // The largest doubles below 2^63 and 2^64.
+        assert_eq!(
+            number::<i64>(9223372036854774784.0),
+            Ok(9223372036854774784)
+        );
+        assert_eq!(
+            number::<u64>(18446744073709549568.0),
+            Ok(18446744073709549568)
+        );
+    }
+
+    #[test]
+    fn integers_past_their_bounds_are_rejected() {
+        assert_eq!(
+            number::<u8>(256.0),
+            Err("expected integer in range of u8, found 256".to_string())
+        );
+        assert!(number::<i8>(-129.0).is_err());
+        assert!(number::<u32>(-1.0).is_err());
+        assert_eq!(
+            number::<i64>(9223372036854775808.0),
+            Err("expected integer in range of i64, found 9223372036854776000".to_string())
+        );
+        assert!(number::<i64>(-9223372036854777856.0).is_err());
+        assert!(number::<u64>(18446744073709551616.0).is_err());
+        assert!(number::<usize>(f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn integers_reject_fractions_and_nan() {
+        assert_eq!(
+            number::<i32>(1.5),
+            Err("expected integer in range of i32, found 1.5".to_string())
+        );
+        assert!(number::<u64>(-0.5).is_err());
+        assert!(number::<i64>(f64::NAN).is_err());
+        assert_eq!(number::<i32>(-0.0), Ok(0));
+    }
+
+    #[test]
+    fn integers_reject_other_types() {
+        let err = i64::from_boof(LiteralValue::String("1".to_string())).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Type);
+        assert_eq!(err.message, "expected number, found string");
+    }
+}
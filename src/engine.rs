@@ -0,0 +1,217 @@
+use std::fs;
+use std::path::Path;
+
+use crate::{
+    checker::Checker,
+    convert::IntoNative,
+    error::{Error, RuntimeError, Warning},
+    expr::LiteralValue,
+    function::Arity,
+    interpreter::Interpreter,
+    lexer::Lexer,
+    lint::Linter,
+    optimizer,
+    parser::Parser,
+    resolver::Resolver,
+    stmt::Stmt,
+    token::Token,
+};
+
+type WarningHandler = Box<dyn FnMut(&Warning)>;
+
+/// Runs boof source against a single interpreter, so globals defined by one
+/// call are visible to the next.
+///
+/// Scripts run on the calling thread's stack. Boofer calls that nest too
+/// deep for it end in a catchable stack overflow error once they use 1 MiB
+/// of it, which any thread with Rust's default 2 MiB stack can afford.
+/// Hosts that want deeper recursion can run the engine on a bigger stack
+/// and raise the limit with [`set_stack_limit`](Engine::set_stack_limit).
+pub struct Engine {
+    interpreter: Interpreter,
+    warnings: Vec<Warning>,
+    optimize: bool,
+    /// Receives parse warnings as soon as they are found, in place of
+    /// `warnings`.
+    on_warning: Option<WarningHandler>,
+    /// How many lines `eval_line` has run, so each call continues the
+    /// numbering of the last.
+    lines: usize,
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Engine {
+            interpreter: Interpreter::new(),
+            warnings: vec![],
+            optimize: true,
+            on_warning: None,
+            lines: 0,
+        }
+    }
+
+    /// Runs `source` and returns the value of its final statement if that is
+    /// an expression, or `nil` otherwise. Errors count lines from the start
+    /// of `source`.
+    pub fn eval(&mut self, source: &str) -> Result<LiteralValue, Error> {
+        self.evaluate(source, 1)
+    }
+
+    /// Runs a line typed at a prompt like [`eval`](Engine::eval), but numbers
+    /// its lines after those of earlier calls, so errors and warnings tell
+    /// the prompt's lines apart.
+    pub fn eval_line(&mut self, source: &str) -> Result<LiteralValue, Error> {
+        let first_line = self.lines + 1;
+        self.lines += source.lines().count().max(1);
+        self.evaluate(source, first_line)
+    }
+
+    fn evaluate(&mut self, source: &str, first_line: usize) -> Result<LiteralValue, Error> {
+        let mut statements = self.parse(source, first_line)?;
+        let mut last = match statements.last() {
+            Some(Stmt::Expression(_)) => statements.pop(),
+            _ => None,
+        };
+        // The final expression is set aside first, so folding an `if` into
+        // its branch can't turn it into a value to return.
+        if self.optimize {
+            optimizer::optimize(&mut statements);
+            if let Some(Stmt::Expression(expr)) = &mut last {
+                optimizer::optimize_expression(expr);
+            }
+        }
+        let runtime_error = |err| Error::runtime_at(err, source, first_line, "<input>");
+        self.interpreter
+            .interpret(&statements)
+            .map_err(runtime_error)?;
+        match last {
+            Some(Stmt::Expression(expr)) => self.interpreter.evaluate(&expr).map_err(runtime_error),
+            _ => Ok(LiteralValue::Nil),
+        }
+    }
+
+    pub fn run_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(|e| Error::io("read file", path, &e))?;
+        let mut statements = self.parse(&source, 1)?;
+        if self.optimize {
+            optimizer::optimize(&mut statements);
+        }
+        self.interpreter
+            .interpret(&statements)
+            .map_err(|err| Error::runtime(err, &source, &path.display().to_string()))
+    }
+
+    /// Checks the script at `path` for likely mistakes without running it.
+    /// The lints are added to the warnings returned by
+    /// [`take_warnings`](Engine::take_warnings), in source order with the
+    /// parser's own warnings.
+    pub fn lint_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(|e| Error::io("read file", path, &e))?;
+        let mut lexer = Lexer::from(source);
+        let statements = self.parse_tokens(lexer.scan()?)?;
+        let allowed = lexer.allowed_lints().clone();
+        self.warnings.extend(Linter::new(allowed).lint(&statements));
+        self.warnings
+            .sort_by_key(|warning| (warning.span.line, warning.span.column));
+        Ok(())
+    }
+
+    /// Turns constant folding and dead code removal on or off before
+    /// scripts run. It is on by default, and turning it off runs scripts
+    /// exactly as parsed.
+    pub fn set_optimize(&mut self, optimize: bool) {
+        self.optimize = optimize;
+    }
+
+    /// Checks the types in the script at `path` without running it,
+    /// returning any type errors in source order. Parse warnings are kept
+    /// for [`take_warnings`](Engine::take_warnings).
+    pub fn check_file<P: AsRef<Path>>(&mut self, path: P) -> Result<Vec<Error>, Error> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(|e| Error::io("read file", path, &e))?;
+        let statements = self.parse(&source, 1)?;
+        Ok(Checker::new().check(&statements))
+    }
+
+    pub fn set_global(&mut self, name: &str, value: LiteralValue) {
+        self.interpreter.define_global(name, value);
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<LiteralValue> {
+        self.interpreter.get_global(name)
+    }
+
+    pub fn register_native<F>(&mut self, name: &str, arity: Arity, function: F)
+    where
+        F: Fn(&mut Interpreter, &[LiteralValue]) -> Result<LiteralValue, RuntimeError> + 'static,
+    {
+        self.interpreter.register_native(name, arity, function);
+    }
+
+    /// Exposes a Rust closure with typed arguments, such as
+    /// `engine.register("add", |a: f64, b: f64| a + b)`.
+    pub fn register<F, Args>(&mut self, name: &str, function: F)
+    where
+        F: IntoNative<Args>,
+    {
+        self.interpreter.register(name, function);
+    }
+
+    /// Seeds the `random` module so scripts produce the same output each run.
+    pub fn seed_random(&mut self, seed: u64) {
+        self.interpreter.seed_random(seed);
+    }
+
+    /// Sets how many bytes of the calling thread's stack nested boofer calls
+    /// may use, 1 MiB by default. See [`Interpreter::set_stack_limit`].
+    pub fn set_stack_limit(&mut self, bytes: usize) {
+        self.interpreter.set_stack_limit(bytes);
+    }
+
+    /// Sets the arguments scripts receive from `args()`.
+    pub fn set_script_args(&mut self, args: Vec<String>) {
+        self.interpreter.set_script_args(args);
+    }
+
+    /// Passes each parse warning to `handler` as soon as the parser reports
+    /// it, before the script runs, instead of keeping it for
+    /// [`take_warnings`](Engine::take_warnings).
+    pub fn set_warning_handler<F>(&mut self, handler: F)
+    where
+        F: FnMut(&Warning) + 'static,
+    {
+        self.on_warning = Some(Box::new(handler));
+    }
+
+    /// Returns the warnings reported while parsing since the last call.
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    fn parse(&mut self, source: &str, first_line: usize) -> Result<Vec<Stmt>, Error> {
+        let tokens = Lexer::from_line(source.to_string(), first_line).scan()?;
+        self.parse_tokens(tokens)
+    }
+
+    fn parse_tokens(&mut self, tokens: Vec<Token>) -> Result<Vec<Stmt>, Error> {
+        let mut parser = Parser::from(tokens);
+        let statements = parser.parse();
+        for warning in parser.take_warnings() {
+            match &mut self.on_warning {
+                Some(handler) => handler(&warning),
+                None => self.warnings.push(warning),
+            }
+        }
+        let statements = statements?;
+        Resolver::new().resolve(&statements)?;
+        Ok(statements)
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
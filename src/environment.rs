@@ -0,0 +1,86 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::expr::LiteralValue;
+
+#[derive(Default)]
+pub struct Environment {
+    values: HashMap<String, LiteralValue>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from(enclosing: Rc<RefCell<Environment>>) -> Self {
+        Environment {
+            values: HashMap::new(),
+            enclosing: Some(enclosing),
+        }
+    }
+
+    pub fn define(&mut self, name: String, value: LiteralValue) {
+        self.values.insert(name, value);
+    }
+
+    /// Updates an existing variable in the nearest scope that defines it,
+    /// returning `false` if no scope does.
+    pub fn assign(&mut self, name: &str, value: LiteralValue) -> bool {
+        if let Some(slot) = self.values.get_mut(name) {
+            *slot = value;
+            return true;
+        }
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+            None => false,
+        }
+    }
+
+    /// Every variable visible from this scope, for suggesting corrections
+    /// to misspelled names.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.values.keys().cloned().collect();
+        if let Some(enclosing) = &self.enclosing {
+            names.extend(enclosing.borrow().names());
+        }
+        names
+    }
+
+    /// Updates a variable in the scope `depth` levels out from this one.
+    pub fn assign_at(&mut self, depth: usize, name: &str, value: LiteralValue) -> bool {
+        if depth > 0 {
+            return match &self.enclosing {
+                Some(enclosing) => enclosing.borrow_mut().assign_at(depth - 1, name, value),
+                None => false,
+            };
+        }
+        match self.values.get_mut(name) {
+            Some(slot) => {
+                *slot = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Reads a variable from the scope `depth` levels out from this one.
+    pub fn get_at(&self, depth: usize, name: &str) -> Option<LiteralValue> {
+        if depth > 0 {
+            return self.enclosing.as_ref()?.borrow().get_at(depth - 1, name);
+        }
+        self.values.get(name).cloned()
+    }
+
+    pub fn get(&self, name: &str) -> Option<LiteralValue> {
+        match self.values.get(name) {
+            Some(value) => Some(value.clone()),
+            None => self
+                .enclosing
+                .as_ref()
+                .and_then(|enclosing| enclosing.borrow().get(name)),
+        }
+    }
+}
@@ -1,29 +1,647 @@
-const ERROR_USAGE: i32 = 64;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::{io, path::Path};
 
-pub struct Error {
-    pub code: i32,
+use crate::{
+    codes::{self, ErrorCode},
+    expr::LiteralValue,
+    stdlib::write_json_string,
+    token::Token,
+};
+
+/// Exit codes from BSD `sysexits.h`.
+const EX_USAGE: i32 = 64;
+const EX_DATAERR: i32 = 65;
+const EX_SOFTWARE: i32 = 70;
+const EX_IOERR: i32 = 74;
+
+/// A position in source text. Lines and columns count from 1, and columns
+/// count chars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// What an error says and where, shared by every kind of [`Error`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub code: &'static ErrorCode,
     pub message: String,
+    pub span: Option<Span>,
+    /// Further detail printed after the message, such as a stack trace.
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(code: &'static ErrorCode, message: String, span: Option<Span>) -> Self {
+        Diagnostic {
+            code,
+            message,
+            span,
+            notes: vec![],
+        }
+    }
+}
+
+/// Any failure reported by the boof library or executable. The variant says
+/// which stage failed and determines the process exit code.
+#[derive(Debug)]
+pub enum Error {
+    /// Source text that can't be split into tokens.
+    Lex(Diagnostic),
+    /// Tokens that don't form a valid program.
+    Parse(Diagnostic),
+    /// A program that parses but is invalid, such as one that reads a
+    /// variable in its own initializer.
+    Resolve(Diagnostic),
+    /// A program that `boof check` found would fail when run, such as one
+    /// that subtracts from a string.
+    Type(Diagnostic),
+    /// An error raised while running that no `catch` handled.
+    Runtime(ErrorKind, Diagnostic),
+    /// A failure to read a script or other input.
+    Io(Diagnostic),
+    /// Invalid command line arguments.
+    Usage(Diagnostic),
+    /// `exit(code)` ended the script.
+    Exit(i32),
 }
 
 impl Error {
     pub fn usage() -> Self {
-        Error {
-            code: ERROR_USAGE,
-            message: "Usage: boof [script]".to_string(),
+        Error::Usage(Diagnostic::new(
+            codes::INVALID_ARGUMENTS,
+            "Usage: boof [--seed n] [--no-opt] [--error-format human|json] [script [args...]]\n       boof lint [--error-format human|json] script\n       boof check [--error-format human|json] script\n       boof --explain code"
+                .to_string(),
+            None,
+        ))
+    }
+
+    pub fn lexing(code: &'static ErrorCode, message: String, span: Span) -> Self {
+        Error::Lex(Diagnostic::new(code, message, Some(span)))
+    }
+
+    pub fn parsing(code: &'static ErrorCode, message: String, span: Span) -> Self {
+        Error::Parse(Diagnostic::new(code, message, Some(span)))
+    }
+
+    pub fn resolving(code: &'static ErrorCode, message: String, span: Span) -> Self {
+        Error::Resolve(Diagnostic::new(code, message, Some(span)))
+    }
+
+    pub fn checking(code: &'static ErrorCode, message: String, span: Span) -> Self {
+        Error::Type(Diagnostic::new(code, message, Some(span)))
+    }
+
+    pub fn io(action: &str, path: &Path, err: &io::Error) -> Self {
+        Error::Io(Diagnostic::new(
+            codes::FILE_UNREADABLE,
+            io_message(action, path, err),
+            None,
+        ))
+    }
+
+    /// Reports a runtime error that reached the top of a script, noting the
+    /// failing line of `source` and a stack trace naming `file`.
+    pub fn runtime(err: RuntimeError, source: &str, file: &str) -> Self {
+        Error::runtime_at(err, source, 1, file)
+    }
+
+    /// Like [`runtime`](Error::runtime), for a `source` whose lines are
+    /// numbered from `first_line`.
+    pub fn runtime_at(err: RuntimeError, source: &str, first_line: usize, file: &str) -> Self {
+        let notes = match err.exit_code {
+            Some(_) => vec![],
+            None => err.traceback(source, first_line, file),
+        };
+        let mut error = Error::from(err);
+        if let Some(diagnostic) = error.diagnostic_mut() {
+            diagnostic.notes = notes;
+        }
+        error
+    }
+
+    /// The stage that failed, such as `"parse"` or `"runtime"`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Lex(_) => "lex",
+            Error::Parse(_) => "parse",
+            Error::Resolve(_) => "resolve",
+            Error::Type(_) => "type",
+            Error::Runtime(..) => "runtime",
+            Error::Io(_) => "io",
+            Error::Usage(_) => "usage",
+            Error::Exit(_) => "exit",
+        }
+    }
+
+    /// The process exit code for this error.
+    pub fn code(&self) -> i32 {
+        match self {
+            Error::Lex(_) | Error::Parse(_) | Error::Resolve(_) | Error::Type(_) => EX_DATAERR,
+            Error::Runtime(..) => EX_SOFTWARE,
+            Error::Io(_) => EX_IOERR,
+            Error::Usage(_) => EX_USAGE,
+            Error::Exit(code) => *code,
+        }
+    }
+
+    /// The message, position and notes, for every error except `Exit`.
+    pub fn diagnostic(&self) -> Option<&Diagnostic> {
+        match self {
+            Error::Lex(diagnostic)
+            | Error::Parse(diagnostic)
+            | Error::Resolve(diagnostic)
+            | Error::Type(diagnostic)
+            | Error::Runtime(_, diagnostic)
+            | Error::Io(diagnostic)
+            | Error::Usage(diagnostic) => Some(diagnostic),
+            Error::Exit(_) => None,
+        }
+    }
+
+    /// The error as a one line JSON object with its `severity`, `code`,
+    /// `message`, `file`, `span` and `notes`. `Exit` gives an empty string.
+    pub fn to_json(&self, file: Option<&str>) -> String {
+        match self.diagnostic() {
+            Some(diagnostic) => json_diagnostic("error", diagnostic, file),
+            None => String::new(),
+        }
+    }
+
+    /// Adds notes to the diagnostic. `Exit` is returned unchanged.
+    pub fn with_notes(mut self, notes: impl IntoIterator<Item = String>) -> Self {
+        if let Some(diagnostic) = self.diagnostic_mut() {
+            diagnostic.notes.extend(notes);
         }
+        self
+    }
+
+    fn diagnostic_mut(&mut self) -> Option<&mut Diagnostic> {
+        match self {
+            Error::Lex(diagnostic)
+            | Error::Parse(diagnostic)
+            | Error::Resolve(diagnostic)
+            | Error::Type(diagnostic)
+            | Error::Runtime(_, diagnostic)
+            | Error::Io(diagnostic)
+            | Error::Usage(diagnostic) => Some(diagnostic),
+            Error::Exit(_) => None,
+        }
+    }
+}
+
+/// Prints the error as `[line: N] Error[B0000]: message` followed by its
+/// notes.
+/// `Exit` prints nothing.
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some(diagnostic) = self.diagnostic() else {
+            return Ok(());
+        };
+        if let Some(span) = diagnostic.span {
+            write!(f, "[line: {}] ", span.line)?;
+        }
+        let code = diagnostic.code.id;
+        match self {
+            Error::Lex(_) | Error::Parse(_) | Error::Resolve(_) | Error::Type(_) => {
+                write!(f, "Error[{code}]: ")?
+            }
+            Error::Runtime(..) => write!(f, "Runtime Error[{code}]: ")?,
+            _ => (),
+        }
+        write!(f, "{}", diagnostic.message)?;
+        for note in &diagnostic.notes {
+            write!(f, "\n{note}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A problem worth reporting that doesn't stop the script from running.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub code: &'static ErrorCode,
+    pub message: String,
+    pub span: Span,
+}
+
+impl Warning {
+    /// The warning as a one line JSON object, in the same shape as
+    /// [`Error::to_json`].
+    pub fn to_json(&self, file: Option<&str>) -> String {
+        let diagnostic = Diagnostic::new(self.code, self.message.clone(), Some(self.span));
+        json_diagnostic("warning", &diagnostic, file)
     }
+}
 
-    pub fn lexing(message: String, line: usize) -> Self {
-        Error {
-            code: 1,
-            message: format!("[line: {line}] Error: {message}"),
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[line: {}] Warning[{}]: {}",
+            self.span.line, self.code.id, self.message
+        )
+    }
+}
+
+fn json_diagnostic(severity: &str, diagnostic: &Diagnostic, file: Option<&str>) -> String {
+    let mut out = String::from("{\"severity\":");
+    write_json_string(&mut out, severity);
+    out.push_str(",\"code\":");
+    write_json_string(&mut out, diagnostic.code.id);
+    out.push_str(",\"message\":");
+    write_json_string(&mut out, &diagnostic.message);
+    out.push_str(",\"file\":");
+    match file {
+        Some(file) => write_json_string(&mut out, file),
+        None => out.push_str("null"),
+    }
+    out.push_str(",\"span\":");
+    match diagnostic.span {
+        Some(span) => out.push_str(&format!(
+            "{{\"line\":{},\"column\":{}}}",
+            span.line, span.column
+        )),
+        None => out.push_str("null"),
+    }
+    out.push_str(",\"notes\":[");
+    for (i, note) in diagnostic.notes.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
         }
+        write_json_string(&mut out, note);
     }
+    out.push_str("]}");
+    out
+}
+
+/// Describes a failed file operation, e.g. `Failed to read file "a.boof": ...`.
+pub fn io_message(action: &str, path: &Path, err: &io::Error) -> String {
+    format!("Failed to {action} {path:?}: {err}")
+}
 
-    pub fn parsing(message: String) -> Self {
-        Error {
-            code: 1,
-            message: format!("Error: {message}"),
+/// The category of a runtime error, seen by `catch` as the error's `kind`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorKind {
+    /// A value of the wrong type, such as `"a" - 1`.
+    Type,
+    /// An undefined variable, key or method.
+    Name,
+    /// A call with the wrong number of arguments.
+    Arity,
+    /// A value of the right type that can't be used, such as malformed JSON.
+    Value,
+    /// A failed file or stream operation.
+    Io,
+    /// Boofer calls nested deeper than the interpreter allows, usually
+    /// recursion that never stops.
+    Recursion,
+    /// A value raised by `throw`.
+    Thrown,
+}
+
+impl ErrorKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorKind::Type => "type",
+            ErrorKind::Name => "name",
+            ErrorKind::Arity => "arity",
+            ErrorKind::Value => "value",
+            ErrorKind::Io => "io",
+            ErrorKind::Recursion => "recursion",
+            ErrorKind::Thrown => "thrown",
         }
     }
+
+    /// The diagnostic code reported when an error of this kind goes uncaught.
+    pub fn code(self) -> &'static ErrorCode {
+        match self {
+            ErrorKind::Type => codes::TYPE_ERROR,
+            ErrorKind::Name => codes::UNDEFINED_NAME,
+            ErrorKind::Arity => codes::ARITY_MISMATCH,
+            ErrorKind::Value => codes::INVALID_VALUE,
+            ErrorKind::Io => codes::RUNTIME_IO,
+            ErrorKind::Recursion => codes::STACK_OVERFLOW,
+            ErrorKind::Thrown => codes::UNCAUGHT_THROW,
+        }
+    }
+}
+
+/// A `boofer` call that an error unwound through.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// The boofer's name, or `<lambda>` for anonymous boofers.
+    pub function: String,
+    /// Where the boofer was called from, unless a native boofer called it.
+    pub call_site: Option<Span>,
+}
+
+#[derive(Debug)]
+pub struct RuntimeError {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub span: Option<Span>,
+    /// Hints shown after the message, such as a suggested spelling.
+    pub notes: Vec<String>,
+    /// The calls the error unwound through, innermost first.
+    pub trace: Vec<Frame>,
+    /// Set by `exit(code)`, which unwinds like an error but ends the script
+    /// quietly with `code`.
+    pub exit_code: Option<i32>,
+    /// The value given to `throw`, which `catch` receives unchanged.
+    pub thrown: Option<Box<LiteralValue>>,
+}
+
+impl RuntimeError {
+    /// An error raised at `token`.
+    pub fn new(message: String, token: &Token) -> Self {
+        RuntimeError {
+            span: Some(token.span()),
+            ..RuntimeError::native(message)
+        }
+    }
+
+    /// An error raised by a native boofer, which is given the line of its
+    /// call site by the interpreter.
+    pub fn native(message: String) -> Self {
+        RuntimeError {
+            kind: ErrorKind::Value,
+            message,
+            span: None,
+            notes: vec![],
+            trace: vec![],
+            exit_code: None,
+            thrown: None,
+        }
+    }
+
+    pub fn exit(code: i32) -> Self {
+        RuntimeError {
+            exit_code: Some(code),
+            ..RuntimeError::native(String::new())
+        }
+    }
+
+    /// An error carrying a value from `throw`. Its message is the value's
+    /// `message` key if it has one, so rethrown errors read the same.
+    pub fn thrown(value: LiteralValue, keyword: &Token) -> Self {
+        let message = match &value {
+            LiteralValue::Map(entries) => match entries.get("message") {
+                Some(LiteralValue::String(message)) => message.clone(),
+                _ => value.to_string(),
+            },
+            value => value.to_string(),
+        };
+        RuntimeError {
+            kind: ErrorKind::Thrown,
+            thrown: Some(Box::new(value)),
+            ..RuntimeError::new(message, keyword)
+        }
+    }
+
+    pub fn with_kind(mut self, kind: ErrorKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn with_notes(mut self, notes: impl IntoIterator<Item = String>) -> Self {
+        self.notes.extend(notes);
+        self
+    }
+
+    /// Whether `catch` may handle this error; `exit` can't be caught.
+    pub fn is_catchable(&self) -> bool {
+        self.exit_code.is_none()
+    }
+
+    /// The value bound by `catch`: the thrown value itself, or a map with the
+    /// error's `kind`, `message`, `line` and `column`.
+    pub fn to_value(&self) -> LiteralValue {
+        if let Some(value) = &self.thrown {
+            return value.as_ref().clone();
+        }
+        let number = |n: Option<usize>| match n {
+            Some(n) => LiteralValue::Number(n as f64),
+            None => LiteralValue::Nil,
+        };
+        let span = self.span;
+        LiteralValue::Map(BTreeMap::from([
+            (
+                "kind".to_string(),
+                LiteralValue::String(self.kind.name().to_string()),
+            ),
+            (
+                "message".to_string(),
+                LiteralValue::String(self.message.clone()),
+            ),
+            ("line".to_string(), number(span.map(|span| span.line))),
+            ("column".to_string(), number(span.map(|span| span.column))),
+        ]))
+    }
+
+    /// Notes quoting the failing line of `source`, which starts at
+    /// `first_line`, with a caret under the error's column, then the error's
+    /// own notes, then listing each active boofer call with its position in
+    /// `file`, innermost first.
+    fn traceback(&self, source: &str, first_line: usize, file: &str) -> Vec<String> {
+        let mut notes = vec![];
+        if let Some((span, text)) = self.span.and_then(|span| {
+            let index = span.line.checked_sub(first_line)?;
+            Some((span, source.lines().nth(index)?))
+        }) {
+            let gutter = " ".repeat(span.line.to_string().len());
+            // Keep tabs so the caret lines up however they are displayed.
+            let indent: String = text
+                .chars()
+                .take(span.column - 1)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            notes.push(format!(" {} | {text}\n {gutter} | {indent}^", span.line));
+        }
+        notes.extend(self.notes.iter().cloned());
+        let mut trace = String::from("Stack trace (innermost first):");
+        let mut position = self.span;
+        let mut last = String::new();
+        let mut repeats = 0;
+        for frame in &self.trace {
+            let line = format!("\n  at {} ({})", frame.function, location(file, position));
+            position = frame.call_site;
+            // Runaway recursion would otherwise print the same frame once per
+            // call.
+            if line == last {
+                repeats += 1;
+                continue;
+            }
+            if repeats > 0 {
+                trace.push_str(&format!("\n  ... repeated {repeats} more times"));
+                repeats = 0;
+            }
+            trace.push_str(&line);
+            last = line;
+        }
+        if repeats > 0 {
+            trace.push_str(&format!("\n  ... repeated {repeats} more times"));
+        }
+        trace.push_str(&format!("\n  at <script> ({})", location(file, position)));
+        notes.push(trace);
+        notes
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "[line: {}] {}", span.line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+fn location(file: &str, span: Option<Span>) -> String {
+    match span {
+        Some(span) => format!("{file}:{}:{}", span.line, span.column),
+        None => format!("{file}:?"),
+    }
+}
+
+impl From<RuntimeError> for Error {
+    fn from(err: RuntimeError) -> Self {
+        match err.exit_code {
+            Some(code) => Error::Exit(code),
+            None => Error::Runtime(
+                err.kind,
+                Diagnostic {
+                    notes: err.notes,
+                    ..Diagnostic::new(err.kind.code(), err.message, err.span)
+                },
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic() -> Diagnostic {
+        Diagnostic::new(codes::INVALID_VALUE, "bad".to_string(), None)
+    }
+
+    #[test]
+    fn each_stage_has_its_exit_code() {
+        let cases = [
+            (Error::Lex(diagnostic()), "lex", 65),
+            (Error::Parse(diagnostic()), "parse", 65),
+            (Error::Resolve(diagnostic()), "resolve", 65),
+            (Error::Type(diagnostic()), "type", 65),
+            (
+                Error::Runtime(ErrorKind::Value, diagnostic()),
+                "runtime",
+                70,
+            ),
+            (Error::Io(diagnostic()), "io", 74),
+            (Error::Usage(diagnostic()), "usage", 64),
+            (Error::Exit(3), "exit", 3),
+        ];
+        for (error, kind, code) in cases {
+            assert_eq!((error.kind(), error.code()), (kind, code));
+        }
+    }
+
+    #[test]
+    fn runtime_errors_take_their_kind_s_code() {
+        let kinds = [
+            (ErrorKind::Type, "B0301"),
+            (ErrorKind::Name, "B0302"),
+            (ErrorKind::Arity, "B0303"),
+            (ErrorKind::Value, "B0304"),
+            (ErrorKind::Io, "B0305"),
+            (ErrorKind::Thrown, "B0306"),
+            (ErrorKind::Recursion, "B0307"),
+        ];
+        for (kind, id) in kinds {
+            let error = Error::from(RuntimeError::native("bad".to_string()).with_kind(kind));
+            assert_eq!(error.diagnostic().unwrap().code.id, id);
+            assert_eq!(error.code(), 70);
+        }
+    }
+
+    #[test]
+    fn exit_unwinds_to_its_own_code() {
+        let error = Error::from(RuntimeError::exit(4));
+        assert!(matches!(error, Error::Exit(4)));
+        assert_eq!(error.code(), 4);
+        assert_eq!(error.to_string(), "");
+        assert!(error.diagnostic().is_none());
+    }
+
+    #[test]
+    fn display_names_the_line_and_code() {
+        let span = Span { line: 3, column: 7 };
+        let error = Error::parsing(codes::EXPECTED_SEMICOLON, "Expect ';'.".to_string(), span);
+        assert_eq!(error.to_string(), "[line: 3] Error[B0101]: Expect ';'.");
+        let runtime = Error::Runtime(ErrorKind::Value, diagnostic());
+        assert_eq!(runtime.to_string(), "Runtime Error[B0304]: bad");
+    }
+
+    #[test]
+    fn json_has_every_field_in_order() {
+        let mut diagnostic = Diagnostic::new(
+            codes::TYPE_ERROR,
+            "Operands of '-' must be numbers".to_string(),
+            Some(Span { line: 2, column: 5 }),
+        );
+        diagnostic.notes = vec!["first".to_string(), "second".to_string()];
+        let error = Error::Runtime(ErrorKind::Type, diagnostic);
+        assert_eq!(
+            error.to_json(Some("main.boof")),
+            r#"{"severity":"error","code":"B0301","message":"Operands of '-' must be numbers","file":"main.boof","span":{"line":2,"column":5},"notes":["first","second"]}"#
+        );
+    }
+
+    #[test]
+    fn json_uses_null_for_missing_file_and_span() {
+        let error = Error::Io(Diagnostic::new(
+            codes::FILE_UNREADABLE,
+            "Failed".to_string(),
+            None,
+        ));
+        assert_eq!(
+            error.to_json(None),
+            r#"{"severity":"error","code":"B0401","message":"Failed","file":null,"span":null,"notes":[]}"#
+        );
+        assert_eq!(Error::Exit(0).to_json(Some("main.boof")), "");
+    }
+
+    #[test]
+    fn json_escapes_strings() {
+        let error = Error::Parse(Diagnostic::new(
+            codes::EXPECTED_SEMICOLON,
+            "quote \" and\nnewline\t\u{1}".to_string(),
+            None,
+        ));
+        assert_eq!(
+            error.to_json(Some("dir\\a.boof")),
+            r#"{"severity":"error","code":"B0101","message":"quote \" and\nnewline\t\u0001","file":"dir\\a.boof","span":null,"notes":[]}"#
+        );
+    }
+
+    #[test]
+    fn warnings_use_the_same_shape() {
+        let warning = Warning {
+            code: codes::MATCH_WITHOUT_DEFAULT,
+            message: "match over literal values has no '_' arm".to_string(),
+            span: Span { line: 1, column: 1 },
+        };
+        assert_eq!(
+            warning.to_json(Some("a.boof")),
+            r#"{"severity":"warning","code":"B0150","message":"match over literal values has no '_' arm","file":"a.boof","span":{"line":1,"column":1},"notes":[]}"#
+        );
+    }
 }
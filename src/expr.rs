@@ -1,14 +1,80 @@
-use crate::token::Token;
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::rc::Rc;
 
-#[derive(Debug)]
+use crate::{
+    function::{Function, NativeFunction},
+    regex::Regex,
+    stmt::FunctionDecl,
+    token::Token,
+};
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum LiteralValue {
     String(String),
-    Number(f32),
+    Number(f64),
+    List(Vec<LiteralValue>),
+    Map(BTreeMap<String, LiteralValue>),
+    Function(Rc<Function>),
+    NativeFunction(Rc<NativeFunction>),
+    Regex(Rc<Regex>),
     True,
     False,
     Nil,
 }
 
+impl LiteralValue {
+    /// The name scripts see from `type_of`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::String(_) => "string",
+            Self::Number(_) => "number",
+            Self::List(_) => "list",
+            Self::Map(_) => "map",
+            Self::Function(_) | Self::NativeFunction(_) => "boofer",
+            Self::Regex(_) => "regex",
+            Self::True | Self::False => "bool",
+            Self::Nil => "nil",
+        }
+    }
+
+    /// Representation used for values nested inside lists and maps, where
+    /// strings are quoted so `["a"]` and `[a]` print differently.
+    pub fn repr(&self) -> String {
+        match self {
+            Self::String(s) => format!("{s:?}"),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for LiteralValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::String(s) => write!(f, "{s}"),
+            Self::Number(n) => write!(f, "{n}"),
+            Self::List(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.repr()).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+            Self::Map(entries) => {
+                let entries: Vec<String> = entries
+                    .iter()
+                    .map(|(key, value)| format!("{key}: {}", value.repr()))
+                    .collect();
+                write!(f, "{{{}}}", entries.join(", "))
+            }
+            Self::Function(function) => write!(f, "{function}"),
+            Self::NativeFunction(function) => write!(f, "{function}"),
+            Self::Regex(regex) => write!(f, "{regex}"),
+            Self::True => write!(f, "true"),
+            Self::False => write!(f, "false"),
+            Self::Nil => write!(f, "nil"),
+        }
+    }
+}
+
 pub enum Expr {
     /// <expr> <op> <expr>
     Binary(Box<Expr>, Token, Box<Expr>),
@@ -21,26 +87,191 @@ pub enum Expr {
 
     /// <op> <expr>
     Unary(Token, Box<Expr>),
+
+    /// <identifier>
+    Variable(Token, Cell<Depth>),
+
+    /// <identifier> = <expr>
+    Assign(Token, Box<Expr>, Cell<Depth>),
+
+    /// <expr> and <expr>, <expr> or <expr>
+    Logical(Box<Expr>, Token, Box<Expr>),
+
+    /// <expr>(<expr>, ...)
+    Call(Box<Expr>, Token, Vec<Expr>),
+
+    /// <expr>.<identifier>
+    Get(Box<Expr>, Token),
+
+    /// boofer (<params>) { <stmt>* } or (<params>) => <expr>
+    Lambda(Rc<FunctionDecl>),
+
+    /// [ <expr>, ... ]
+    List(Vec<Expr>),
+
+    /// { <key>: <expr>, ... }
+    Map(Vec<(String, Expr)>),
+
+    /// match <expr> { <pattern> (if <expr>)? => <expr>, ... }
+    Match(Token, Box<Expr>, Vec<MatchArm>),
+}
+
+/// Which scope a variable reference reads or assigns, as worked out by the
+/// [`Resolver`](crate::Resolver).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Depth {
+    /// Not resolved, so the name is looked up through the enclosing scopes
+    /// when the reference runs.
+    #[default]
+    Unresolved,
+    /// A global, including one not yet defined when the reference was
+    /// resolved.
+    Global,
+    /// A local declared this many scopes out from the reference.
+    Local(usize),
 }
 
-impl Expr {
-    pub fn print(&self) {
-        println!("{}", self.to_string());
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expr>,
+    pub body: Expr,
+}
+
+pub enum Pattern {
+    /// _
+    Wildcard,
+
+    /// <identifier>, binds the matched value
+    Binding(String),
+
+    /// literal value compared with `==`
+    Literal(LiteralValue),
+
+    /// <number>..<number> or <number>..=<number>
+    Range(f64, f64, bool),
+
+    /// [ <pattern>, ..., ..<identifier>? ]
+    List(Vec<Pattern>, Option<Rest>),
+
+    /// { <key>: <pattern>, ... }
+    Map(Vec<(String, Pattern)>),
+
+    /// <pattern> | <pattern> | ...
+    Or(Vec<Pattern>),
+}
+
+/// The `..` or `..name` tail of a list pattern.
+pub enum Rest {
+    Ignored,
+    Binding(String),
+}
+
+impl Pattern {
+    /// Appends the names the pattern binds when it matches.
+    pub fn bindings(&self, names: &mut Vec<String>) {
+        match self {
+            Self::Binding(name) => names.push(name.clone()),
+            Self::Wildcard | Self::Literal(_) | Self::Range(..) => (),
+            Self::List(items, rest) => {
+                items.iter().for_each(|item| item.bindings(names));
+                if let Some(Rest::Binding(name)) = rest {
+                    names.push(name.clone());
+                }
+            }
+            Self::Map(entries) => entries.iter().for_each(|(_, item)| item.bindings(names)),
+            Self::Or(alternatives) => alternatives.iter().for_each(|item| item.bindings(names)),
+        }
     }
 
-    pub fn to_string(&self) -> String {
+    /// Whether the pattern matches every value, making later arms unreachable.
+    pub fn is_irrefutable(&self) -> bool {
         match self {
-            Self::Binary(left, op, right) => format!(
-                "({} {} {})",
-                op.to_string(),
-                left.to_string(),
-                right.to_string()
-            ),
-            Self::Grouping(expr) => format!("(group {})", expr.to_string()),
-            Self::Literal(l) => {
-                format!("{l:?}")
-            }
-            Self::Unary(op, expr) => format!("({} {})", op.to_string(), expr.to_string()),
+            Self::Wildcard | Self::Binding(_) => true,
+            Self::Or(alternatives) => alternatives.iter().any(Pattern::is_irrefutable),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Wildcard => write!(f, "_"),
+            Self::Binding(name) => write!(f, "{name}"),
+            Self::Literal(l) => write!(f, "{l:?}"),
+            Self::Range(start, end, inclusive) => {
+                let op = if *inclusive { "..=" } else { ".." };
+                write!(f, "{start}{op}{end}")
+            }
+            Self::List(items, rest) => {
+                let mut items: Vec<String> = items.iter().map(|p| p.to_string()).collect();
+                match rest {
+                    Some(Rest::Ignored) => items.push("..".to_string()),
+                    Some(Rest::Binding(name)) => items.push(format!("..{name}")),
+                    None => (),
+                }
+                write!(f, "[{}]", items.join(", "))
+            }
+            Self::Map(entries) => {
+                let entries: Vec<String> = entries
+                    .iter()
+                    .map(|(key, pattern)| format!("{key}: {pattern}"))
+                    .collect();
+                write!(f, "{{{}}}", entries.join(", "))
+            }
+            Self::Or(alternatives) => {
+                let alternatives: Vec<String> =
+                    alternatives.iter().map(|p| p.to_string()).collect();
+                write!(f, "{}", alternatives.join(" | "))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Binary(left, op, right) => write!(f, "({op} {left} {right})"),
+            Self::Grouping(expr) => write!(f, "(group {expr})"),
+            Self::Literal(l) => write!(f, "{l:?}"),
+            Self::Unary(op, expr) => write!(f, "({op} {expr})"),
+            Self::Variable(name, _) => write!(f, "{name}"),
+            Self::Assign(name, value, _) => write!(f, "(= {name} {value})"),
+            Self::Logical(left, op, right) => write!(f, "({op} {left} {right})"),
+            Self::Call(callee, _, args) => {
+                let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+                write!(f, "(call {callee} {})", args.join(" "))
+            }
+            Self::Get(object, name) => write!(f, "(. {object} {name})"),
+            Self::Lambda(declaration) => {
+                let params: Vec<String> = declaration
+                    .params
+                    .iter()
+                    .map(|p| p.lexeme.clone())
+                    .collect();
+                write!(f, "(boofer ({}) ...)", params.join(" "))
+            }
+            Self::List(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                write!(f, "(list {})", items.join(" "))
+            }
+            Self::Map(entries) => {
+                let entries: Vec<String> = entries
+                    .iter()
+                    .map(|(key, value)| format!("({key} {value})"))
+                    .collect();
+                write!(f, "(map {})", entries.join(" "))
+            }
+            Self::Match(_, subject, arms) => {
+                write!(f, "(match {subject}")?;
+                for arm in arms {
+                    match &arm.guard {
+                        Some(guard) => write!(f, " ({} if {guard} {})", arm.pattern, arm.body)?,
+                        None => write!(f, " ({} {})", arm.pattern, arm.body)?,
+                    }
+                }
+                write!(f, ")")
+            }
         }
     }
 }
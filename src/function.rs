@@ -0,0 +1,99 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::{
+    environment::Environment, error::RuntimeError, expr::LiteralValue, interpreter::Interpreter,
+    stmt::FunctionDecl,
+};
+
+/// A `boofer` value: a declaration together with the environment it closes over.
+pub struct Function {
+    pub name: Option<String>,
+    pub declaration: Rc<FunctionDecl>,
+    pub closure: Rc<RefCell<Environment>>,
+}
+
+impl Function {
+    pub fn arity(&self) -> usize {
+        self.declaration.params.len()
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "<boofer {name}>"),
+            None => write!(f, "<boofer>"),
+        }
+    }
+}
+
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+/// Functions are only equal to themselves.
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+/// The number of arguments a native boofer accepts.
+#[derive(Clone, Copy)]
+pub enum Arity {
+    Fixed(usize),
+    /// Inclusive lower and upper bounds, for trailing optional arguments.
+    Range(usize, usize),
+    Variadic,
+}
+
+impl Arity {
+    pub fn accepts(&self, count: usize) -> bool {
+        match *self {
+            Self::Fixed(arity) => arity == count,
+            Self::Range(min, max) => min <= count && count <= max,
+            Self::Variadic => true,
+        }
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Fixed(arity) => write!(f, "{arity}"),
+            Self::Range(min, max) => write!(f, "{min} to {max}"),
+            Self::Variadic => write!(f, "any number of"),
+        }
+    }
+}
+
+pub type NativeFn = dyn Fn(&mut Interpreter, &[LiteralValue]) -> Result<LiteralValue, RuntimeError>;
+
+/// A boofer implemented in Rust and exposed to scripts by the host.
+pub struct NativeFunction {
+    pub name: String,
+    pub arity: Arity,
+    pub function: Box<NativeFn>,
+}
+
+impl fmt::Display for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<native boofer {}>", self.name)
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl PartialEq for NativeFunction {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
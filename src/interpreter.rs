@@ -1,165 +1,698 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
 use crate::{
-    expr::{Expr, LiteralValue},
+    convert::IntoNative,
+    environment::Environment,
+    error::{ErrorKind, Frame, RuntimeError},
+    expr::{Depth, Expr, LiteralValue, MatchArm, Pattern, Rest},
+    function::{Arity, Function, NativeFunction},
+    stdlib::{self, Rng},
+    stmt::Stmt,
+    suggest,
     token::{Token, TokenType},
 };
-pub struct Interpreter {}
+
+/// Why execution of a statement stopped before reaching its end.
+enum Unwind {
+    Return(LiteralValue),
+    Error(RuntimeError),
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(err: RuntimeError) -> Self {
+        Unwind::Error(err)
+    }
+}
+
+pub struct Interpreter {
+    globals: Rc<RefCell<Environment>>,
+    environment: Rc<RefCell<Environment>>,
+    /// Boofers callable with method syntax, keyed by the receiver's type name.
+    methods: HashMap<String, BTreeMap<String, LiteralValue>>,
+    rng: Rng,
+    args: Vec<String>,
+    /// How many boofer calls are running, limited to `MAX_CALL_DEPTH`.
+    depth: usize,
+    /// Where the native stack was when the outermost boofer call started.
+    stack_base: usize,
+    /// How many bytes of native stack nested boofer calls may use.
+    stack_limit: usize,
+}
+
+/// The deepest boofer calls may nest before the interpreter gives up with
+/// a catchable error, rather than overflowing the native stack.
+pub const MAX_CALL_DEPTH: usize = 1000;
+
+/// The native stack boofer calls may use unless the host sets another
+/// limit: half of the 2 MiB Rust gives spawned threads, leaving the rest
+/// for the host's own frames.
+pub const DEFAULT_STACK_LIMIT: usize = 1024 * 1024;
 
 impl Interpreter {
-    pub fn evaluate(expr: Expr) -> LiteralValue {
-        match expr {
-            Expr::Binary(left, op, right) => Self::evaluate_binary(*left, op, *right),
-            Expr::Unary(op, expr) => Self::evaluate_unary(op, *expr),
-            Expr::Grouping(expr) => Self::evaluate(*expr),
-            Expr::Literal(l) => l,
-            _ => LiteralValue::Nil,
-        }
+    pub fn new() -> Self {
+        let globals = Rc::new(RefCell::new(Environment::new()));
+        let mut interpreter = Interpreter {
+            environment: Rc::clone(&globals),
+            globals,
+            methods: HashMap::new(),
+            rng: Rng::new(),
+            args: vec![],
+            depth: 0,
+            stack_base: 0,
+            stack_limit: DEFAULT_STACK_LIMIT,
+        };
+        stdlib::register(&mut interpreter);
+        interpreter
     }
 
-    fn evaluate_binary(left: Expr, op: Token, right: Expr) -> LiteralValue {
-        let left = Self::evaluate(left);
-        let right = Self::evaluate(right);
+    pub fn define_global(&mut self, name: &str, value: LiteralValue) {
+        self.globals.borrow_mut().define(name.to_string(), value);
+    }
 
-        match op.token_type {
-            TokenType::Minus => {
-                if let LiteralValue::Number(left) = left {
-                    if let LiteralValue::Number(right) = right {
-                        return LiteralValue::Number(left - right);
-                    }
-                }
-                LiteralValue::Nil
+    pub fn get_global(&self, name: &str) -> Option<LiteralValue> {
+        self.globals.borrow().get(name)
+    }
+
+    /// Seeds the generator behind the `random` module, making its output
+    /// reproducible.
+    pub fn seed_random(&mut self, seed: u64) {
+        self.rng.seed(seed);
+    }
+
+    /// Sets how many bytes of the calling thread's stack nested boofer calls
+    /// may use before failing with a stack overflow error. Hosts that run
+    /// scripts on a bigger stack than Rust's default can raise it, and
+    /// must keep it well below the stack's size.
+    pub fn set_stack_limit(&mut self, bytes: usize) {
+        self.stack_limit = bytes;
+    }
+
+    /// Sets the arguments returned by `args()`.
+    pub fn set_script_args(&mut self, args: Vec<String>) {
+        self.args = args;
+    }
+
+    pub fn script_args(&self) -> &[String] {
+        &self.args
+    }
+
+    pub(crate) fn rng(&mut self) -> &mut Rng {
+        &mut self.rng
+    }
+
+    /// Makes each boofer in `methods` callable as `value.name(args)` on values
+    /// of `type_name`, with the value passed as the first argument.
+    pub fn define_methods(&mut self, type_name: &str, methods: BTreeMap<String, LiteralValue>) {
+        self.methods
+            .entry(type_name.to_string())
+            .or_default()
+            .extend(methods);
+    }
+
+    /// Exposes a Rust function to scripts as a global boofer.
+    pub fn register_native<F>(&mut self, name: &str, arity: Arity, function: F)
+    where
+        F: Fn(&mut Interpreter, &[LiteralValue]) -> Result<LiteralValue, RuntimeError> + 'static,
+    {
+        let native = NativeFunction {
+            name: name.to_string(),
+            arity,
+            function: Box::new(function),
+        };
+        self.define_global(name, LiteralValue::NativeFunction(Rc::new(native)));
+    }
+
+    /// Exposes a Rust closure with typed arguments, converting arguments with
+    /// [`FromBoof`] and the result with [`ToBoof`].
+    ///
+    /// [`FromBoof`]: crate::FromBoof
+    /// [`ToBoof`]: crate::ToBoof
+    pub fn register<F, Args>(&mut self, name: &str, function: F)
+    where
+        F: IntoNative<Args>,
+    {
+        let native = NativeFunction {
+            name: name.to_string(),
+            arity: F::arity(),
+            function: function.into_native(name),
+        };
+        self.define_global(name, LiteralValue::NativeFunction(Rc::new(native)));
+    }
+
+    pub fn interpret(&mut self, statements: &[Stmt]) -> Result<(), RuntimeError> {
+        for statement in statements {
+            match self.execute(statement) {
+                Ok(()) => (),
+                Err(Unwind::Error(err)) => return Err(err),
+                // The resolver rejects `return` outside of a boofer.
+                Err(Unwind::Return(_)) => return Ok(()),
             }
-            TokenType::Slash => {
-                if let LiteralValue::Number(left) = left {
-                    if let LiteralValue::Number(right) = right {
-                        return LiteralValue::Number(left / right);
-                    }
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self, stmt: &Stmt) -> Result<(), Unwind> {
+        match stmt {
+            Stmt::Expression(expr) => {
+                self.evaluate(expr)?;
+            }
+            Stmt::Print(exprs) => {
+                let mut values = Vec::with_capacity(exprs.len());
+                for expr in exprs {
+                    values.push(self.evaluate(expr)?.to_string());
                 }
-                LiteralValue::Nil
+                println!("{}", values.join(" "));
             }
-            TokenType::Star => {
-                if let LiteralValue::Number(left) = left {
-                    if let LiteralValue::Number(right) = right {
-                        return LiteralValue::Number(left * right);
-                    }
+            Stmt::Var(name, _, initializer) => {
+                let value = match initializer {
+                    Some(expr) => self.evaluate(expr)?,
+                    None => LiteralValue::Nil,
+                };
+                self.environment
+                    .borrow_mut()
+                    .define(name.lexeme.clone(), value);
+            }
+            Stmt::Block(statements) => {
+                let scope = Environment::from(Rc::clone(&self.environment));
+                self.execute_block(statements, scope)?;
+            }
+            Stmt::If(_, condition, then_branch, else_branch) => {
+                if Self::is_truthy(&self.evaluate(condition)?) {
+                    self.execute(then_branch)?;
+                } else if let Some(else_branch) = else_branch {
+                    self.execute(else_branch)?;
                 }
-                LiteralValue::Nil
             }
-            TokenType::Plus => {
-                if let LiteralValue::String(left) = left {
-                    if let LiteralValue::String(right) = right {
-                        return LiteralValue::String(left + &right.clone());
-                    }
-                } else if let LiteralValue::Number(left) = left {
-                    if let LiteralValue::Number(right) = right {
-                        return LiteralValue::Number(left + right);
-                    }
+            Stmt::While(_, condition, body) => {
+                while Self::is_truthy(&self.evaluate(condition)?) {
+                    self.execute(body)?;
                 }
-                LiteralValue::Nil
             }
-            TokenType::Greater => {
-                if let LiteralValue::Number(left) = left {
-                    if let LiteralValue::Number(right) = right {
-                        if left > right {
-                            return LiteralValue::True;
-                        } else {
-                            return LiteralValue::False;
+            Stmt::Function(name, declaration) => {
+                let function = Function {
+                    name: Some(name.lexeme.clone()),
+                    declaration: Rc::clone(declaration),
+                    closure: Rc::clone(&self.environment),
+                };
+                self.environment.borrow_mut().define(
+                    name.lexeme.clone(),
+                    LiteralValue::Function(Rc::new(function)),
+                );
+            }
+            Stmt::Throw(keyword, value) => {
+                let value = self.evaluate(value)?;
+                return Err(Unwind::Error(RuntimeError::thrown(value, keyword)));
+            }
+            Stmt::Try(body, handler, finally) => {
+                let scope = Environment::from(Rc::clone(&self.environment));
+                let mut result = self.execute_block(body, scope);
+                if let Some(handler) = handler {
+                    result = match result {
+                        Err(Unwind::Error(err)) if err.is_catchable() => {
+                            let mut scope = Environment::from(Rc::clone(&self.environment));
+                            if let Some(name) = &handler.name {
+                                scope.define(name.lexeme.clone(), err.to_value());
+                            }
+                            self.execute_block(&handler.body, scope)
                         }
-                    }
+                        result => result,
+                    };
+                }
+                if let Some(finally) = finally {
+                    let scope = Environment::from(Rc::clone(&self.environment));
+                    self.execute_block(finally, scope)?;
                 }
-                LiteralValue::Nil
+                result?;
             }
-            TokenType::GreaterEqual => {
-                if let LiteralValue::Number(left) = left {
-                    if let LiteralValue::Number(right) = right {
-                        if left >= right {
-                            return LiteralValue::True;
-                        } else {
-                            return LiteralValue::False;
-                        }
+            Stmt::Return(_, value) => {
+                let value = match value {
+                    Some(expr) => self.evaluate(expr)?,
+                    None => LiteralValue::Nil,
+                };
+                return Err(Unwind::Return(value));
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_block(&mut self, statements: &[Stmt], scope: Environment) -> Result<(), Unwind> {
+        let previous = std::mem::replace(&mut self.environment, Rc::new(RefCell::new(scope)));
+        let result = statements.iter().try_for_each(|stmt| self.execute(stmt));
+        self.environment = previous;
+        result
+    }
+
+    pub fn evaluate(&mut self, expr: &Expr) -> Result<LiteralValue, RuntimeError> {
+        match expr {
+            Expr::Binary(left, op, right) => self.evaluate_binary(left, op, right),
+            Expr::Unary(op, expr) => self.evaluate_unary(op, expr),
+            Expr::Grouping(expr) => self.evaluate(expr),
+            Expr::Literal(l) => Ok(l.clone()),
+            Expr::Variable(name, depth) => {
+                let value = match depth.get() {
+                    Depth::Unresolved => self.environment.borrow().get(&name.lexeme),
+                    Depth::Global => self.globals.borrow().get(&name.lexeme),
+                    Depth::Local(depth) => self.environment.borrow().get_at(depth, &name.lexeme),
+                };
+                value.ok_or_else(|| self.undefined_variable(name))
+            }
+            Expr::Assign(name, value, depth) => {
+                let value = self.evaluate(value)?;
+                let assigned = match depth.get() {
+                    Depth::Unresolved => self
+                        .environment
+                        .borrow_mut()
+                        .assign(&name.lexeme, value.clone()),
+                    Depth::Global => self
+                        .globals
+                        .borrow_mut()
+                        .assign(&name.lexeme, value.clone()),
+                    Depth::Local(depth) => {
+                        self.environment
+                            .borrow_mut()
+                            .assign_at(depth, &name.lexeme, value.clone())
                     }
+                };
+                if !assigned {
+                    return Err(self.undefined_variable(name));
                 }
-                LiteralValue::Nil
+                Ok(value)
             }
-            TokenType::Less => {
-                if let LiteralValue::Number(left) = left {
-                    if let LiteralValue::Number(right) = right {
-                        if left < right {
-                            return LiteralValue::True;
-                        } else {
-                            return LiteralValue::False;
-                        }
-                    }
+            Expr::Logical(left, op, right) => {
+                let left = self.evaluate(left)?;
+                let short_circuits = match op.token_type {
+                    TokenType::Or => Self::is_truthy(&left),
+                    _ => !Self::is_truthy(&left),
+                };
+                if short_circuits {
+                    return Ok(left);
                 }
-                LiteralValue::Nil
+                self.evaluate(right)
             }
-            TokenType::LessEqual => {
-                if let LiteralValue::Number(left) = left {
-                    if let LiteralValue::Number(right) = right {
-                        if left <= right {
-                            return LiteralValue::True;
-                        } else {
-                            return LiteralValue::False;
-                        }
-                    }
+            Expr::Call(callee, paren, args) => {
+                let (callee, receiver) = self.evaluate_callee(callee)?;
+                let mut values: Vec<LiteralValue> = receiver.into_iter().collect();
+                for arg in args {
+                    values.push(self.evaluate(arg)?);
                 }
-                LiteralValue::Nil
+                self.call(&callee, paren, values)
             }
-            TokenType::EqualEqual => Self::equals(left, right),
-            TokenType::BangEqual => Self::negate(Self::equals(left, right)),
-            _ => LiteralValue::Nil,
+            Expr::Get(object, name) => match self.evaluate(object)? {
+                LiteralValue::Map(entries) => entries.get(&name.lexeme).cloned().ok_or_else(|| {
+                    RuntimeError::new(format!("Undefined key '{}'", name.lexeme), name)
+                        .with_kind(ErrorKind::Name)
+                        .with_notes(suggest::did_you_mean(
+                            &name.lexeme,
+                            entries.keys().map(String::as_str),
+                        ))
+                }),
+                value => Err(RuntimeError::new(
+                    format!("Only maps have properties, not {}", value.type_name()),
+                    name,
+                )
+                .with_kind(ErrorKind::Type)),
+            },
+            Expr::Lambda(declaration) => Ok(LiteralValue::Function(Rc::new(Function {
+                name: None,
+                declaration: Rc::clone(declaration),
+                closure: Rc::clone(&self.environment),
+            }))),
+            Expr::List(items) => {
+                let mut values = vec![];
+                for item in items {
+                    values.push(self.evaluate(item)?);
+                }
+                Ok(LiteralValue::List(values))
+            }
+            Expr::Map(entries) => {
+                let mut values = BTreeMap::new();
+                for (key, value) in entries {
+                    values.insert(key.clone(), self.evaluate(value)?);
+                }
+                Ok(LiteralValue::Map(values))
+            }
+            Expr::Match(keyword, subject, arms) => self.evaluate_match(keyword, subject, arms),
+        }
+    }
+
+    /// Evaluates the callee of a call, resolving `value.name` to a method when
+    /// `value` has no such key. Methods are returned with their receiver.
+    fn evaluate_callee(
+        &mut self,
+        callee: &Expr,
+    ) -> Result<(LiteralValue, Option<LiteralValue>), RuntimeError> {
+        let Expr::Get(object, name) = callee else {
+            return Ok((self.evaluate(callee)?, None));
+        };
+        let object = self.evaluate(object)?;
+        if let LiteralValue::Map(entries) = &object {
+            if let Some(value) = entries.get(&name.lexeme) {
+                return Ok((value.clone(), None));
+            }
+        }
+        let methods = self.methods.get(object.type_name());
+        if let Some(method) = methods.and_then(|methods| methods.get(&name.lexeme)) {
+            return Ok((method.clone(), Some(object)));
         }
+        let mut candidates: Vec<&str> = methods
+            .into_iter()
+            .flat_map(|methods| methods.keys().map(String::as_str))
+            .collect();
+        if let LiteralValue::Map(entries) = &object {
+            candidates.extend(entries.keys().map(String::as_str));
+        }
+        Err(RuntimeError::new(
+            format!("{} has no method '{}'", object.type_name(), name.lexeme),
+            name,
+        )
+        .with_kind(ErrorKind::Name)
+        .with_notes(suggest::did_you_mean(&name.lexeme, candidates)))
+    }
+
+    /// An error for reading or assigning a variable that no scope defines,
+    /// suggesting a visible variable or keyword with a similar name.
+    fn undefined_variable(&self, name: &Token) -> RuntimeError {
+        let names = self.environment.borrow().names();
+        let note = suggest::did_you_mean(&name.lexeme, names.iter().map(String::as_str))
+            .or_else(|| suggest::keyword(&name.lexeme));
+        RuntimeError::new(format!("Undefined variable '{}'", name.lexeme), name)
+            .with_kind(ErrorKind::Name)
+            .with_notes(note)
+    }
+
+    fn call(
+        &mut self,
+        callee: &LiteralValue,
+        paren: &Token,
+        args: Vec<LiteralValue>,
+    ) -> Result<LiteralValue, RuntimeError> {
+        self.call_value(callee, args).map_err(|mut err| {
+            err.span.get_or_insert(paren.span());
+            // Only the boofer called here can still lack a call site.
+            if let Some(frame) = err.trace.last_mut() {
+                frame.call_site.get_or_insert(paren.span());
+            }
+            err
+        })
     }
 
-    fn evaluate_unary(op: Token, expr: Expr) -> LiteralValue {
-        let val = Self::evaluate(expr);
-        match op.token_type {
-            TokenType::Bang => Self::is_falsy(val),
-            TokenType::Minus => {
-                if let LiteralValue::Number(val) = val {
-                    return LiteralValue::Number(-val);
+    /// Calls a boofer value with already evaluated arguments, which lets
+    /// native boofers call back into script code.
+    pub fn call_value(
+        &mut self,
+        callee: &LiteralValue,
+        args: Vec<LiteralValue>,
+    ) -> Result<LiteralValue, RuntimeError> {
+        match callee {
+            LiteralValue::Function(function) => {
+                if args.len() != function.arity() {
+                    return Err(Self::arity_error(
+                        Arity::Fixed(function.arity()),
+                        args.len(),
+                    ));
                 }
-                LiteralValue::Nil
+                self.call_function(function, args).map_err(|mut err| {
+                    err.trace.push(Frame {
+                        function: function.name.as_deref().unwrap_or("<lambda>").to_string(),
+                        call_site: None,
+                    });
+                    err
+                })
             }
+            LiteralValue::NativeFunction(native) => {
+                if !native.arity.accepts(args.len()) {
+                    return Err(Self::arity_error(native.arity, args.len()));
+                }
+                (native.function)(self, &args)
+            }
+            _ => Err(
+                RuntimeError::native(format!("Can only call boofers, not {}", callee.repr()))
+                    .with_kind(ErrorKind::Type),
+            ),
+        }
+    }
+
+    fn call_function(
+        &mut self,
+        function: &Function,
+        args: Vec<LiteralValue>,
+    ) -> Result<LiteralValue, RuntimeError> {
+        if self.depth == MAX_CALL_DEPTH {
+            return Err(RuntimeError::native(format!(
+                "Stack overflow (max call depth {MAX_CALL_DEPTH})"
+            ))
+            .with_kind(ErrorKind::Recursion));
+        }
+        // How deep calls can go on the host's stack depends on the build and
+        // on what each call does, so the stack they use is measured too.
+        let position = stack_position();
+        if self.depth == 0 {
+            self.stack_base = position;
+        }
+        if self.stack_base.abs_diff(position) > self.stack_limit {
+            return Err(RuntimeError::native(format!(
+                "Stack overflow (out of stack space at call depth {})",
+                self.depth
+            ))
+            .with_kind(ErrorKind::Recursion));
+        }
+        let mut scope = Environment::from(Rc::clone(&function.closure));
+        for (param, arg) in function.declaration.params.iter().zip(args) {
+            scope.define(param.lexeme.clone(), arg);
+        }
+        self.depth += 1;
+        let result = self.execute_block(&function.declaration.body, scope);
+        self.depth -= 1;
+        match result {
+            Ok(()) => Ok(LiteralValue::Nil),
+            Err(Unwind::Return(value)) => Ok(value),
+            Err(Unwind::Error(err)) => Err(err),
+        }
+    }
+
+    fn arity_error(expected: Arity, got: usize) -> RuntimeError {
+        let plural = if matches!(expected, Arity::Fixed(1)) {
+            ""
+        } else {
+            "s"
+        };
+        RuntimeError::native(format!(
+            "Expected {expected} argument{plural} but got {got}"
+        ))
+        .with_kind(ErrorKind::Arity)
+    }
+
+    fn evaluate_binary(
+        &mut self,
+        left: &Expr,
+        op: &Token,
+        right: &Expr,
+    ) -> Result<LiteralValue, RuntimeError> {
+        let left = self.evaluate(left)?;
+        let right = self.evaluate(right)?;
+        Self::binary(op, left, right)
+    }
+
+    /// Applies a binary operator to evaluated operands. The optimizer uses
+    /// this to fold operators on literals.
+    pub(crate) fn binary(
+        op: &Token,
+        left: LiteralValue,
+        right: LiteralValue,
+    ) -> Result<LiteralValue, RuntimeError> {
+        let value = match (&op.token_type, left, right) {
+            (TokenType::EqualEqual, left, right) => Self::from_bool(left == right),
+            (TokenType::BangEqual, left, right) => Self::from_bool(left != right),
+            (TokenType::Plus, LiteralValue::String(left), LiteralValue::String(right)) => {
+                LiteralValue::String(left + &right)
+            }
+            (token_type, LiteralValue::Number(left), LiteralValue::Number(right)) => {
+                match token_type {
+                    TokenType::Plus => LiteralValue::Number(left + right),
+                    TokenType::Minus => LiteralValue::Number(left - right),
+                    TokenType::Star => LiteralValue::Number(left * right),
+                    TokenType::Slash => LiteralValue::Number(left / right),
+                    TokenType::Greater => Self::from_bool(left > right),
+                    TokenType::GreaterEqual => Self::from_bool(left >= right),
+                    TokenType::Less => Self::from_bool(left < right),
+                    TokenType::LessEqual => Self::from_bool(left <= right),
+                    _ => LiteralValue::Nil,
+                }
+            }
+            (TokenType::Plus, left, right) => {
+                return Err(Self::operand_error(
+                    op,
+                    "two numbers or two strings",
+                    &[left, right],
+                ))
+            }
+            (_, left, right) => return Err(Self::operand_error(op, "numbers", &[left, right])),
+        };
+        Ok(value)
+    }
+
+    fn evaluate_unary(&mut self, op: &Token, expr: &Expr) -> Result<LiteralValue, RuntimeError> {
+        let val = self.evaluate(expr)?;
+        Self::unary(op, val)
+    }
+
+    /// Applies a unary operator to an evaluated operand.
+    pub(crate) fn unary(op: &Token, val: LiteralValue) -> Result<LiteralValue, RuntimeError> {
+        let value = match op.token_type {
+            TokenType::Bang => Self::from_bool(!Self::is_truthy(&val)),
+            TokenType::Minus => match val {
+                LiteralValue::Number(val) => LiteralValue::Number(-val),
+                val => return Err(Self::operand_error(op, "a number", &[val])),
+            },
             _ => LiteralValue::Nil,
+        };
+        Ok(value)
+    }
+
+    fn operand_error(op: &Token, expected: &str, operands: &[LiteralValue]) -> RuntimeError {
+        let found: Vec<&str> = operands.iter().map(LiteralValue::type_name).collect();
+        let noun = if operands.len() == 1 {
+            "Operand"
+        } else {
+            "Operands"
+        };
+        RuntimeError::new(
+            format!(
+                "{noun} of '{}' must be {expected}, not {}",
+                op.lexeme,
+                found.join(" and ")
+            ),
+            op,
+        )
+        .with_kind(ErrorKind::Type)
+    }
+
+    fn evaluate_match(
+        &mut self,
+        keyword: &Token,
+        subject: &Expr,
+        arms: &[MatchArm],
+    ) -> Result<LiteralValue, RuntimeError> {
+        let value = self.evaluate(subject)?;
+        for arm in arms {
+            let mut bindings = vec![];
+            if !Self::match_pattern(&arm.pattern, &value, &mut bindings) {
+                continue;
+            }
+            let mut scope = Environment::from(Rc::clone(&self.environment));
+            for (name, value) in bindings {
+                scope.define(name, value);
+            }
+            let previous = std::mem::replace(&mut self.environment, Rc::new(RefCell::new(scope)));
+            let result = self.evaluate_arm(arm);
+            self.environment = previous;
+            if let Some(value) = result? {
+                return Ok(value);
+            }
+        }
+        Err(RuntimeError::new(
+            format!("No match arm matched value {}", value.repr()),
+            keyword,
+        ))
+    }
+
+    /// Evaluates the arm body in the current scope, or `None` if its guard
+    /// rejects the value.
+    fn evaluate_arm(&mut self, arm: &MatchArm) -> Result<Option<LiteralValue>, RuntimeError> {
+        if let Some(guard) = &arm.guard {
+            if !Self::is_truthy(&self.evaluate(guard)?) {
+                return Ok(None);
+            }
         }
+        Ok(Some(self.evaluate(&arm.body)?))
     }
 
-    fn equals(left: LiteralValue, right: LiteralValue) -> LiteralValue {
-        match (left, right) {
-            (LiteralValue::Nil, LiteralValue::Nil) => LiteralValue::True,
-            (LiteralValue::True, LiteralValue::True) => LiteralValue::True,
-            (LiteralValue::False, LiteralValue::False) => LiteralValue::True,
-            (LiteralValue::Number(left), LiteralValue::Number(right)) => {
-                if left == right {
-                    return LiteralValue::True;
+    fn match_pattern(
+        pattern: &Pattern,
+        value: &LiteralValue,
+        bindings: &mut Vec<(String, LiteralValue)>,
+    ) -> bool {
+        match pattern {
+            Pattern::Wildcard => true,
+            Pattern::Binding(name) => {
+                bindings.push((name.clone(), value.clone()));
+                true
+            }
+            Pattern::Literal(l) => l == value,
+            Pattern::Range(start, end, inclusive) => match value {
+                LiteralValue::Number(n) if *inclusive => start <= n && n <= end,
+                LiteralValue::Number(n) => start <= n && n < end,
+                _ => false,
+            },
+            Pattern::List(patterns, rest) => {
+                let LiteralValue::List(items) = value else {
+                    return false;
+                };
+                let length_matches = match rest {
+                    Some(_) => items.len() >= patterns.len(),
+                    None => items.len() == patterns.len(),
+                };
+                if !length_matches {
+                    return false;
+                }
+                for (pattern, item) in patterns.iter().zip(items) {
+                    if !Self::match_pattern(pattern, item, bindings) {
+                        return false;
+                    }
                 }
-                LiteralValue::False
+                if let Some(Rest::Binding(name)) = rest {
+                    let tail = items[patterns.len()..].to_vec();
+                    bindings.push((name.clone(), LiteralValue::List(tail)));
+                }
+                true
+            }
+            Pattern::Map(entries) => {
+                let LiteralValue::Map(values) = value else {
+                    return false;
+                };
+                entries.iter().all(|(key, pattern)| match values.get(key) {
+                    Some(value) => Self::match_pattern(pattern, value, bindings),
+                    None => false,
+                })
             }
-            (LiteralValue::String(left), LiteralValue::String(right)) => {
-                if left == right {
-                    return LiteralValue::True;
+            Pattern::Or(alternatives) => {
+                for alternative in alternatives {
+                    let mut alternative_bindings = vec![];
+                    if Self::match_pattern(alternative, value, &mut alternative_bindings) {
+                        bindings.append(&mut alternative_bindings);
+                        return true;
+                    }
                 }
-                LiteralValue::False
+                false
             }
-            _ => LiteralValue::False,
         }
     }
 
-    fn negate(value: LiteralValue) -> LiteralValue {
-        match value {
-            LiteralValue::False | LiteralValue::Nil => LiteralValue::True,
-            _ => LiteralValue::False,
+    fn from_bool(value: bool) -> LiteralValue {
+        if value {
+            LiteralValue::True
+        } else {
+            LiteralValue::False
         }
     }
 
-    fn is_truthy(value: LiteralValue) -> LiteralValue {
-        match value {
-            LiteralValue::False | LiteralValue::Nil => LiteralValue::False,
-            _ => LiteralValue::True,
-        }
+    pub fn is_truthy(value: &LiteralValue) -> bool {
+        !matches!(value, LiteralValue::False | LiteralValue::Nil)
     }
+}
+
+/// An address in the current native stack frame, for measuring how much
+/// stack nested calls have used.
+#[inline(never)]
+fn stack_position() -> usize {
+    let marker = 0u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
 
-    fn is_falsy(value: LiteralValue) -> LiteralValue {
-        Self::negate(Self::is_truthy(value))
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
     }
 }
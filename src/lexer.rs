@@ -1,93 +1,149 @@
 use std::collections::HashMap;
+use std::rc::Rc;
 
+use crate::codes::{self, ErrorCode};
+use crate::error::{Error, Span};
+use crate::regex::Regex;
 use crate::token::{Token, TokenType};
-use crate::Error;
+
+/// Reserved words and the tokens they lex as.
+pub(crate) const KEYWORDS: &[(&str, TokenType)] = &[
+    ("boof", TokenType::Var),
+    ("boofer", TokenType::Func),
+    ("and", TokenType::And),
+    ("for", TokenType::For),
+    ("while", TokenType::While),
+    ("nil", TokenType::Nil),
+    ("or", TokenType::Or),
+    ("if", TokenType::If),
+    ("else", TokenType::Else),
+    ("elseif", TokenType::ElseIf),
+    ("return", TokenType::Return),
+    ("throw", TokenType::Throw),
+    ("try", TokenType::Try),
+    ("catch", TokenType::Catch),
+    ("finally", TokenType::Finally),
+    ("true", TokenType::True),
+    ("false", TokenType::False),
+    ("print", TokenType::Print),
+    ("match", TokenType::Match),
+];
 
 pub struct Lexer {
-    source: String,
+    source: Vec<char>,
     tokens: Vec<Token>,
     start: usize,
     current: usize,
     line: usize,
+    /// Index of the first char of the current line, for token columns.
+    line_start: usize,
+    start_line: usize,
+    start_column: usize,
     keywords: HashMap<&'static str, TokenType>,
+    /// Lint names silenced by `// boof:allow(...)` comments, by the line
+    /// each comment applies to.
+    allowed: HashMap<usize, Vec<String>>,
 }
 
 impl Lexer {
     pub fn from(source: String) -> Self {
-        let keywords = HashMap::from([
-            ("boof", TokenType::Var),
-            ("boofer", TokenType::Func),
-            ("and", TokenType::And),
-            ("for", TokenType::For),
-            ("while", TokenType::While),
-            ("nil", TokenType::Nil),
-            ("or", TokenType::Or),
-            ("if", TokenType::If),
-            ("else", TokenType::Else),
-            ("elseif", TokenType::ElseIf),
-            ("return", TokenType::Return),
-            ("true", TokenType::True),
-            ("false", TokenType::False),
-            ("print", TokenType::Print),
-        ]);
+        Self::from_line(source, 1)
+    }
+
+    /// Lexes `source` as if it began at `line`, for input that continues
+    /// earlier source such as successive lines typed at the prompt.
+    pub fn from_line(source: String, line: usize) -> Self {
+        let keywords = KEYWORDS.iter().cloned().collect();
         Lexer {
-            source,
+            source: source.chars().collect(),
             tokens: vec![],
             start: 0,
             current: 0,
-            line: 1,
+            line,
+            line_start: 0,
+            start_line: line,
+            start_column: 1,
             keywords,
+            allowed: HashMap::new(),
         }
     }
     pub fn scan(&mut self) -> Result<Vec<Token>, Error> {
         while !self.is_finished() {
             self.start = self.current;
+            self.start_line = self.line;
+            self.start_column = self.current - self.line_start + 1;
             self.scan_token()?;
         }
-        self.tokens
-            .push(Token::from(TokenType::EOF, "\0".to_string(), self.line));
+        self.tokens.push(Token::from(
+            TokenType::Eof,
+            "\0".to_string(),
+            self.line,
+            self.current - self.line_start + 1,
+        ));
         Ok(self.tokens.clone())
     }
 
     fn scan_token(&mut self) -> Result<(), Error> {
         let c = self.next();
         match c {
-            '(' => Ok(self.add_token(TokenType::LeftParen)),
-            ')' => Ok(self.add_token(TokenType::RightParen)),
-            '{' => Ok(self.add_token(TokenType::LeftBrace)),
-            '}' => Ok(self.add_token(TokenType::RightBrace)),
-            ';' => Ok(self.add_token(TokenType::SemiColon)),
-            '+' => Ok(self.add_token(TokenType::Plus)),
-            '-' => Ok(self.add_token(TokenType::Minus)),
-            '*' => Ok(self.add_token(TokenType::Star)),
-            '.' => Ok(self.add_token(TokenType::Dot)),
-            ',' => Ok(self.add_token(TokenType::Comma)),
+            '(' => self.add_token(TokenType::LeftParen),
+            ')' => self.add_token(TokenType::RightParen),
+            '{' => self.add_token(TokenType::LeftBrace),
+            '}' => self.add_token(TokenType::RightBrace),
+            '[' => self.add_token(TokenType::LeftBracket),
+            ']' => self.add_token(TokenType::RightBracket),
+            ';' => self.add_token(TokenType::SemiColon),
+            ':' => self.add_token(TokenType::Colon),
+            '+' => self.add_token(TokenType::Plus),
+            '-' => {
+                if self.match_char('>') {
+                    self.add_token(TokenType::Arrow)
+                } else {
+                    self.add_token(TokenType::Minus)
+                }
+            }
+            '*' => self.add_token(TokenType::Star),
+            ',' => self.add_token(TokenType::Comma),
+            '|' => self.add_token(TokenType::Pipe),
+            '.' => {
+                if self.match_char('.') {
+                    if self.match_char('=') {
+                        self.add_token(TokenType::DotDotEqual)
+                    } else {
+                        self.add_token(TokenType::DotDot)
+                    }
+                } else {
+                    self.add_token(TokenType::Dot)
+                }
+            }
             '!' => {
                 if self.match_char('=') {
-                    Ok(self.add_token(TokenType::BangEqual))
+                    self.add_token(TokenType::BangEqual)
                 } else {
-                    Ok(self.add_token(TokenType::Bang))
+                    self.add_token(TokenType::Bang)
                 }
             }
             '=' => {
                 if self.match_char('=') {
-                    Ok(self.add_token(TokenType::EqualEqual))
+                    self.add_token(TokenType::EqualEqual)
+                } else if self.match_char('>') {
+                    self.add_token(TokenType::FatArrow)
                 } else {
-                    Ok(self.add_token(TokenType::Equal))
+                    self.add_token(TokenType::Equal)
                 }
             }
             '>' => {
                 if self.match_char('=') {
-                    Ok(self.add_token(TokenType::GreaterEqual))
+                    self.add_token(TokenType::GreaterEqual)
                 } else {
-                    Ok(self.add_token(TokenType::Greater))
+                    self.add_token(TokenType::Greater)
                 }
             }
             '<' => {
                 if self.match_char('=') {
-                    Ok(self.add_token(TokenType::LessEqual))
+                    self.add_token(TokenType::LessEqual)
                 } else {
-                    Ok(self.add_token(TokenType::Less))
+                    self.add_token(TokenType::Less)
                 }
             }
             '/' => {
@@ -95,36 +151,42 @@ impl Lexer {
                     while self.peek() != '\n' && !self.is_finished() {
                         self.next();
                     }
-                    Ok(())
+                    self.comment();
+                } else if self.regex_allowed() {
+                    self.process_regex()?
                 } else {
-                    Ok(self.add_token(TokenType::Slash))
+                    self.add_token(TokenType::Slash)
                 }
             }
-            ' ' | '\r' | '\t' => Ok(()),
-            '\n' => Ok(self.line += 1),
-            '"' => self.process_string(),
+            ' ' | '\r' | '\t' => (),
+            '\n' => self.new_line(),
+            '"' => self.process_string()?,
             c => {
                 if c.is_numeric() {
-                    self.process_number()
+                    self.process_number()?
                 } else if c.is_ascii_alphabetic() || c == '_' {
                     self.process_identifier()
                 } else {
-                    Err(Error::lexing(
+                    return Err(self.error(
+                        codes::UNEXPECTED_CHARACTER,
                         format!("Unexpected Character \"{c}\""),
-                        self.line,
-                    ))
+                    ));
                 }
             }
         }
+        Ok(())
     }
 
-    fn process_identifier(&mut self) -> Result<(), Error> {
+    fn process_identifier(&mut self) {
         while self.peek().is_ascii_alphanumeric() || self.peek() == '_' {
             self.next();
         }
-        let text = &self.source[self.start..self.current];
-        let token_type = self.keywords.get(&text).unwrap_or(&TokenType::Identifier);
-        Ok(self.add_token(token_type.clone()))
+        let text: String = self.source[self.start..self.current].iter().collect();
+        let token_type = self
+            .keywords
+            .get(text.as_str())
+            .unwrap_or(&TokenType::Identifier);
+        self.add_token(token_type.clone())
     }
 
     fn process_number(&mut self) -> Result<(), Error> {
@@ -137,34 +199,150 @@ impl Lexer {
                 self.next();
             }
         }
-        match self.source[self.start..self.current].parse() {
-            Ok(val) => Ok(self.add_token(TokenType::Number(val))),
-            Err(e) => Err(Error::lexing(e.to_string(), self.line)),
+        let text: String = self.source[self.start..self.current].iter().collect();
+        match text.parse() {
+            Ok(val) => {
+                self.add_token(TokenType::Number(val));
+                Ok(())
+            }
+            Err(e) => Err(self.error(codes::INVALID_NUMBER, e.to_string())),
         }
     }
 
     fn process_string(&mut self) -> Result<(), Error> {
+        let mut value = String::new();
         while self.peek() != '"' && !self.is_finished() {
-            if self.peek() == '\n' {
-                self.line += 1;
+            match self.next() {
+                '\n' => {
+                    self.new_line();
+                    value.push('\n');
+                }
+                '\\' if !self.is_finished() => value.push(self.process_escape()?),
+                c => value.push(c),
             }
-            self.next();
         }
         if self.is_finished() {
-            return Err(Error::lexing("Unterminated String".to_string(), self.line));
+            return Err(self.error(
+                codes::UNTERMINATED_STRING,
+                "Unterminated String".to_string(),
+            ));
         }
 
         self.next();
 
-        let value = &self.source[(self.start + 1)..(self.current - 1)];
-        Ok(self.add_token(TokenType::String(value.to_string())))
+        self.add_token(TokenType::String(value));
+        Ok(())
+    }
+
+    /// Records the lints a `// boof:allow(name, ...)` comment silences: those
+    /// on the line it ends, or on the next line if it is alone on its line.
+    fn comment(&mut self) {
+        let text: String = self.source[self.start + 2..self.current].iter().collect();
+        let Some(names) = text.trim_start().strip_prefix("boof:allow(") else {
+            return;
+        };
+        let names = names.split(')').next().unwrap_or_default();
+        let alone = self
+            .tokens
+            .last()
+            .is_none_or(|token| token.line < self.start_line);
+        let line = if alone {
+            self.start_line + 1
+        } else {
+            self.start_line
+        };
+        self.allowed
+            .entry(line)
+            .or_default()
+            .extend(names.split(',').map(|name| name.trim().to_string()));
+    }
+
+    /// Lint names silenced by `// boof:allow(...)` comments in the source
+    /// scanned so far, by the line they apply to.
+    pub fn allowed_lints(&self) -> &HashMap<usize, Vec<String>> {
+        &self.allowed
+    }
+
+    /// A `/` starts a regex literal unless it follows something that ends an
+    /// operand, in which case it is division: `a / b` but
+    /// `re.find_all(/a+/, s)`.
+    fn regex_allowed(&self) -> bool {
+        !self.tokens.last().is_some_and(|token| {
+            matches!(
+                token.token_type,
+                TokenType::Identifier
+                    | TokenType::Number(_)
+                    | TokenType::String(_)
+                    | TokenType::Regex(_)
+                    | TokenType::True
+                    | TokenType::False
+                    | TokenType::Nil
+                    | TokenType::RightParen
+                    | TokenType::RightBracket
+                    | TokenType::RightBrace
+            )
+        })
+    }
+
+    /// Scans `/pattern/flags`. A `/` inside a class or after a backslash
+    /// does not end the pattern.
+    fn process_regex(&mut self) -> Result<(), Error> {
+        let mut pattern = String::new();
+        let mut in_class = false;
+        loop {
+            if self.is_finished() || self.peek() == '\n' {
+                return Err(self.error(codes::UNTERMINATED_REGEX, "Unterminated Regex".to_string()));
+            }
+            match self.next() {
+                '/' if !in_class => break,
+                '\\' if !self.is_finished() && self.peek() != '\n' => {
+                    pattern.push('\\');
+                    pattern.push(self.next());
+                }
+                c => {
+                    in_class = match c {
+                        '[' => true,
+                        ']' => false,
+                        _ => in_class,
+                    };
+                    pattern.push(c);
+                }
+            }
+        }
+        let mut flags = String::new();
+        while self.peek().is_ascii_alphabetic() {
+            flags.push(self.next());
+        }
+        let regex = Regex::new(&pattern, &flags).map_err(|err| {
+            self.error(
+                codes::INVALID_REGEX,
+                format!("Invalid regex /{pattern}/{flags}: {err}"),
+            )
+        })?;
+        self.add_token(TokenType::Regex(Rc::new(regex)));
+        Ok(())
+    }
+
+    fn process_escape(&mut self) -> Result<char, Error> {
+        match self.next() {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '"' => Ok('"'),
+            '\\' => Ok('\\'),
+            c => Err(self.error(
+                codes::UNKNOWN_ESCAPE,
+                format!("Unknown escape sequence \"\\{c}\""),
+            )),
+        }
     }
 
     fn match_char(&mut self, expected: char) -> bool {
         if self.is_finished() {
             return false;
         }
-        if self.source.chars().nth(self.current) == Some(expected) {
+        if self.source[self.current] == expected {
             self.current += 1;
             return true;
         }
@@ -174,17 +352,36 @@ impl Lexer {
     fn add_token(&mut self, token_type: TokenType) {
         self.tokens.push(Token::from(
             token_type,
-            self.source[self.start..self.current].to_string(),
-            self.line,
+            self.source[self.start..self.current].iter().collect(),
+            self.start_line,
+            self.start_column,
         ));
     }
 
+    /// A lexing error positioned at the start of the current token.
+    fn error(&self, code: &'static ErrorCode, message: String) -> Error {
+        Error::lexing(
+            code,
+            message,
+            Span {
+                line: self.start_line,
+                column: self.start_column,
+            },
+        )
+    }
+
+    /// Records that the char just consumed was a line break.
+    fn new_line(&mut self) {
+        self.line += 1;
+        self.line_start = self.current;
+    }
+
     fn is_finished(&self) -> bool {
         self.current >= self.source.len()
     }
 
     fn next(&mut self) -> char {
-        let ch = self.source.chars().nth(self.current).unwrap();
+        let ch = self.source[self.current];
         self.current += 1;
         ch
     }
@@ -193,13 +390,13 @@ impl Lexer {
         if self.is_finished() {
             return '\0';
         }
-        self.source.chars().nth(self.current).unwrap()
+        self.source[self.current]
     }
 
     fn peek_next(&self) -> char {
         if self.current + 1 >= self.source.len() {
             return '\0';
         }
-        self.source.chars().nth(self.current + 1).unwrap()
+        self.source[self.current + 1]
     }
 }
@@ -0,0 +1,46 @@
+//! The boof language as a library.
+//!
+//! [`Engine`] is the simplest way to embed boof: it owns an [`Interpreter`]
+//! and runs source text or files against a persistent set of globals. The
+//! [`Lexer`], [`Parser`], [`Resolver`] and [`Interpreter`] stages, and the
+//! [`optimize`] pass between the last two, are also exposed for hosts that
+//! need to drive them separately.
+
+mod checker;
+pub mod codes;
+mod convert;
+mod engine;
+mod environment;
+mod error;
+mod expr;
+mod function;
+mod interpreter;
+mod lexer;
+mod lint;
+mod optimizer;
+mod parser;
+mod regex;
+mod resolver;
+mod stdlib;
+mod stmt;
+mod suggest;
+mod token;
+mod types;
+
+pub use checker::Checker;
+pub use codes::ErrorCode;
+pub use convert::{FromBoof, IntoNative, NativeResult, ToBoof};
+pub use engine::Engine;
+pub use error::{Diagnostic, Error, ErrorKind, Frame, RuntimeError, Span, Warning};
+pub use expr::{Depth, Expr, LiteralValue as Value, MatchArm, Pattern, Rest};
+pub use function::{Arity, Function, NativeFn, NativeFunction};
+pub use interpreter::Interpreter;
+pub use lexer::Lexer;
+pub use lint::Linter;
+pub use optimizer::{optimize, optimize_expression};
+pub use parser::Parser;
+pub use regex::{Captures, Regex};
+pub use resolver::Resolver;
+pub use stmt::{CatchClause, FunctionDecl, Stmt};
+pub use token::{Token, TokenType};
+pub use types::Type;
@@ -0,0 +1,468 @@
+//! `boof lint`, which reports code that runs but is probably a mistake.
+//!
+//! Each lint has a name that a `// boof:allow(name, ...)` comment can
+//! silence for the line it ends, or for the next line when the comment is
+//! alone on its line. Names starting with `_` are never reported as unused.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    codes::{self, ErrorCode},
+    error::{Span, Warning},
+    expr::{Expr, LiteralValue, MatchArm},
+    interpreter::Interpreter,
+    stmt::{FunctionDecl, Stmt},
+    token::{Token, TokenType},
+};
+
+/// The lints, by the name used in `// boof:allow(...)`.
+const LINTS: &[(&str, &ErrorCode)] = &[
+    ("unused", codes::UNUSED_VARIABLE),
+    ("unreachable", codes::UNREACHABLE_CODE),
+    ("constant_condition", codes::CONSTANT_CONDITION),
+    ("self_assignment", codes::SELF_ASSIGNMENT),
+    ("nil_comparison", codes::NIL_COMPARISON),
+    ("shadowing", codes::SHADOWED_VARIABLE),
+];
+
+/// A declared name, and whether anything reads it.
+#[derive(Default)]
+struct Declaration {
+    /// What to call the name in an unused warning and where it was declared,
+    /// or `None` for names that aren't reported, such as boofers and catch
+    /// bindings.
+    report: Option<(&'static str, Span)>,
+    used: bool,
+}
+
+pub struct Linter {
+    /// Lint names allowed on each line.
+    allowed: HashMap<usize, Vec<String>>,
+    /// Local scopes, innermost last.
+    scopes: Vec<HashMap<String, Declaration>>,
+    /// Every name declared at the top level, wherever it is declared.
+    top_level: HashSet<String>,
+    globals: Vec<(String, Declaration)>,
+    global_reads: HashSet<String>,
+    warnings: Vec<Warning>,
+}
+
+impl Linter {
+    /// A linter that silences the lints in `allowed` on each line, as
+    /// recorded by [`Lexer::allowed_lints`](crate::Lexer::allowed_lints).
+    pub fn new(allowed: HashMap<usize, Vec<String>>) -> Self {
+        Linter {
+            allowed,
+            scopes: vec![],
+            top_level: HashSet::new(),
+            globals: vec![],
+            global_reads: HashSet::new(),
+            warnings: vec![],
+        }
+    }
+
+    /// Lints a whole script, returning its warnings in source order.
+    pub fn lint(mut self, statements: &[Stmt]) -> Vec<Warning> {
+        for stmt in statements {
+            if let Stmt::Var(name, ..) | Stmt::Function(name, _) = stmt {
+                self.top_level.insert(name.lexeme.clone());
+            }
+        }
+        self.statements(statements);
+        for (name, declaration) in std::mem::take(&mut self.globals) {
+            if !self.global_reads.contains(&name) {
+                self.report_unused(&name, &declaration);
+            }
+        }
+
+        let allowed = self.allowed;
+        let mut warnings: Vec<Warning> = self
+            .warnings
+            .into_iter()
+            .filter(|warning| {
+                let name = LINTS
+                    .iter()
+                    .find(|(_, code)| *code == warning.code)
+                    .map(|(name, _)| *name);
+                !allowed
+                    .get(&warning.span.line)
+                    .is_some_and(|names| names.iter().any(|allowed| Some(allowed.as_str()) == name))
+            })
+            .collect();
+        warnings.sort_by_key(|warning| (warning.span.line, warning.span.column));
+        warnings
+    }
+
+    /// Lints a list of statements, noting any that follow a `return` or
+    /// `throw` and so can never run.
+    fn statements(&mut self, statements: &[Stmt]) {
+        for (i, stmt) in statements.iter().enumerate() {
+            self.statement(stmt);
+            if let Stmt::Return(keyword, _) | Stmt::Throw(keyword, _) = stmt {
+                if i + 1 < statements.len() {
+                    self.warn(
+                        codes::UNREACHABLE_CODE,
+                        format!("code after '{}' is unreachable", keyword.lexeme),
+                        keyword.span(),
+                    );
+                }
+                break;
+            }
+        }
+    }
+
+    fn statement(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Expression(expr) => self.expression(expr),
+            Stmt::Print(exprs) => exprs.iter().for_each(|expr| self.expression(expr)),
+            Stmt::Var(name, _, initializer) => {
+                if let Some(initializer) = initializer {
+                    self.expression(initializer);
+                }
+                self.declare(name, Some("variable"));
+            }
+            Stmt::Block(statements) => self.block(statements),
+            Stmt::If(keyword, condition, then_branch, else_branch) => {
+                self.condition(keyword, condition);
+                self.expression(condition);
+                self.statement(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.statement(else_branch);
+                }
+            }
+            Stmt::While(keyword, condition, body) => {
+                // `while (true)` is how loops that exit with `return` are
+                // written, and is what `for (;;)` becomes.
+                if !matches!(condition, Expr::Literal(LiteralValue::True)) {
+                    self.condition(keyword, condition);
+                }
+                self.expression(condition);
+                self.statement(body);
+            }
+            Stmt::Function(name, declaration) => {
+                self.declare(name, None);
+                self.function(declaration);
+            }
+            Stmt::Throw(_, value) => self.expression(value),
+            Stmt::Try(body, handler, finally) => {
+                self.block(body);
+                if let Some(handler) = handler {
+                    self.scopes.push(HashMap::new());
+                    if let Some(name) = &handler.name {
+                        self.declare(name, None);
+                    }
+                    self.statements(&handler.body);
+                    self.end_scope();
+                }
+                if let Some(finally) = finally {
+                    self.block(finally);
+                }
+            }
+            Stmt::Return(_, value) => {
+                if let Some(value) = value {
+                    self.expression(value);
+                }
+            }
+        }
+    }
+
+    fn expression(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Binary(left, op, right) => {
+                self.nil_comparison(left, op, right);
+                self.expression(left);
+                self.expression(right);
+            }
+            Expr::Logical(left, _, right) => {
+                self.expression(left);
+                self.expression(right);
+            }
+            Expr::Grouping(expr) | Expr::Unary(_, expr) | Expr::Get(expr, _) => {
+                self.expression(expr)
+            }
+            Expr::Literal(_) => (),
+            Expr::Variable(name, _) => self.read(&name.lexeme),
+            Expr::Assign(name, value, _) => {
+                if let Expr::Variable(source, _) = unwrap_grouping(value) {
+                    if source.lexeme == name.lexeme {
+                        self.warn(
+                            codes::SELF_ASSIGNMENT,
+                            format!("'{}' is assigned to itself", name.lexeme),
+                            name.span(),
+                        );
+                    }
+                }
+                self.expression(value);
+            }
+            Expr::Call(callee, _, args) => {
+                self.expression(callee);
+                args.iter().for_each(|arg| self.expression(arg));
+            }
+            Expr::Lambda(declaration) => self.function(declaration),
+            Expr::List(items) => items.iter().for_each(|item| self.expression(item)),
+            Expr::Map(entries) => entries.iter().for_each(|(_, value)| self.expression(value)),
+            Expr::Match(_, subject, arms) => {
+                self.expression(subject);
+                arms.iter().for_each(|arm| self.arm(arm));
+            }
+        }
+    }
+
+    fn function(&mut self, declaration: &FunctionDecl) {
+        self.scopes.push(HashMap::new());
+        for param in &declaration.params {
+            self.declare(param, Some("parameter"));
+        }
+        self.statements(&declaration.body);
+        self.end_scope();
+    }
+
+    fn arm(&mut self, arm: &MatchArm) {
+        // Pattern bindings may shadow on purpose and aren't reported, but
+        // still hide outer names from the guard and body.
+        let mut names = vec![];
+        arm.pattern.bindings(&mut names);
+        let scope = names
+            .into_iter()
+            .map(|name| (name, Declaration::default()))
+            .collect();
+        self.scopes.push(scope);
+        if let Some(guard) = &arm.guard {
+            self.expression(guard);
+        }
+        self.expression(&arm.body);
+        self.end_scope();
+    }
+
+    fn block(&mut self, statements: &[Stmt]) {
+        self.scopes.push(HashMap::new());
+        self.statements(statements);
+        self.end_scope();
+    }
+
+    fn end_scope(&mut self) {
+        let scope = self.scopes.pop().unwrap_or_default();
+        for (name, declaration) in scope {
+            if !declaration.used {
+                self.report_unused(&name, &declaration);
+            }
+        }
+    }
+
+    fn declare(&mut self, name: &Token, kind: Option<&'static str>) {
+        let declaration = Declaration {
+            report: kind.map(|kind| (kind, name.span())),
+            used: false,
+        };
+        let Some(scope) = self.scopes.last() else {
+            if kind.is_some() {
+                self.globals.push((name.lexeme.clone(), declaration));
+            }
+            return;
+        };
+        if !scope.contains_key(&name.lexeme) && self.is_visible(&name.lexeme) {
+            self.warn(
+                codes::SHADOWED_VARIABLE,
+                format!("'{}' shadows a variable in an outer scope", name.lexeme),
+                name.span(),
+            );
+        }
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.lexeme.clone(), declaration);
+        }
+    }
+
+    fn is_visible(&self, name: &str) -> bool {
+        self.top_level.contains(name) || self.scopes.iter().any(|scope| scope.contains_key(name))
+    }
+
+    fn read(&mut self, name: &str) {
+        for scope in self.scopes.iter_mut().rev() {
+            if let Some(declaration) = scope.get_mut(name) {
+                declaration.used = true;
+                return;
+            }
+        }
+        self.global_reads.insert(name.to_string());
+    }
+
+    fn report_unused(&mut self, name: &str, declaration: &Declaration) {
+        if let Some((kind, span)) = declaration.report {
+            if !name.starts_with('_') {
+                self.warn(
+                    codes::UNUSED_VARIABLE,
+                    format!("{kind} '{name}' is never read"),
+                    span,
+                );
+            }
+        }
+    }
+
+    /// Warns about an `if` or `while` whose condition is a literal.
+    fn condition(&mut self, keyword: &Token, condition: &Expr) {
+        if let Expr::Literal(value) = unwrap_grouping(condition) {
+            let always = if Interpreter::is_truthy(value) {
+                "true"
+            } else {
+                "false"
+            };
+            self.warn(
+                codes::CONSTANT_CONDITION,
+                format!("'{}' condition is always {always}", keyword.lexeme),
+                keyword.span(),
+            );
+        }
+    }
+
+    /// Warns about `==` or `!=` against `nil` when the result is already
+    /// known, or when `nil` is written first.
+    fn nil_comparison(&mut self, left: &Expr, op: &Token, right: &Expr) {
+        let equal = match op.token_type {
+            TokenType::EqualEqual => true,
+            TokenType::BangEqual => false,
+            _ => return,
+        };
+        let is_nil =
+            |expr: &Expr| matches!(unwrap_grouping(expr), Expr::Literal(LiteralValue::Nil));
+        let (other, nil_first) = match (is_nil(left), is_nil(right)) {
+            (_, true) => (left, false),
+            (true, false) => (right, true),
+            (false, false) => return,
+        };
+        if is_nil(other) || never_nil(other) {
+            let result = is_nil(other) == equal;
+            self.warn(
+                codes::NIL_COMPARISON,
+                format!("comparison with nil is always {result}"),
+                op.span(),
+            );
+        } else if nil_first {
+            self.warn(
+                codes::NIL_COMPARISON,
+                format!(
+                    "write the value before nil, as in 'value {} nil'",
+                    op.lexeme
+                ),
+                op.span(),
+            );
+        }
+    }
+
+    fn warn(&mut self, code: &'static ErrorCode, message: String, span: Span) {
+        self.warnings.push(Warning {
+            code,
+            message,
+            span,
+        });
+    }
+}
+
+fn unwrap_grouping(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Grouping(inner) => unwrap_grouping(inner),
+        expr => expr,
+    }
+}
+
+/// Whether `expr` always gives a value other than `nil`.
+fn never_nil(expr: &Expr) -> bool {
+    match unwrap_grouping(expr) {
+        Expr::Literal(value) => *value != LiteralValue::Nil,
+        Expr::Binary(..) | Expr::Unary(..) | Expr::Lambda(_) | Expr::List(_) | Expr::Map(_) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer::Lexer, parser::Parser};
+
+    /// The line and code of each warning in `source`.
+    fn lint(source: &str) -> Vec<(usize, &'static str)> {
+        let mut lexer = Lexer::from(source.to_string());
+        let statements = Parser::from(lexer.scan().unwrap()).parse().unwrap();
+        Linter::new(lexer.allowed_lints().clone())
+            .lint(&statements)
+            .iter()
+            .map(|warning| (warning.span.line, warning.code.id))
+            .collect()
+    }
+
+    #[test]
+    fn unused_variables() {
+        assert_eq!(
+            lint("boof a = 1;\nboofer f(p) { boof b = 2; }\nf(1);"),
+            [(1, "B0601"), (2, "B0601"), (2, "B0601")]
+        );
+        assert_eq!(lint("boof _a = 1; boof b = 2; print b;"), []);
+        assert_eq!(lint("boofer f() {} try {} catch (e) {}"), []);
+    }
+
+    #[test]
+    fn unreachable_code() {
+        assert_eq!(
+            lint("boofer f() {\n  return 1;\n  print 2;\n}\nf();"),
+            [(2, "B0602")]
+        );
+        assert_eq!(lint("throw 1;\nprint 2;"), [(1, "B0602")]);
+    }
+
+    #[test]
+    fn constant_conditions() {
+        assert_eq!(lint("if (true) print 1;"), [(1, "B0603")]);
+        assert_eq!(lint("while (nil) print 1;"), [(1, "B0603")]);
+        assert_eq!(lint("boof x = 1; if (x > 0) print x;"), []);
+    }
+
+    #[test]
+    fn self_assignment() {
+        assert_eq!(lint("boof x = 1;\nx = x;\nprint x;"), [(2, "B0604")]);
+    }
+
+    #[test]
+    fn nil_comparisons() {
+        assert_eq!(lint("boof x = nil;\nprint nil == x;"), [(2, "B0605")]);
+        assert_eq!(lint(r#"print "a" != nil;"#), [(1, "B0605")]);
+        assert_eq!(lint("boof x = nil; print x == nil;"), []);
+    }
+
+    #[test]
+    fn shadowing() {
+        assert_eq!(
+            lint("boof x = 1;\n{\n  boof x = 2;\n  print x;\n}\nprint x;"),
+            [(3, "B0606")]
+        );
+        assert_eq!(
+            lint("{ boof x = 1; print x; } { boof x = 2; print x; }"),
+            []
+        );
+    }
+
+    #[test]
+    fn allow_comments() {
+        assert_eq!(lint("boof a = 1; // boof:allow(unused)"), []);
+        assert_eq!(
+            lint("// boof:allow(unused)\nboof a = 1;\nboof b = 2;"),
+            [(3, "B0601")]
+        );
+        assert_eq!(
+            lint("//boof:allow(shadowing, unused)\nboof a = 1; { boof a = 2; }"),
+            []
+        );
+        // Only the named lints are silenced.
+        assert_eq!(
+            lint("boof x = 1; x = x; // boof:allow(unused)"),
+            [(1, "B0604")]
+        );
+    }
+
+    #[test]
+    fn allow_comments_in_strings_are_ignored() {
+        assert_eq!(lint("boof a = \"// boof:allow(unused)\";"), [(1, "B0601")]);
+        assert_eq!(
+            lint("print \"// boof:allow(unused)\";\nboof a = 1;"),
+            [(2, "B0601")]
+        );
+    }
+}
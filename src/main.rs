@@ -1,73 +1,240 @@
-mod interpreter;
-mod error;
-mod expr;
-mod lexer;
-mod parser;
-mod token;
-
-use error::Error;
-use lexer::Lexer;
-use parser::Parser;
+use boof::{codes, Diagnostic, Engine, Error, ErrorCode, Value, Warning};
 use std::env::args;
 use std::path::Path;
+use std::thread;
 use std::{
-    fs,
     io::{self, Write},
     process,
 };
 
+/// How diagnostics are written to stderr.
+#[derive(Clone, Copy)]
+enum ErrorFormat {
+    /// Messages for people, with source excerpts and stack traces.
+    Human,
+    /// One JSON object per line, for editors and CI.
+    Json,
+}
+
+/// What to do with the script.
+#[derive(Clone, Copy, PartialEq)]
+enum Command {
+    Run,
+    /// `boof lint script` reports likely mistakes instead of running it.
+    Lint,
+    /// `boof check script` reports type errors instead of running it.
+    Check,
+}
+
+/// The command line options that apply once the engine is set up.
+struct Options {
+    script: Option<String>,
+    error_format: ErrorFormat,
+}
+
+/// Stack for the thread that runs scripts, deep enough for boofer calls to
+/// reach `MAX_CALL_DEPTH` even in a debug build.
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// The share of `STACK_SIZE` boofer calls may use, leaving the rest for
+/// natives and the frames below the outermost call.
+const STACK_LIMIT: usize = STACK_SIZE / 2;
+
 fn main() {
-    let args: Vec<String> = args().collect();
+    let runner = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .expect("failed to start the script thread");
+    // A panic has already printed its message.
+    if runner.join().is_err() {
+        process::exit(70);
+    }
+}
 
-    let result = match args.len() {
-        1 => run_prompt(),
-        2 => run_file(Path::new(&args[1])),
-        _ => Err(Error::usage()),
+fn run() {
+    let args: Vec<String> = args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "--explain") {
+        match explain(args.get(1).map(String::as_str)) {
+            Ok(()) => return,
+            Err(e) => {
+                eprintln!("{e}");
+                process::exit(e.code());
+            }
+        }
+    }
+    let command = match args.first().map(String::as_str) {
+        Some("lint") => Command::Lint,
+        Some("check") => Command::Check,
+        _ => Command::Run,
+    };
+    let args = match command {
+        Command::Run => args,
+        _ => args[1..].to_vec(),
+    };
+    let (engine, options) = match parse_args(args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(e.code());
+        }
+    };
+    let format = options.error_format;
+    let (result, file) = match (command, options.script) {
+        (Command::Run, None) => (run_prompt(engine, format), "<input>".to_string()),
+        (_, None) => (Err(Error::usage()), String::new()),
+        (Command::Run, Some(path)) => (run_file(engine, Path::new(&path), format), path),
+        (Command::Lint, Some(path)) => (lint_file(engine, Path::new(&path), format), path),
+        (Command::Check, Some(path)) => (check_file(engine, Path::new(&path), format), path),
     };
 
     if let Err(e) = result {
-        eprintln!("{}", e.message);
-        process::exit(e.code);
+        report(format, &e, &file);
+        io::stdout().flush().unwrap();
+        process::exit(e.code());
+    }
+}
+
+/// Applies command line options to a new engine and returns it along with
+/// the remaining options, such as the script to run.
+fn parse_args(args: Vec<String>) -> Result<(Engine, Options), Error> {
+    let mut engine = Engine::new();
+    engine.set_stack_limit(STACK_LIMIT);
+    let mut options = Options {
+        script: None,
+        error_format: ErrorFormat::Human,
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--seed" {
+            let seed = args.next().and_then(|seed| seed.parse().ok());
+            engine.seed_random(seed.ok_or_else(Error::usage)?);
+        } else if let Some(seed) = arg.strip_prefix("--seed=") {
+            engine.seed_random(seed.parse().map_err(|_| Error::usage())?);
+        } else if arg == "--no-opt" {
+            engine.set_optimize(false);
+        } else if arg == "--error-format" {
+            options.error_format = parse_error_format(args.next().as_deref())?;
+        } else if let Some(format) = arg.strip_prefix("--error-format=") {
+            options.error_format = parse_error_format(Some(format))?;
+        } else if !arg.starts_with("--") {
+            options.script = Some(arg);
+            engine.set_script_args(args.collect());
+            break;
+        } else {
+            return Err(Error::usage());
+        }
     }
+    Ok((engine, options))
 }
 
-fn run_file(path: &Path) -> Result<(), Error> {
-    let data = fs::read_to_string(path).or_else(|_| {
-        Err(Error {
-            code: 1,
-            message: format!("Failed to read file {path:?}"),
-        })
+/// Prints the explanation of a diagnostic code, for `boof --explain B0102`.
+fn explain(id: Option<&str>) -> Result<(), Error> {
+    let id = id.ok_or_else(Error::usage)?;
+    let code = ErrorCode::lookup(id).ok_or_else(|| {
+        Error::Usage(Diagnostic::new(
+            codes::INVALID_ARGUMENTS,
+            format!("Unknown error code '{id}'. Codes look like B0102."),
+            None,
+        ))
     })?;
-    run(data)
+    println!("{}: {}\n\n{}", code.id, code.title, code.explanation);
+    Ok(())
 }
 
-fn run_prompt() -> Result<(), Error> {
+fn parse_error_format(format: Option<&str>) -> Result<ErrorFormat, Error> {
+    match format {
+        Some("human") => Ok(ErrorFormat::Human),
+        Some("json") => Ok(ErrorFormat::Json),
+        _ => Err(Error::usage()),
+    }
+}
+
+fn run_file(mut engine: Engine, path: &Path, format: ErrorFormat) -> Result<(), Error> {
+    let file = path.display().to_string();
+    engine.set_warning_handler(move |warning| print_warning(format, warning, &file));
+    engine.run_file(path)
+}
+
+/// Prints the script's warnings without running it, ending with exit code 1
+/// if there were any.
+fn lint_file(mut engine: Engine, path: &Path, format: ErrorFormat) -> Result<(), Error> {
+    let result = engine.lint_file(path);
+    let warnings = engine.take_warnings();
+    let found = !warnings.is_empty();
+    for warning in warnings {
+        print_warning(format, &warning, &path.display().to_string());
+    }
+    result?;
+    if found {
+        return Err(Error::Exit(1));
+    }
+    Ok(())
+}
+
+/// Prints the script's type errors without running it, ending with the
+/// exit code of a parse error if there were any.
+fn check_file(mut engine: Engine, path: &Path, format: ErrorFormat) -> Result<(), Error> {
+    let file = path.display().to_string();
+    let result = engine.check_file(path);
+    print_warnings(&mut engine, format, &file);
+    let errors = result?;
+    for error in &errors {
+        report(format, error, &file);
+    }
+    match errors.first() {
+        Some(error) => Err(Error::Exit(error.code())),
+        None => Ok(()),
+    }
+}
+
+fn run_prompt(mut engine: Engine, format: ErrorFormat) -> Result<(), Error> {
+    engine.set_warning_handler(move |warning| print_warning(format, warning, "<input>"));
     loop {
         print!("> ");
         io::stdout().flush().unwrap();
         let mut line = String::new();
-        let bytes_read = io::stdin().read_line(&mut line).or_else(|_| {
-            Err(Error {
-                code: 1,
-                message: "Failed to read line".to_string(),
-            })
+        let bytes_read = io::stdin().read_line(&mut line).map_err(|_| {
+            Error::Io(Diagnostic::new(
+                codes::FILE_UNREADABLE,
+                "Failed to read line".to_string(),
+                None,
+            ))
         })?;
         if bytes_read == 0 {
             break;
         }
-        run(line)?;
+        let result = engine.eval_line(&line);
+        match result {
+            Ok(Value::Nil) => (),
+            Ok(value) => println!("{value}"),
+            // `exit()` reports no message and ends the prompt.
+            Err(e @ Error::Exit(_)) => return Err(e),
+            Err(e) => report(format, &e, "<input>"),
+        }
     }
     Ok(())
 }
 
-fn run(source: String) -> Result<(), Error> {
-    let mut lexer = Lexer::from(source);
-    let tokens = lexer.scan()?;
-    let mut parser = Parser::from(tokens);
-    let expression = parser.parse();
-    match expression {
-        Ok(expr) => expr.print(),
-        Err(err) => eprintln!("{}", err.message),
+/// Writes an error to stderr, unless it is an `exit()` with nothing to say.
+fn report(format: ErrorFormat, error: &Error, file: &str) {
+    let message = match format {
+        ErrorFormat::Human => error.to_string(),
+        ErrorFormat::Json => error.to_json(Some(file)),
+    };
+    if !message.is_empty() {
+        eprintln!("{message}");
+    }
+}
+
+fn print_warnings(engine: &mut Engine, format: ErrorFormat, file: &str) {
+    for warning in engine.take_warnings() {
+        print_warning(format, &warning, file);
+    }
+}
+
+fn print_warning(format: ErrorFormat, warning: &Warning, file: &str) {
+    match format {
+        ErrorFormat::Human => eprintln!("{warning}"),
+        ErrorFormat::Json => eprintln!("{}", warning.to_json(Some(file))),
     }
-    Ok(())
 }
@@ -0,0 +1,321 @@
+//! Constant folding and dead code removal, run over resolved statements
+//! before they're interpreted.
+//!
+//! Rewrites never change what a script prints or throws. An operator on
+//! literals is only folded when the interpreter would give it a value, so an
+//! error like `"a" - 1` is still raised by the original expression, at its
+//! own line, when the script reaches it.
+
+use std::rc::Rc;
+
+use crate::{
+    expr::{Expr, LiteralValue},
+    interpreter::Interpreter,
+    stmt::{FunctionDecl, Stmt},
+    token::TokenType,
+};
+
+/// Folds constant expressions in `statements`, removes branches and loops
+/// whose condition is known to skip them, and drops statements after one
+/// that never completes, such as `return`.
+pub fn optimize(statements: &mut Vec<Stmt>) {
+    let mut kept = Vec::with_capacity(statements.len());
+    for stmt in statements.drain(..) {
+        let Some(stmt) = statement(stmt) else {
+            continue;
+        };
+        let ends = never_completes(&stmt);
+        kept.push(stmt);
+        if ends {
+            break;
+        }
+    }
+    *statements = kept;
+}
+
+/// Folds the constant parts of `expr`, which becomes a literal if all of it
+/// is constant.
+pub fn optimize_expression(expr: &mut Expr) {
+    let folded = match expr {
+        Expr::Binary(left, op, right) => {
+            optimize_expression(left);
+            optimize_expression(right);
+            match (&**left, &**right) {
+                (Expr::Literal(left), Expr::Literal(right)) => {
+                    Interpreter::binary(op, left.clone(), right.clone()).ok()
+                }
+                _ => None,
+            }
+            .map(Expr::Literal)
+        }
+        Expr::Unary(op, operand) => {
+            optimize_expression(operand);
+            match &**operand {
+                Expr::Literal(value) => Interpreter::unary(op, value.clone()).ok(),
+                _ => None,
+            }
+            .map(Expr::Literal)
+        }
+        // Only literals lose their parentheses, since `(map.key)()` and
+        // `map.key()` are different calls.
+        Expr::Grouping(inner) => {
+            optimize_expression(inner);
+            matches!(**inner, Expr::Literal(_)).then(|| take(inner))
+        }
+        Expr::Logical(left, op, right) => {
+            optimize_expression(left);
+            optimize_expression(right);
+            match &**left {
+                Expr::Literal(value) => {
+                    let short_circuits = match op.token_type {
+                        TokenType::Or => Interpreter::is_truthy(value),
+                        _ => !Interpreter::is_truthy(value),
+                    };
+                    Some(if short_circuits {
+                        take(left)
+                    } else {
+                        take(right)
+                    })
+                }
+                _ => None,
+            }
+        }
+        Expr::Literal(_) | Expr::Variable(..) => None,
+        Expr::Assign(_, value, _) => {
+            optimize_expression(value);
+            None
+        }
+        Expr::Call(callee, _, args) => {
+            optimize_expression(callee);
+            args.iter_mut().for_each(optimize_expression);
+            None
+        }
+        Expr::Get(object, _) => {
+            optimize_expression(object);
+            None
+        }
+        Expr::Lambda(declaration) => {
+            function(declaration);
+            None
+        }
+        Expr::List(items) => {
+            items.iter_mut().for_each(optimize_expression);
+            None
+        }
+        Expr::Map(entries) => {
+            entries
+                .iter_mut()
+                .for_each(|(_, value)| optimize_expression(value));
+            None
+        }
+        Expr::Match(_, subject, arms) => {
+            optimize_expression(subject);
+            for arm in arms {
+                if let Some(guard) = &mut arm.guard {
+                    optimize_expression(guard);
+                }
+                optimize_expression(&mut arm.body);
+            }
+            None
+        }
+    };
+    if let Some(folded) = folded {
+        *expr = folded;
+    }
+}
+
+/// Optimizes one statement, or returns `None` if it would do nothing.
+fn statement(stmt: Stmt) -> Option<Stmt> {
+    let stmt = match stmt {
+        Stmt::Expression(mut expr) => {
+            optimize_expression(&mut expr);
+            Stmt::Expression(expr)
+        }
+        Stmt::Print(mut exprs) => {
+            exprs.iter_mut().for_each(optimize_expression);
+            Stmt::Print(exprs)
+        }
+        Stmt::Var(name, annotation, mut initializer) => {
+            if let Some(initializer) = &mut initializer {
+                optimize_expression(initializer);
+            }
+            Stmt::Var(name, annotation, initializer)
+        }
+        Stmt::Block(mut statements) => {
+            optimize(&mut statements);
+            Stmt::Block(statements)
+        }
+        // A branch runs in the scope of its `if`, so a taken branch can
+        // replace the whole statement.
+        Stmt::If(keyword, mut condition, then_branch, else_branch) => {
+            optimize_expression(&mut condition);
+            let else_branch = else_branch.and_then(|branch| statement(*branch));
+            return match constant(&condition) {
+                Some(true) => statement(*then_branch),
+                Some(false) => else_branch,
+                None => Some(Stmt::If(
+                    keyword,
+                    condition,
+                    Box::new(body(*then_branch)),
+                    else_branch.map(Box::new),
+                )),
+            };
+        }
+        Stmt::While(keyword, mut condition, loop_body) => {
+            optimize_expression(&mut condition);
+            if constant(&condition) == Some(false) {
+                return None;
+            }
+            Stmt::While(keyword, condition, Box::new(body(*loop_body)))
+        }
+        Stmt::Function(name, mut declaration) => {
+            function(&mut declaration);
+            Stmt::Function(name, declaration)
+        }
+        Stmt::Throw(keyword, mut value) => {
+            optimize_expression(&mut value);
+            Stmt::Throw(keyword, value)
+        }
+        Stmt::Try(mut body, mut handler, mut finally) => {
+            optimize(&mut body);
+            if let Some(handler) = &mut handler {
+                optimize(&mut handler.body);
+            }
+            if let Some(finally) = &mut finally {
+                optimize(finally);
+            }
+            Stmt::Try(body, handler, finally)
+        }
+        Stmt::Return(keyword, mut value) => {
+            if let Some(value) = &mut value {
+                optimize_expression(value);
+            }
+            Stmt::Return(keyword, value)
+        }
+    };
+    Some(stmt)
+}
+
+/// Optimizes the body of an `if` or `while`, which can't be left out.
+fn body(stmt: Stmt) -> Stmt {
+    statement(stmt).unwrap_or(Stmt::Block(vec![]))
+}
+
+fn function(declaration: &mut Rc<FunctionDecl>) {
+    // Declarations are only shared once the interpreter has created a
+    // boofer from them, so this only skips code that has already run.
+    if let Some(declaration) = Rc::get_mut(declaration) {
+        optimize(&mut declaration.body);
+    }
+}
+
+/// Whether a condition is known to be truthy or falsy.
+fn constant(condition: &Expr) -> Option<bool> {
+    match condition {
+        Expr::Literal(value) => Some(Interpreter::is_truthy(value)),
+        _ => None,
+    }
+}
+
+/// Whether running `stmt` always ends by returning or throwing, so nothing
+/// after it in the same list can run. Boof has no `break`, so `while (true)`
+/// only ends that way too.
+fn never_completes(stmt: &Stmt) -> bool {
+    match stmt {
+        Stmt::Return(..) | Stmt::Throw(..) => true,
+        Stmt::While(_, condition, _) => constant(condition) == Some(true),
+        _ => false,
+    }
+}
+
+fn take(expr: &mut Expr) -> Expr {
+    std::mem::replace(expr, Expr::Literal(LiteralValue::Nil))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer::Lexer, parser::Parser};
+
+    /// Optimizes `source` and describes what is left of each statement.
+    fn optimized(source: &str) -> Vec<String> {
+        let tokens = Lexer::from(source.to_string()).scan().unwrap();
+        let mut statements = Parser::from(tokens).parse().unwrap();
+        optimize(&mut statements);
+        statements.iter().map(describe).collect()
+    }
+
+    fn describe(stmt: &Stmt) -> String {
+        match stmt {
+            Stmt::Expression(expr) => expr.to_string(),
+            Stmt::Print(exprs) => {
+                let exprs: Vec<String> = exprs.iter().map(|expr| expr.to_string()).collect();
+                format!("print {}", exprs.join(" "))
+            }
+            Stmt::Block(statements) => {
+                let statements: Vec<String> = statements.iter().map(describe).collect();
+                format!("{{ {} }}", statements.join("; "))
+            }
+            Stmt::If(_, condition, ..) => format!("if {condition}"),
+            Stmt::While(_, condition, _) => format!("while {condition}"),
+            Stmt::Function(name, declaration) => {
+                let body: Vec<String> = declaration.body.iter().map(describe).collect();
+                format!("boofer {} {{ {} }}", name.lexeme, body.join("; "))
+            }
+            Stmt::Return(_, Some(value)) => format!("return {value}"),
+            _ => "other".to_string(),
+        }
+    }
+
+    #[test]
+    fn folds_arithmetic() {
+        assert_eq!(optimized("1 + 2 * 3;"), ["Number(7.0)"]);
+        assert_eq!(optimized("-(4 - 6) / 2;"), ["Number(1.0)"]);
+        assert_eq!(optimized("1 < 2 == true;"), ["True"]);
+    }
+
+    #[test]
+    fn folds_strings_and_logic() {
+        assert_eq!(optimized(r#""a" + "b" + "c";"#), [r#"String("abc")"#]);
+        assert_eq!(optimized("!true;"), ["False"]);
+        assert_eq!(optimized("nil or x;"), ["x"]);
+        assert_eq!(optimized("false and x;"), ["False"]);
+    }
+
+    #[test]
+    fn folds_constant_parts_only() {
+        assert_eq!(optimized("x * (2 + 3);"), ["(* x Number(5.0))"]);
+    }
+
+    #[test]
+    fn leaves_errors_to_run_time() {
+        assert_eq!(optimized(r#""a" - 1;"#), [r#"(- String("a") Number(1.0))"#]);
+        assert_eq!(optimized("-nil;"), ["(- Nil)"]);
+    }
+
+    #[test]
+    fn removes_branches_not_taken() {
+        assert_eq!(
+            optimized("if (1 > 2) print 1; else print 2;"),
+            ["print Number(2.0)"]
+        );
+        assert_eq!(
+            optimized("if (false) print 1; elseif (true) { print 2; }"),
+            ["{ print Number(2.0) }"]
+        );
+        assert_eq!(
+            optimized("if (nil) print 1; while (false) print 2;"),
+            Vec::<String>::new()
+        );
+        assert_eq!(optimized("if (x) print 1;"), ["if x"]);
+    }
+
+    #[test]
+    fn removes_unreachable_statements() {
+        assert_eq!(
+            optimized("boofer f() { return 1; print 2; }"),
+            ["boofer f { return Number(1.0) }"]
+        );
+        assert_eq!(optimized("while (true) {} print 1;"), ["while True"]);
+    }
+}
@@ -1,7 +1,14 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
 use crate::{
-    error::Error,
-    expr::{Expr, LiteralValue},
+    codes::{self, ErrorCode},
+    error::{Error, Warning},
+    expr::{Expr, LiteralValue, MatchArm, Pattern, Rest},
+    stmt::{CatchClause, FunctionDecl, Stmt},
+    suggest,
     token::{Token, TokenType},
+    types::Type,
 };
 
 macro_rules! compare_token_types {
@@ -13,14 +20,19 @@ macro_rules! compare_token_types {
             (TokenType::RightParen, TokenType::RightParen) => true,
             (TokenType::LeftBrace, TokenType::LeftBrace) => true,
             (TokenType::RightBrace, TokenType::RightBrace) => true,
+            (TokenType::LeftBracket, TokenType::LeftBracket) => true,
+            (TokenType::RightBracket, TokenType::RightBracket) => true,
             (TokenType::If, TokenType::If) => true,
             (TokenType::Else, TokenType::Else) => true,
             (TokenType::ElseIf, TokenType::ElseIf) => true,
             (TokenType::SemiColon, TokenType::SemiColon) => true,
+            (TokenType::Colon, TokenType::Colon) => true,
             (TokenType::Plus, TokenType::Plus) => true,
             (TokenType::Minus, TokenType::Minus) => true,
             (TokenType::Equal, TokenType::Equal) => true,
             (TokenType::EqualEqual, TokenType::EqualEqual) => true,
+            (TokenType::FatArrow, TokenType::FatArrow) => true,
+            (TokenType::Arrow, TokenType::Arrow) => true,
             (TokenType::Greater, TokenType::Greater) => true,
             (TokenType::GreaterEqual, TokenType::GreaterEqual) => true,
             (TokenType::Less, TokenType::Less) => true,
@@ -30,20 +42,29 @@ macro_rules! compare_token_types {
             (TokenType::Slash, TokenType::Slash) => true,
             (TokenType::Star, TokenType::Star) => true,
             (TokenType::Dot, TokenType::Dot) => true,
+            (TokenType::DotDot, TokenType::DotDot) => true,
+            (TokenType::DotDotEqual, TokenType::DotDotEqual) => true,
             (TokenType::Comma, TokenType::Comma) => true,
+            (TokenType::Pipe, TokenType::Pipe) => true,
             (TokenType::Identifier, TokenType::Identifier) => true,
             (TokenType::String(_), TokenType::String(_)) => true,
             (TokenType::Number(_), TokenType::Number(_)) => true,
+            (TokenType::Regex(_), TokenType::Regex(_)) => true,
             (TokenType::And, TokenType::And) => true,
             (TokenType::Nil, TokenType::Nil) => true,
             (TokenType::While, TokenType::While) => true,
             (TokenType::For, TokenType::For) => true,
             (TokenType::Or, TokenType::Or) => true,
             (TokenType::Return, TokenType::Return) => true,
+            (TokenType::Throw, TokenType::Throw) => true,
+            (TokenType::Try, TokenType::Try) => true,
+            (TokenType::Catch, TokenType::Catch) => true,
+            (TokenType::Finally, TokenType::Finally) => true,
             (TokenType::Print, TokenType::Print) => true,
+            (TokenType::Match, TokenType::Match) => true,
             (TokenType::False, TokenType::False) => true,
             (TokenType::True, TokenType::True) => true,
-            (TokenType::EOF, TokenType::EOF) => true,
+            (TokenType::Eof, TokenType::Eof) => true,
             _ => false,
         }
     };
@@ -52,19 +73,401 @@ macro_rules! compare_token_types {
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    /// Index of the first token of the innermost statement being parsed.
+    statement_start: usize,
+    warnings: Vec<Warning>,
 }
 
 impl Parser {
     pub fn from(tokens: Vec<Token>) -> Self {
-        Parser { tokens, current: 0 }
+        Parser {
+            tokens,
+            current: 0,
+            statement_start: 0,
+            warnings: vec![],
+        }
+    }
+
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    pub fn parse(&mut self) -> Result<Vec<Stmt>, Error> {
+        let mut statements = vec![];
+        while !self.is_finished() {
+            statements.push(self.declaration()?);
+        }
+        Ok(statements)
+    }
+
+    fn declaration(&mut self) -> Result<Stmt, Error> {
+        self.statement_start = self.current;
+        if self.match_tokens(&[TokenType::Var]) {
+            return self.var_declaration();
+        }
+        if self.check(&TokenType::Func) && self.check_next(&TokenType::Identifier) {
+            self.next();
+            return self.function_declaration();
+        }
+        self.statement()
+    }
+
+    fn var_declaration(&mut self) -> Result<Stmt, Error> {
+        let name = self.consume(
+            &TokenType::Identifier,
+            String::from("Expect variable name."),
+        )?;
+        let annotation = if self.match_tokens(&[TokenType::Colon]) {
+            Some(self.type_annotation()?)
+        } else {
+            None
+        };
+        let initializer = if self.match_tokens(&[TokenType::Equal]) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+        self.consume(
+            &TokenType::SemiColon,
+            String::from("Expect ';' after variable declaration."),
+        )?;
+        Ok(Stmt::Var(name, annotation, initializer))
+    }
+
+    fn function_declaration(&mut self) -> Result<Stmt, Error> {
+        let name = self.consume(&TokenType::Identifier, String::from("Expect boofer name."))?;
+        Ok(Stmt::Function(name, self.function_body()?))
+    }
+
+    /// Parses `(<params>) (-> <type>)? { <stmt>* }` following `boofer` or a
+    /// boofer name.
+    fn function_body(&mut self) -> Result<Rc<FunctionDecl>, Error> {
+        self.consume(
+            &TokenType::LeftParen,
+            String::from("Expect '(' before boofer parameters."),
+        )?;
+        let (params, param_types) = self.parameters()?;
+        let return_type = if self.match_tokens(&[TokenType::Arrow]) {
+            Some(self.type_annotation()?)
+        } else {
+            None
+        };
+        self.consume(
+            &TokenType::LeftBrace,
+            String::from("Expect '{' before boofer body."),
+        )?;
+        let body = self.block()?;
+        Ok(Rc::new(FunctionDecl {
+            params,
+            param_types,
+            return_type,
+            body,
+        }))
+    }
+
+    /// Parses a comma separated list of parameters, each with an optional
+    /// `: <type>`, up to and including the `)`.
+    fn parameters(&mut self) -> Result<(Vec<Token>, Vec<Option<Type>>), Error> {
+        let mut params = vec![];
+        let mut types = vec![];
+        while !self.check(&TokenType::RightParen) {
+            params.push(self.consume(
+                &TokenType::Identifier,
+                String::from("Expect parameter name."),
+            )?);
+            types.push(if self.match_tokens(&[TokenType::Colon]) {
+                Some(self.type_annotation()?)
+            } else {
+                None
+            });
+            if !self.match_tokens(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(
+            &TokenType::RightParen,
+            String::from("Expect ')' after parameters."),
+        )?;
+        Ok((params, types))
+    }
+
+    /// Parses the type name in an annotation. `nil` is a keyword, so it is
+    /// accepted as well as identifiers.
+    fn type_annotation(&mut self) -> Result<Type, Error> {
+        let token = self.peek();
+        if !matches!(token.token_type, TokenType::Identifier | TokenType::Nil) {
+            return Err(self.error(codes::UNKNOWN_TYPE, String::from("Expect type name.")));
+        }
+        let Some(ty) = Type::from_name(&token.lexeme) else {
+            let help = suggest::did_you_mean(&token.lexeme, Type::NAMES.iter().copied());
+            return Err(self
+                .error(
+                    codes::UNKNOWN_TYPE,
+                    format!("Unknown type '{}'.", token.lexeme),
+                )
+                .with_notes(help));
+        };
+        self.next();
+        Ok(ty)
+    }
+
+    fn statement(&mut self) -> Result<Stmt, Error> {
+        if self.match_tokens(&[TokenType::Print]) {
+            return self.print_statement();
+        }
+        if self.match_tokens(&[TokenType::If]) {
+            return self.if_statement();
+        }
+        if self.match_tokens(&[TokenType::While]) {
+            return self.while_statement();
+        }
+        if self.match_tokens(&[TokenType::For]) {
+            return self.for_statement();
+        }
+        if self.match_tokens(&[TokenType::Return]) {
+            return self.return_statement();
+        }
+        if self.match_tokens(&[TokenType::Throw]) {
+            return self.throw_statement();
+        }
+        if self.match_tokens(&[TokenType::Try]) {
+            return self.try_statement();
+        }
+        if self.match_tokens(&[TokenType::LeftBrace]) {
+            return Ok(Stmt::Block(self.block()?));
+        }
+        self.expression_statement()
+    }
+
+    fn print_statement(&mut self) -> Result<Stmt, Error> {
+        let mut values = vec![];
+        if !self.check(&TokenType::SemiColon) {
+            values.push(self.expression()?);
+            while self.match_tokens(&[TokenType::Comma]) {
+                values.push(self.expression()?);
+            }
+        }
+        self.consume(
+            &TokenType::SemiColon,
+            String::from("Expect ';' after value."),
+        )?;
+        Ok(Stmt::Print(values))
+    }
+
+    fn if_statement(&mut self) -> Result<Stmt, Error> {
+        let keyword = self.previous();
+        let condition = self.condition()?;
+        let then_branch = self.statement()?;
+        let else_branch = if self.match_tokens(&[TokenType::ElseIf]) {
+            Some(Box::new(self.if_statement()?))
+        } else if self.match_tokens(&[TokenType::Else]) {
+            Some(Box::new(self.statement()?))
+        } else {
+            None
+        };
+        Ok(Stmt::If(
+            keyword,
+            condition,
+            Box::new(then_branch),
+            else_branch,
+        ))
+    }
+
+    fn while_statement(&mut self) -> Result<Stmt, Error> {
+        let keyword = self.previous();
+        let condition = self.condition()?;
+        let body = self.statement()?;
+        Ok(Stmt::While(keyword, condition, Box::new(body)))
+    }
+
+    /// Parses `for (<init>; <condition>; <increment>) <stmt>` and desugars it
+    /// into a `while` loop inside its own block.
+    fn for_statement(&mut self) -> Result<Stmt, Error> {
+        let keyword = self.previous();
+        self.consume(
+            &TokenType::LeftParen,
+            String::from("Expect '(' after 'for'."),
+        )?;
+        let initializer = if self.match_tokens(&[TokenType::SemiColon]) {
+            None
+        } else if self.match_tokens(&[TokenType::Var]) {
+            Some(self.var_declaration()?)
+        } else {
+            Some(self.expression_statement()?)
+        };
+        let condition = if self.check(&TokenType::SemiColon) {
+            Expr::Literal(LiteralValue::True)
+        } else {
+            self.expression()?
+        };
+        self.consume(
+            &TokenType::SemiColon,
+            String::from("Expect ';' after loop condition."),
+        )?;
+        let increment = if self.check(&TokenType::RightParen) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.consume(
+            &TokenType::RightParen,
+            String::from("Expect ')' after for clauses."),
+        )?;
+
+        let mut body = self.statement()?;
+        if let Some(increment) = increment {
+            body = Stmt::Block(vec![body, Stmt::Expression(increment)]);
+        }
+        body = Stmt::While(keyword, condition, Box::new(body));
+        if let Some(initializer) = initializer {
+            body = Stmt::Block(vec![initializer, body]);
+        }
+        Ok(body)
+    }
+
+    fn return_statement(&mut self) -> Result<Stmt, Error> {
+        let keyword = self.previous();
+        let value = if self.check(&TokenType::SemiColon) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.consume(
+            &TokenType::SemiColon,
+            String::from("Expect ';' after return value."),
+        )?;
+        Ok(Stmt::Return(keyword, value))
+    }
+
+    fn throw_statement(&mut self) -> Result<Stmt, Error> {
+        let keyword = self.previous();
+        let value = self.expression()?;
+        self.consume(
+            &TokenType::SemiColon,
+            String::from("Expect ';' after thrown value."),
+        )?;
+        Ok(Stmt::Throw(keyword, value))
+    }
+
+    fn try_statement(&mut self) -> Result<Stmt, Error> {
+        let body = self.braced_block("try")?;
+        let handler = if self.match_tokens(&[TokenType::Catch]) {
+            let mut name = None;
+            if self.match_tokens(&[TokenType::LeftParen]) {
+                name = Some(self.consume(
+                    &TokenType::Identifier,
+                    String::from("Expect error name after '('."),
+                )?);
+                self.consume(
+                    &TokenType::RightParen,
+                    String::from("Expect ')' after error name."),
+                )?;
+            }
+            let body = self.braced_block("catch")?;
+            Some(CatchClause { name, body })
+        } else {
+            None
+        };
+        let finally = if self.match_tokens(&[TokenType::Finally]) {
+            Some(self.braced_block("finally")?)
+        } else {
+            None
+        };
+        if handler.is_none() && finally.is_none() {
+            return Err(self.error(
+                codes::TRY_WITHOUT_HANDLER,
+                String::from("Expect 'catch' or 'finally' after try block."),
+            ));
+        }
+        Ok(Stmt::Try(body, handler, finally))
     }
 
-    pub fn parse(&mut self) -> Result<Expr, Error> {
-        self.expression()
+    /// Parses the `{ ... }` block that must follow `keyword`.
+    fn braced_block(&mut self, keyword: &str) -> Result<Vec<Stmt>, Error> {
+        self.consume(
+            &TokenType::LeftBrace,
+            format!("Expect '{{' after '{keyword}'."),
+        )?;
+        self.block()
+    }
+
+    /// Parses statements up to and including the closing `}`.
+    fn block(&mut self) -> Result<Vec<Stmt>, Error> {
+        let mut statements = vec![];
+        while !self.check(&TokenType::RightBrace) && !self.is_finished() {
+            statements.push(self.declaration()?);
+        }
+        self.consume(
+            &TokenType::RightBrace,
+            String::from("Expect '}' after block."),
+        )?;
+        Ok(statements)
+    }
+
+    /// Parses the parenthesized condition of `if`, `elseif` and `while`.
+    fn condition(&mut self) -> Result<Expr, Error> {
+        self.consume(
+            &TokenType::LeftParen,
+            String::from("Expect '(' before condition."),
+        )?;
+        let condition = self.expression()?;
+        self.consume(
+            &TokenType::RightParen,
+            String::from("Expect ')' after condition."),
+        )?;
+        Ok(condition)
+    }
+
+    /// The trailing `;` may be left off the final statement so the prompt can
+    /// evaluate bare expressions.
+    fn expression_statement(&mut self) -> Result<Stmt, Error> {
+        let expr = self.expression()?;
+        if !self.is_finished() {
+            self.consume(
+                &TokenType::SemiColon,
+                String::from("Expect ';' after expression."),
+            )?;
+        }
+        Ok(Stmt::Expression(expr))
     }
 
     fn expression(&mut self) -> Result<Expr, Error> {
-        self.equality()
+        self.assignment()
+    }
+
+    fn assignment(&mut self) -> Result<Expr, Error> {
+        let expr = self.or()?;
+        if self.match_tokens(&[TokenType::Equal]) {
+            let value = self.assignment()?;
+            if let Expr::Variable(name, _) = expr {
+                return Ok(Expr::Assign(name, Box::new(value), Cell::default()));
+            }
+            return Err(self.error(
+                codes::INVALID_ASSIGNMENT_TARGET,
+                String::from("Invalid assignment target."),
+            ));
+        }
+        Ok(expr)
+    }
+
+    fn or(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.and()?;
+        while self.match_tokens(&[TokenType::Or]) {
+            let op = self.previous();
+            let right = self.and()?;
+            expr = Expr::Logical(Box::new(expr), op, Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.equality()?;
+        while self.match_tokens(&[TokenType::And]) {
+            let op = self.previous();
+            let right = self.equality()?;
+            expr = Expr::Logical(Box::new(expr), op, Box::new(right));
+        }
+        Ok(expr)
     }
 
     fn equality(&mut self) -> Result<Expr, Error> {
@@ -118,7 +521,49 @@ impl Parser {
             let right = self.unary()?;
             return Ok(Expr::Unary(op, Box::new(right)));
         }
-        self.primary()
+        self.call()
+    }
+
+    fn call(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.match_tokens(&[TokenType::LeftParen]) {
+                let paren = self.previous();
+                let mut args = vec![];
+                while !self.check(&TokenType::RightParen) {
+                    args.push(self.expression()?);
+                    if !self.match_tokens(&[TokenType::Comma]) {
+                        break;
+                    }
+                }
+                self.consume(
+                    &TokenType::RightParen,
+                    String::from("Expect ')' after arguments."),
+                )?;
+                expr = Expr::Call(Box::new(expr), paren, args);
+            } else if self.match_tokens(&[TokenType::Dot]) {
+                let name = self.property_name()?;
+                expr = Expr::Get(Box::new(expr), name);
+            } else {
+                break;
+            }
+        }
+        Ok(expr)
+    }
+
+    /// Property names may be keywords, so `re.match` can name a boofer.
+    fn property_name(&mut self) -> Result<Token, Error> {
+        let token = self.peek();
+        let is_word = token
+            .lexeme
+            .starts_with(|c: char| c.is_ascii_alphabetic() || c == '_');
+        if is_word && !matches!(token.token_type, TokenType::String(_)) {
+            return Ok(self.next());
+        }
+        Err(self.error(
+            codes::EXPECTED_PROPERTY_NAME,
+            String::from("Expect property name after '.'."),
+        ))
     }
 
     fn primary(&mut self) -> Result<Expr, Error> {
@@ -141,43 +586,338 @@ impl Parser {
                 return Ok(Expr::Literal(LiteralValue::String(val)));
             }
         }
+        if let TokenType::Regex(regex) = self.peek().token_type {
+            self.next();
+            return Ok(Expr::Literal(LiteralValue::Regex(regex)));
+        }
+        if self.match_tokens(&[TokenType::Identifier]) {
+            return Ok(Expr::Variable(self.previous(), Cell::default()));
+        }
+        if self.match_tokens(&[TokenType::Func]) {
+            return Ok(Expr::Lambda(self.function_body()?));
+        }
+        if self.check(&TokenType::LeftParen) && self.is_lambda() {
+            return self.lambda();
+        }
         if self.match_tokens(&[TokenType::LeftParen]) {
             let expr = self.expression()?;
             self.consume(
                 &TokenType::RightParen,
                 String::from("Expect ')' after expression."),
-            );
+            )?;
             return Ok(Expr::Grouping(Box::new(expr)));
         }
-        Err(Error::parsing(String::from("Expected Expression")))
+        if self.match_tokens(&[TokenType::LeftBracket]) {
+            return self.list();
+        }
+        if self.match_tokens(&[TokenType::LeftBrace]) {
+            return self.map();
+        }
+        if self.match_tokens(&[TokenType::Match]) {
+            return self.match_expression();
+        }
+        Err(self.error(
+            codes::EXPECTED_EXPRESSION,
+            String::from("Expected Expression"),
+        ))
+    }
+
+    /// Looks past the `(` at the current token for a parameter list closed by
+    /// `)` and followed by `=>`, which distinguishes a lambda from a grouping.
+    fn is_lambda(&self) -> bool {
+        let mut i = self.current + 1;
+        loop {
+            match self.tokens[i].token_type {
+                TokenType::RightParen => {
+                    return matches!(
+                        self.tokens.get(i + 1).map(|t| &t.token_type),
+                        Some(TokenType::FatArrow)
+                    );
+                }
+                TokenType::Identifier => {
+                    let after = |i: usize| self.tokens.get(i + 1).map(|t| &t.token_type);
+                    // Skip an annotation, `: <type>`.
+                    if after(i) == Some(&TokenType::Colon) {
+                        i += 2;
+                    }
+                    match after(i) {
+                        Some(TokenType::Comma) => i += 2,
+                        Some(TokenType::RightParen) => i += 1,
+                        _ => return false,
+                    }
+                }
+                _ => return false,
+            }
+        }
     }
 
-    fn synchronize(&mut self) {
+    fn lambda(&mut self) -> Result<Expr, Error> {
         self.next();
-        while !self.is_finished() {
-            if matches!(self.previous().token_type, TokenType::SemiColon) {
+        let (params, param_types) = self.parameters()?;
+        let arrow = self.consume(
+            &TokenType::FatArrow,
+            String::from("Expect '=>' after lambda parameters."),
+        )?;
+        let body = self.expression()?;
+        Ok(Expr::Lambda(Rc::new(FunctionDecl {
+            params,
+            param_types,
+            return_type: None,
+            body: vec![Stmt::Return(arrow, Some(body))],
+        })))
+    }
+
+    fn list(&mut self) -> Result<Expr, Error> {
+        let mut items = vec![];
+        while !self.check(&TokenType::RightBracket) {
+            items.push(self.expression()?);
+            if !self.match_tokens(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(
+            &TokenType::RightBracket,
+            String::from("Expect ']' after list items."),
+        )?;
+        Ok(Expr::List(items))
+    }
+
+    fn map(&mut self) -> Result<Expr, Error> {
+        let mut entries = vec![];
+        while !self.check(&TokenType::RightBrace) {
+            let key = self.map_key()?;
+            self.consume(&TokenType::Colon, String::from("Expect ':' after map key."))?;
+            entries.push((key, self.expression()?));
+            if !self.match_tokens(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(
+            &TokenType::RightBrace,
+            String::from("Expect '}' after map entries."),
+        )?;
+        Ok(Expr::Map(entries))
+    }
+
+    fn map_key(&mut self) -> Result<String, Error> {
+        if self.match_tokens(&[TokenType::Identifier]) {
+            return Ok(self.previous().lexeme);
+        }
+        if self.match_tokens(&[TokenType::String(String::new())]) {
+            if let TokenType::String(key) = self.previous().token_type {
+                return Ok(key);
+            }
+        }
+        Err(self.error(
+            codes::INVALID_MAP_KEY,
+            String::from("Expect identifier or string as map key."),
+        ))
+    }
+
+    fn match_expression(&mut self) -> Result<Expr, Error> {
+        let keyword = self.previous();
+        let subject = self.expression()?;
+        self.consume(
+            &TokenType::LeftBrace,
+            String::from("Expect '{' after match value."),
+        )?;
+        let mut arms = vec![];
+        while !self.check(&TokenType::RightBrace) {
+            let pattern = self.pattern()?;
+            let guard = if self.match_tokens(&[TokenType::If]) {
+                Some(self.expression()?)
+            } else {
+                None
+            };
+            self.consume(
+                &TokenType::FatArrow,
+                String::from("Expect '=>' after match pattern."),
+            )?;
+            let body = self.expression()?;
+            arms.push(MatchArm {
+                pattern,
+                guard,
+                body,
+            });
+            if !self.match_tokens(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(
+            &TokenType::RightBrace,
+            String::from("Expect '}' after match arms."),
+        )?;
+        self.check_literal_match(&keyword, &arms);
+        Ok(Expr::Match(keyword, Box::new(subject), arms))
+    }
+
+    /// Warns when every arm matches a fixed literal and none of them catches
+    /// the remaining values, unless the literals cover both booleans.
+    fn check_literal_match(&mut self, keyword: &Token, arms: &[MatchArm]) {
+        let mut literals = vec![];
+        for arm in arms {
+            if arm.guard.is_none() && arm.pattern.is_irrefutable() {
                 return;
             }
+            match &arm.pattern {
+                Pattern::Literal(l) => literals.push(l),
+                Pattern::Or(alternatives) => {
+                    for alternative in alternatives {
+                        match alternative {
+                            Pattern::Literal(l) => literals.push(l),
+                            _ => return,
+                        }
+                    }
+                }
+                _ => return,
+            }
+        }
+        if literals.contains(&&LiteralValue::True) && literals.contains(&&LiteralValue::False) {
+            return;
+        }
+        self.warnings.push(Warning {
+            code: codes::MATCH_WITHOUT_DEFAULT,
+            message: String::from("match over literal values has no '_' arm"),
+            span: keyword.span(),
+        });
+    }
 
-            match self.peek().token_type {
-                TokenType::Func => return,
-                TokenType::Var => return,
-                TokenType::For => return,
-                TokenType::If => return,
-                TokenType::While => return,
-                TokenType::Print => return,
-                TokenType::Return => return,
-                _ => (),
+    fn pattern(&mut self) -> Result<Pattern, Error> {
+        let mut alternatives = vec![self.pattern_alternative()?];
+        while self.match_tokens(&[TokenType::Pipe]) {
+            alternatives.push(self.pattern_alternative()?);
+        }
+        if alternatives.len() == 1 {
+            return Ok(alternatives.remove(0));
+        }
+        Ok(Pattern::Or(alternatives))
+    }
+
+    fn pattern_alternative(&mut self) -> Result<Pattern, Error> {
+        if self.match_tokens(&[TokenType::Identifier]) {
+            let name = self.previous().lexeme;
+            if name == "_" {
+                return Ok(Pattern::Wildcard);
             }
+            return Ok(Pattern::Binding(name));
+        }
+        if self.match_tokens(&[TokenType::LeftBracket]) {
+            return self.list_pattern();
         }
+        if self.match_tokens(&[TokenType::LeftBrace]) {
+            return self.map_pattern();
+        }
+        let literal = self.pattern_literal()?;
+        if self.match_tokens(&[TokenType::DotDot, TokenType::DotDotEqual]) {
+            let inclusive = matches!(self.previous().token_type, TokenType::DotDotEqual);
+            let end = self.pattern_literal()?;
+            return match (literal, end) {
+                (LiteralValue::Number(start), LiteralValue::Number(end)) => {
+                    Ok(Pattern::Range(start, end, inclusive))
+                }
+                _ => Err(self.error(
+                    codes::NON_NUMERIC_RANGE_PATTERN,
+                    String::from("Range patterns must have number bounds."),
+                )),
+            };
+        }
+        Ok(Pattern::Literal(literal))
+    }
+
+    fn pattern_literal(&mut self) -> Result<LiteralValue, Error> {
+        let negative = self.match_tokens(&[TokenType::Minus]);
+        let literal = match self.peek().token_type {
+            TokenType::Number(val) if negative => LiteralValue::Number(-val),
+            TokenType::Number(val) => LiteralValue::Number(val),
+            TokenType::String(val) if !negative => LiteralValue::String(val),
+            TokenType::True if !negative => LiteralValue::True,
+            TokenType::False if !negative => LiteralValue::False,
+            TokenType::Nil if !negative => LiteralValue::Nil,
+            _ => return Err(self.error(codes::EXPECTED_PATTERN, String::from("Expected pattern."))),
+        };
         self.next();
+        Ok(literal)
+    }
+
+    fn list_pattern(&mut self) -> Result<Pattern, Error> {
+        let mut items = vec![];
+        let mut rest = None;
+        while !self.check(&TokenType::RightBracket) {
+            if self.match_tokens(&[TokenType::DotDot]) {
+                rest = if self.match_tokens(&[TokenType::Identifier]) {
+                    Some(Rest::Binding(self.previous().lexeme))
+                } else {
+                    Some(Rest::Ignored)
+                };
+                break;
+            }
+            items.push(self.pattern()?);
+            if !self.match_tokens(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(
+            &TokenType::RightBracket,
+            String::from("Expect ']' after list pattern."),
+        )?;
+        Ok(Pattern::List(items, rest))
+    }
+
+    fn map_pattern(&mut self) -> Result<Pattern, Error> {
+        let mut entries = vec![];
+        while !self.check(&TokenType::RightBrace) {
+            let key = self.map_key()?;
+            let pattern = if self.match_tokens(&[TokenType::Colon]) {
+                self.pattern()?
+            } else {
+                Pattern::Binding(key.clone())
+            };
+            entries.push((key, pattern));
+            if !self.match_tokens(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(
+            &TokenType::RightBrace,
+            String::from("Expect '}' after map pattern."),
+        )?;
+        Ok(Pattern::Map(entries))
     }
 
     fn consume(&mut self, token_type: &TokenType, message: String) -> Result<Token, Error> {
         if self.check(token_type) {
             return Ok(self.next());
         }
-        Err(Error::parsing(message))
+        let code = match token_type {
+            TokenType::SemiColon => codes::EXPECTED_SEMICOLON,
+            TokenType::RightParen => codes::EXPECTED_RIGHT_PAREN,
+            TokenType::LeftParen => codes::EXPECTED_LEFT_PAREN,
+            TokenType::RightBrace => codes::EXPECTED_RIGHT_BRACE,
+            TokenType::LeftBrace => codes::EXPECTED_LEFT_BRACE,
+            TokenType::RightBracket => codes::EXPECTED_RIGHT_BRACKET,
+            TokenType::Colon => codes::EXPECTED_COLON,
+            TokenType::FatArrow => codes::EXPECTED_FAT_ARROW,
+            _ => codes::EXPECTED_IDENTIFIER,
+        };
+        Err(self.error(code, message))
+    }
+
+    /// A parsing error positioned at the current token. If the statement
+    /// starts with, or the error is next to, a name that looks like a
+    /// misspelled keyword such as `retrun`, a help note suggests the keyword.
+    fn error(&self, code: &'static ErrorCode, message: String) -> Error {
+        let nearby = [
+            Some(self.statement_start),
+            self.current.checked_sub(1),
+            Some(self.current),
+        ];
+        let help = nearby
+            .into_iter()
+            .flatten()
+            .map(|i| &self.tokens[i])
+            .filter(|token| token.token_type == TokenType::Identifier)
+            .find_map(|token| suggest::keyword(&token.lexeme));
+        Error::parsing(code, message, self.peek().span()).with_notes(help)
     }
 
     fn match_tokens(&mut self, token_types: &[TokenType]) -> bool {
@@ -192,11 +932,18 @@ impl Parser {
 
     fn check(&self, token_type: &TokenType) -> bool {
         if self.is_finished() {
-            return true;
+            return matches!(token_type, TokenType::Eof);
         }
         compare_token_types!(self.peek().token_type, token_type)
     }
 
+    fn check_next(&self, token_type: &TokenType) -> bool {
+        match self.tokens.get(self.current + 1) {
+            Some(token) => compare_token_types!(&token.token_type, token_type),
+            None => false,
+        }
+    }
+
     fn next(&mut self) -> Token {
         if !self.is_finished() {
             self.current += 1;
@@ -205,7 +952,7 @@ impl Parser {
     }
 
     fn is_finished(&self) -> bool {
-        matches!(self.peek().token_type, TokenType::EOF)
+        matches!(self.peek().token_type, TokenType::Eof)
     }
 
     fn peek(&self) -> Token {
@@ -0,0 +1,909 @@
+//! A small backtracking regular expression engine behind regex literals and
+//! the `re` module.
+//!
+//! Patterns support literals, `.`, classes like `[a-z]` and `[^,]`, the
+//! escapes `\d \w \s \D \W \S \b \B`, anchors `^ $`, groups (capturing,
+//! `(?:...)` and `(?<name>...)`), alternation and the quantifiers `* + ?
+//! {n} {n,} {n,m}`, each optionally lazy. Flags are `i` (ignore case), `m`
+//! (`^` and `$` match at line breaks) and `s` (`.` matches newlines).
+//!
+//! Matching works on chars, so every position is a char index. Each
+//! (instruction, position) state is explored at most once per search, which
+//! keeps matching linear in the size of the program times the text.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasherDefault, Hasher};
+
+/// Largest count allowed in a `{n,m}` quantifier.
+const MAX_REPEAT: u32 = 1000;
+/// Largest compiled program, to bound the memory a search can use.
+const MAX_PROGRAM: usize = 100_000;
+
+/// The span of each group in one match, as char indices into the text.
+/// Group 0 is the whole match; groups that did not take part are `None`.
+pub type Captures = Vec<Option<(usize, usize)>>;
+
+pub struct Regex {
+    source: String,
+    flags: String,
+    program: Vec<Inst>,
+    names: Vec<Option<String>>,
+    ignore_case: bool,
+    dot_all: bool,
+}
+
+enum Inst {
+    Char(char),
+    Any,
+    Class(Class),
+    Split(usize, usize),
+    Jump(usize),
+    Save(usize),
+    Assert(Assertion),
+    Match,
+}
+
+#[derive(Clone, Copy)]
+enum Assertion {
+    TextStart,
+    TextEnd,
+    LineStart,
+    LineEnd,
+    WordBoundary,
+    NotWordBoundary,
+}
+
+#[derive(Clone)]
+struct Class {
+    negated: bool,
+    items: Vec<ClassItem>,
+}
+
+#[derive(Clone)]
+enum ClassItem {
+    Range(char, char),
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+}
+
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class(Class),
+    Assert(Assertion),
+    Group(Box<Node>, Option<usize>),
+    Concat(Vec<Node>),
+    Alternate(Vec<Node>),
+    Repeat(Box<Node>, u32, Option<u32>, bool),
+}
+
+impl Regex {
+    /// Compiles `pattern` with the given flags, describing the problem if
+    /// either is invalid.
+    pub fn new(pattern: &str, flags: &str) -> Result<Regex, String> {
+        let mut multiline = false;
+        let mut regex = Regex {
+            source: pattern.to_string(),
+            flags: flags.to_string(),
+            program: vec![],
+            names: vec![None],
+            ignore_case: false,
+            dot_all: false,
+        };
+        for flag in flags.chars() {
+            let seen = match flag {
+                'i' => std::mem::replace(&mut regex.ignore_case, true),
+                'm' => std::mem::replace(&mut multiline, true),
+                's' => std::mem::replace(&mut regex.dot_all, true),
+                _ => return Err(format!("unknown flag '{flag}'")),
+            };
+            if seen {
+                return Err(format!("repeated flag '{flag}'"));
+            }
+        }
+
+        let mut parser = PatternParser {
+            chars: pattern.chars().collect(),
+            current: 0,
+            names: vec![None],
+            multiline,
+        };
+        let root = parser.alternation()?;
+        if parser.current < parser.chars.len() {
+            return Err(String::from("unmatched ')'"));
+        }
+        regex.names = parser.names;
+
+        regex.program.push(Inst::Save(0));
+        regex.emit(&root)?;
+        regex.program.push(Inst::Save(1));
+        regex.program.push(Inst::Match);
+        Ok(regex)
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn flags(&self) -> &str {
+        &self.flags
+    }
+
+    /// The name of each group, if it has one, starting with the whole match.
+    pub fn group_names(&self) -> &[Option<String>] {
+        &self.names
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        self.captures_at(&text, 0).is_some()
+    }
+
+    /// The leftmost match starting at or after `start`.
+    pub fn captures_at(&self, text: &[char], start: usize) -> Option<Captures> {
+        self.search(text, start, &mut Visited::new(self.program.len()))
+    }
+
+    /// Every non-overlapping match, scanning from the start of the text.
+    /// After an empty match the search resumes one char further on.
+    pub fn captures_all(&self, text: &[char]) -> Vec<Captures> {
+        let mut visited = Visited::new(self.program.len());
+        let mut matches = vec![];
+        let mut start = 0;
+        while start <= text.len() {
+            let Some(captures) = self.search(text, start, &mut visited) else {
+                break;
+            };
+            // States on the path to this match led somewhere, so they
+            // can't be skipped by the next search.
+            visited.clear();
+            let (begin, end) = captures[0].unwrap_or_default();
+            start = if end == begin { end + 1 } else { end };
+            matches.push(captures);
+        }
+        matches
+    }
+
+    /// Like [`captures_at`](Regex::captures_at), skipping states marked in
+    /// `visited`, which must only hold states that failed to match.
+    fn search(&self, text: &[char], start: usize, visited: &mut Visited) -> Option<Captures> {
+        let mut slots = vec![None; self.names.len() * 2];
+        (start..=text.len()).find_map(|begin| {
+            if !self.run(text, begin, &mut slots, visited) {
+                return None;
+            }
+            Some(slots.chunks(2).map(|pair| pair[0].zip(pair[1])).collect())
+        })
+    }
+
+    fn run(
+        &self,
+        text: &[char],
+        begin: usize,
+        slots: &mut [Option<usize>],
+        visited: &mut Visited,
+    ) -> bool {
+        enum Job {
+            Explore(usize, usize),
+            Restore(usize, Option<usize>),
+        }
+
+        let mut stack = vec![Job::Explore(0, begin)];
+        while let Some(job) = stack.pop() {
+            let (mut pc, mut pos) = match job {
+                Job::Explore(pc, pos) => (pc, pos),
+                Job::Restore(slot, value) => {
+                    slots[slot] = value;
+                    continue;
+                }
+            };
+            while visited.insert(pc, pos) {
+                match &self.program[pc] {
+                    Inst::Match => return true,
+                    Inst::Char(expected) => match text.get(pos) {
+                        Some(&c) if self.char_eq(c, *expected) => {
+                            pc += 1;
+                            pos += 1;
+                        }
+                        _ => break,
+                    },
+                    Inst::Any => match text.get(pos) {
+                        Some('\n') if !self.dot_all => break,
+                        Some(_) => {
+                            pc += 1;
+                            pos += 1;
+                        }
+                        None => break,
+                    },
+                    Inst::Class(class) => match text.get(pos) {
+                        Some(&c) if class.matches(c, self.ignore_case) => {
+                            pc += 1;
+                            pos += 1;
+                        }
+                        _ => break,
+                    },
+                    Inst::Split(first, second) => {
+                        stack.push(Job::Explore(*second, pos));
+                        pc = *first;
+                    }
+                    Inst::Jump(target) => pc = *target,
+                    Inst::Save(slot) => {
+                        stack.push(Job::Restore(*slot, slots[*slot]));
+                        slots[*slot] = Some(pos);
+                        pc += 1;
+                    }
+                    Inst::Assert(assertion) => {
+                        if !assertion.holds(text, pos) {
+                            break;
+                        }
+                        pc += 1;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    fn char_eq(&self, a: char, b: char) -> bool {
+        a == b || (self.ignore_case && fold(a) == fold(b))
+    }
+
+    fn emit(&mut self, node: &Node) -> Result<(), String> {
+        if self.program.len() > MAX_PROGRAM {
+            return Err(String::from("pattern is too large"));
+        }
+        match node {
+            Node::Empty => {}
+            Node::Char(c) => self.program.push(Inst::Char(*c)),
+            Node::Any => self.program.push(Inst::Any),
+            Node::Class(class) => self.program.push(Inst::Class(class.clone())),
+            Node::Assert(assertion) => self.program.push(Inst::Assert(*assertion)),
+            Node::Group(inner, index) => {
+                if let Some(index) = index {
+                    self.program.push(Inst::Save(index * 2));
+                }
+                self.emit(inner)?;
+                if let Some(index) = index {
+                    self.program.push(Inst::Save(index * 2 + 1));
+                }
+            }
+            Node::Concat(nodes) => {
+                for node in nodes {
+                    self.emit(node)?;
+                }
+            }
+            Node::Alternate(branches) => {
+                let mut exits = vec![];
+                for (i, branch) in branches.iter().enumerate() {
+                    if i + 1 == branches.len() {
+                        self.emit(branch)?;
+                        break;
+                    }
+                    let split = self.placeholder();
+                    self.emit(branch)?;
+                    exits.push(self.placeholder());
+                    self.program[split] = Inst::Split(split + 1, self.program.len());
+                }
+                for exit in exits {
+                    self.program[exit] = Inst::Jump(self.program.len());
+                }
+            }
+            Node::Repeat(inner, min, max, greedy) => {
+                for _ in 0..*min {
+                    self.emit(inner)?;
+                }
+                match max {
+                    None => {
+                        let split = self.placeholder();
+                        self.emit(inner)?;
+                        self.program.push(Inst::Jump(split));
+                        self.program[split] = self.split(split + 1, self.program.len(), *greedy);
+                    }
+                    Some(max) => {
+                        let mut splits = vec![];
+                        for _ in *min..*max {
+                            splits.push(self.placeholder());
+                            self.emit(inner)?;
+                        }
+                        let end = self.program.len();
+                        for split in splits {
+                            self.program[split] = self.split(split + 1, end, *greedy);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn placeholder(&mut self) -> usize {
+        self.program.push(Inst::Jump(0));
+        self.program.len() - 1
+    }
+
+    /// A split that prefers `body` when greedy and `skip` when lazy.
+    fn split(&self, body: usize, skip: usize, greedy: bool) -> Inst {
+        if greedy {
+            Inst::Split(body, skip)
+        } else {
+            Inst::Split(skip, body)
+        }
+    }
+}
+
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "/{}/{}", self.source, self.flags)
+    }
+}
+
+impl fmt::Debug for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Regex({self})")
+    }
+}
+
+/// Regexes are equal when they were written the same way.
+impl PartialEq for Regex {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source && self.flags == other.flags
+    }
+}
+
+impl Assertion {
+    fn holds(self, text: &[char], pos: usize) -> bool {
+        match self {
+            Assertion::TextStart => pos == 0,
+            Assertion::TextEnd => pos == text.len(),
+            Assertion::LineStart => pos == 0 || text[pos - 1] == '\n',
+            Assertion::LineEnd => pos == text.len() || text[pos] == '\n',
+            Assertion::WordBoundary | Assertion::NotWordBoundary => {
+                let before = pos > 0 && is_word(text[pos - 1]);
+                let after = text.get(pos).is_some_and(|&c| is_word(c));
+                (before != after) == matches!(self, Assertion::WordBoundary)
+            }
+        }
+    }
+}
+
+impl Class {
+    fn matches(&self, c: char, ignore_case: bool) -> bool {
+        let found = self.contains(c)
+            || (ignore_case
+                && (c.to_lowercase().any(|c| self.contains(c))
+                    || c.to_uppercase().any(|c| self.contains(c))));
+        found != self.negated
+    }
+
+    fn contains(&self, c: char) -> bool {
+        self.items.iter().any(|item| match *item {
+            ClassItem::Range(start, end) => (start..=end).contains(&c),
+            ClassItem::Digit(negated) => c.is_ascii_digit() != negated,
+            ClassItem::Word(negated) => is_word(c) != negated,
+            ClassItem::Space(negated) => c.is_whitespace() != negated,
+        })
+    }
+
+    fn single(item: ClassItem) -> Self {
+        Class {
+            negated: false,
+            items: vec![item],
+        }
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// The states already explored by searches of one text, one bit per
+/// instruction and position. Only the 64-bit words holding marked states
+/// are stored, so memory follows the states a search reaches, however
+/// large the program or the text.
+struct Visited {
+    instructions: usize,
+    words: HashMap<usize, u64, BuildHasherDefault<IndexHasher>>,
+}
+
+impl Visited {
+    fn new(instructions: usize) -> Self {
+        Visited {
+            instructions,
+            words: HashMap::default(),
+        }
+    }
+
+    /// Marks a state, returning `false` if it was already marked.
+    fn insert(&mut self, pc: usize, pos: usize) -> bool {
+        let index = pos * self.instructions + pc;
+        let word = self.words.entry(index / 64).or_default();
+        let mask = 1 << (index % 64);
+        let fresh = *word & mask == 0;
+        *word |= mask;
+        fresh
+    }
+
+    /// Unmarks every state, in time proportional to the states marked.
+    fn clear(&mut self) {
+        self.words.clear();
+    }
+}
+
+/// Hashes the word indexes of a [`Visited`] set. They are plain integers,
+/// so a multiply spreads them well enough, far faster than the default
+/// hasher.
+#[derive(Default)]
+struct IndexHasher(u64);
+
+impl Hasher for IndexHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.write_u64(self.0 ^ u64::from(byte));
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.0 = n.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.write_u64(n as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+struct PatternParser {
+    chars: Vec<char>,
+    current: usize,
+    names: Vec<Option<String>>,
+    multiline: bool,
+}
+
+impl PatternParser {
+    fn alternation(&mut self) -> Result<Node, String> {
+        let mut branches = vec![self.concat()?];
+        while self.match_char('|') {
+            branches.push(self.concat()?);
+        }
+        if branches.len() == 1 {
+            return Ok(branches.remove(0));
+        }
+        Ok(Node::Alternate(branches))
+    }
+
+    fn concat(&mut self) -> Result<Node, String> {
+        let mut nodes = vec![];
+        while !matches!(self.peek(), None | Some('|' | ')')) {
+            nodes.push(self.repeat()?);
+        }
+        match nodes.len() {
+            0 => Ok(Node::Empty),
+            1 => Ok(nodes.remove(0)),
+            _ => Ok(Node::Concat(nodes)),
+        }
+    }
+
+    fn repeat(&mut self) -> Result<Node, String> {
+        let atom = self.atom()?;
+        let (min, max) = match self.peek() {
+            Some('{') => match self.counts()? {
+                Some(counts) => counts,
+                None => return Ok(atom),
+            },
+            Some(c @ ('*' | '+' | '?')) => {
+                self.current += 1;
+                match c {
+                    '*' => (0, None),
+                    '+' => (1, None),
+                    _ => (0, Some(1)),
+                }
+            }
+            _ => return Ok(atom),
+        };
+        if matches!(atom, Node::Assert(_) | Node::Empty) {
+            return Err(String::from("nothing to repeat"));
+        }
+        let greedy = !self.match_char('?');
+        if matches!(self.peek(), Some('*' | '+' | '?')) {
+            return Err(String::from("nothing to repeat"));
+        }
+        Ok(Node::Repeat(Box::new(atom), min, max, greedy))
+    }
+
+    /// Parses `{n}`, `{n,}` or `{n,m}`, consuming it only if well formed so
+    /// that any other `{` is a literal.
+    fn counts(&mut self) -> Result<Option<(u32, Option<u32>)>, String> {
+        let start = self.current;
+        self.current += 1;
+        let Some(min) = self.count() else {
+            self.current = start;
+            return Ok(None);
+        };
+        let max = if self.match_char(',') {
+            match self.peek() {
+                Some('}') => None,
+                _ => match self.count() {
+                    Some(max) => Some(max),
+                    None => {
+                        self.current = start;
+                        return Ok(None);
+                    }
+                },
+            }
+        } else {
+            Some(min)
+        };
+        if !self.match_char('}') {
+            self.current = start;
+            return Ok(None);
+        }
+        if max.is_some_and(|max| max < min) {
+            return Err(format!("invalid repetition {{{min},{}}}", max.unwrap_or(0)));
+        }
+        if min.max(max.unwrap_or(0)) > MAX_REPEAT {
+            return Err(format!("repetition count exceeds {MAX_REPEAT}"));
+        }
+        Ok(Some((min, max)))
+    }
+
+    fn count(&mut self) -> Option<u32> {
+        let start = self.current;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.current += 1;
+        }
+        let digits: String = self.chars[start..self.current].iter().collect();
+        digits.parse().ok()
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        let Some(c) = self.next() else {
+            return Ok(Node::Empty);
+        };
+        match c {
+            '(' => self.group(),
+            '[' => self.class().map(Node::Class),
+            '.' => Ok(Node::Any),
+            '^' if self.multiline => Ok(Node::Assert(Assertion::LineStart)),
+            '^' => Ok(Node::Assert(Assertion::TextStart)),
+            '$' if self.multiline => Ok(Node::Assert(Assertion::LineEnd)),
+            '$' => Ok(Node::Assert(Assertion::TextEnd)),
+            '*' | '+' | '?' => Err(String::from("nothing to repeat")),
+            '\\' => match self.next() {
+                Some('b') => Ok(Node::Assert(Assertion::WordBoundary)),
+                Some('B') => Ok(Node::Assert(Assertion::NotWordBoundary)),
+                Some(c) => Ok(match escape_class(c) {
+                    Some(item) => Node::Class(Class::single(item)),
+                    None => Node::Char(escape_char(c)?),
+                }),
+                None => Err(String::from("pattern ends with a lone '\\'")),
+            },
+            c => Ok(Node::Char(c)),
+        }
+    }
+
+    fn group(&mut self) -> Result<Node, String> {
+        let index = if self.match_char('?') {
+            if self.match_char(':') {
+                None
+            } else if self.match_char('<') || (self.match_char('P') && self.match_char('<')) {
+                let name = self.group_name()?;
+                self.names.push(Some(name));
+                Some(self.names.len() - 1)
+            } else {
+                return Err(String::from("unknown group syntax after '(?'"));
+            }
+        } else {
+            self.names.push(None);
+            Some(self.names.len() - 1)
+        };
+        let inner = self.alternation()?;
+        if !self.match_char(')') {
+            return Err(String::from("unclosed group"));
+        }
+        Ok(Node::Group(Box::new(inner), index))
+    }
+
+    fn group_name(&mut self) -> Result<String, String> {
+        let mut name = String::new();
+        while let Some(c) = self.next() {
+            if c == '>' {
+                if name.is_empty() {
+                    return Err(String::from("empty group name"));
+                }
+                if self.names.contains(&Some(name.clone())) {
+                    return Err(format!("duplicate group name '{name}'"));
+                }
+                return Ok(name);
+            }
+            if !is_word(c) {
+                return Err(format!("invalid character '{c}' in group name"));
+            }
+            name.push(c);
+        }
+        Err(String::from("unclosed group name"))
+    }
+
+    fn class(&mut self) -> Result<Class, String> {
+        let negated = self.match_char('^');
+        let mut items = vec![];
+        let mut first = true;
+        loop {
+            let c = match self.next() {
+                Some(']') if !first => return Ok(Class { negated, items }),
+                Some(c) => c,
+                None => return Err(String::from("unclosed character class")),
+            };
+            first = false;
+            let start = if c == '\\' {
+                let escaped = self
+                    .next()
+                    .ok_or_else(|| String::from("unclosed character class"))?;
+                if let Some(item) = escape_class(escaped) {
+                    items.push(item);
+                    continue;
+                }
+                escape_char(escaped)?
+            } else {
+                c
+            };
+            if self.peek() == Some('-') && !matches!(self.peek_next(), Some(']') | None) {
+                self.current += 1;
+                let end = match self.next() {
+                    Some('\\') => {
+                        let escaped = self
+                            .next()
+                            .ok_or_else(|| String::from("unclosed character class"))?;
+                        escape_char(escaped)?
+                    }
+                    Some(c) => c,
+                    None => return Err(String::from("unclosed character class")),
+                };
+                if end < start {
+                    return Err(format!("invalid class range '{start}-{end}'"));
+                }
+                items.push(ClassItem::Range(start, end));
+            } else {
+                items.push(ClassItem::Range(start, start));
+            }
+        }
+    }
+
+    fn match_char(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.current += 1;
+            return true;
+        }
+        false
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.current).copied()
+    }
+
+    fn peek_next(&self) -> Option<char> {
+        self.chars.get(self.current + 1).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.current += 1;
+        Some(c)
+    }
+}
+
+fn escape_class(c: char) -> Option<ClassItem> {
+    match c {
+        'd' => Some(ClassItem::Digit(false)),
+        'D' => Some(ClassItem::Digit(true)),
+        'w' => Some(ClassItem::Word(false)),
+        'W' => Some(ClassItem::Word(true)),
+        's' => Some(ClassItem::Space(false)),
+        'S' => Some(ClassItem::Space(true)),
+        _ => None,
+    }
+}
+
+fn escape_char(c: char) -> Result<char, String> {
+    match c {
+        'n' => Ok('\n'),
+        't' => Ok('\t'),
+        'r' => Ok('\r'),
+        'f' => Ok('\u{c}'),
+        'v' => Ok('\u{b}'),
+        '0' => Ok('\0'),
+        c if c.is_alphanumeric() => Err(format!("unknown escape '\\{c}'")),
+        c => Ok(c),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The text of each group in the first match of `pattern` in `text`.
+    fn groups(pattern: &str, flags: &str, text: &str) -> Option<Vec<Option<String>>> {
+        let regex = Regex::new(pattern, flags).unwrap();
+        let chars: Vec<char> = text.chars().collect();
+        let captures = regex.captures_at(&chars, 0)?;
+        Some(
+            captures
+                .iter()
+                .map(|span| span.map(|(start, end)| chars[start..end].iter().collect()))
+                .collect(),
+        )
+    }
+
+    /// The text of the first match, if any.
+    fn find(pattern: &str, flags: &str, text: &str) -> Option<String> {
+        groups(pattern, flags, text)?.swap_remove(0)
+    }
+
+    /// The span of every match.
+    fn spans(pattern: &str, text: &str) -> Vec<(usize, usize)> {
+        let chars: Vec<char> = text.chars().collect();
+        Regex::new(pattern, "")
+            .unwrap()
+            .captures_all(&chars)
+            .iter()
+            .map(|captures| captures[0].unwrap())
+            .collect()
+    }
+
+    fn error(pattern: &str, flags: &str) -> String {
+        Regex::new(pattern, flags).err().unwrap()
+    }
+
+    #[test]
+    fn classes() {
+        assert_eq!(find("[a-c]+", "", "xxabcad"), Some("abca".into()));
+        assert_eq!(find("[^,]+", "", ",,one,two"), Some("one".into()));
+        assert_eq!(find(r"\d+", "", "abc 123"), Some("123".into()));
+        assert_eq!(
+            find(r"\w+", "", "  snake_case9!"),
+            Some("snake_case9".into())
+        );
+        assert_eq!(find(r"\s\S", "", "a  b"), Some(" b".into()));
+        assert_eq!(find(r"[\d.-]+", "", "v-1.25"), Some("-1.25".into()));
+        assert_eq!(find("a.c", "", "a\nc abc"), Some("abc".into()));
+        assert_eq!(find("a.c", "s", "a\nc"), Some("a\nc".into()));
+    }
+
+    #[test]
+    fn anchors() {
+        assert_eq!(find("^b", "", "ab"), None);
+        assert_eq!(find("a$", "", "ab"), None);
+        assert_eq!(find("^ab$", "", "ab"), Some("ab".into()));
+        assert_eq!(find(r"\bcat\b", "", "concat cat"), Some("cat".into()));
+        assert_eq!(spans(r"\bcat\b", "concat cat"), [(7, 10)]);
+        assert_eq!(spans(r"\Bcat", "concat cat"), [(3, 6)]);
+    }
+
+    #[test]
+    fn greedy_and_lazy_quantifiers() {
+        assert_eq!(find("<.+>", "", "<a><b>"), Some("<a><b>".into()));
+        assert_eq!(find("<.+?>", "", "<a><b>"), Some("<a>".into()));
+        assert_eq!(find("a*", "", "aaa"), Some("aaa".into()));
+        assert_eq!(find("a*?", "", "aaa"), Some("".into()));
+        assert_eq!(find("a??b", "", "ab"), Some("ab".into()));
+        assert_eq!(find("a{2}", "", "aaaa"), Some("aa".into()));
+        assert_eq!(find("a{2,}", "", "aaaa"), Some("aaaa".into()));
+        assert_eq!(find("a{1,3}?", "", "aaaa"), Some("a".into()));
+        assert_eq!(find("a{2,3}", "", "a"), None);
+    }
+
+    #[test]
+    fn alternation() {
+        assert_eq!(find("cat|dog", "", "hotdog"), Some("dog".into()));
+        assert_eq!(find("a|ab", "", "ab"), Some("a".into()));
+        assert_eq!(find("x(a|b)+y", "", "xababy"), Some("xababy".into()));
+        assert_eq!(find("^(|a)b", "", "b"), Some("b".into()));
+    }
+
+    #[test]
+    fn capture_groups() {
+        assert_eq!(
+            groups(r"(\d+)-(\d+)", "", "from 10-20"),
+            Some(vec![
+                Some("10-20".into()),
+                Some("10".into()),
+                Some("20".into())
+            ])
+        );
+        assert_eq!(
+            groups("(a)|(b)", "", "b"),
+            Some(vec![Some("b".into()), None, Some("b".into())])
+        );
+        assert_eq!(
+            groups("(?:a)(b)", "", "ab"),
+            Some(vec![Some("ab".into()), Some("b".into())])
+        );
+        let regex = Regex::new(r"(?<year>\d{4})-(\d\d)", "").unwrap();
+        assert_eq!(regex.group_names(), [None, Some("year".into()), None]);
+        assert_eq!(
+            groups("(a)+", "", "aaa"),
+            Some(vec![Some("aaa".into()), Some("a".into())])
+        );
+    }
+
+    #[test]
+    fn flags() {
+        assert_eq!(find("hello", "i", "Say HeLLo"), Some("HeLLo".into()));
+        assert_eq!(find("[a-z]+", "i", "ABC"), Some("ABC".into()));
+        assert_eq!(find("^b$", "", "a\nb\nc"), None);
+        assert_eq!(find("^b$", "m", "a\nb\nc"), Some("b".into()));
+        assert_eq!(find("c$", "m", "a\nb\nc"), Some("c".into()));
+        assert_eq!(find("^B$", "mi", "a\nb"), Some("b".into()));
+        // Searches with `find_all`, `replace` and `split` are always global,
+        // so there is no `g` flag.
+        assert_eq!(error("a", "g"), "unknown flag 'g'");
+        assert_eq!(error("a", "ii"), "repeated flag 'i'");
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        for (pattern, message) in [
+            ("(a", "unclosed group"),
+            ("a)", "unmatched ')'"),
+            ("[a-", "unclosed character class"),
+            ("[z-a]", "invalid class range 'z-a'"),
+            ("*a", "nothing to repeat"),
+            ("a**", "nothing to repeat"),
+            ("a{3,2}", "invalid repetition {3,2}"),
+            ("a{1001}", "repetition count exceeds 1000"),
+            ("a\\", "pattern ends with a lone '\\'"),
+            (r"\q", "unknown escape '\\q'"),
+            ("(?<>a)", "empty group name"),
+            ("(?<n>a)(?<n>b)", "duplicate group name 'n'"),
+            ("(?=a)", "unknown group syntax after '(?'"),
+        ] {
+            assert_eq!(error(pattern, ""), message, "{pattern}");
+        }
+        assert_eq!(error("(a{1000}){1000}", ""), "pattern is too large");
+    }
+
+    #[test]
+    fn find_all_advances_past_empty_matches() {
+        assert_eq!(spans("x*", "axxb"), [(0, 0), (1, 3), (3, 3), (4, 4)]);
+        assert_eq!(spans("", "ab"), [(0, 0), (1, 1), (2, 2)]);
+        assert_eq!(spans(r"\b", "hi yo"), [(0, 0), (2, 2), (3, 3), (5, 5)]);
+        assert_eq!(spans("a|", "éa"), [(0, 0), (1, 2), (2, 2)]);
+        assert_eq!(spans("a", ""), []);
+    }
+
+    #[test]
+    fn find_all_over_a_long_text() {
+        let text = "ab ".repeat(100_000);
+        let matches = spans(r"\w+", &text);
+        assert_eq!(matches.len(), 100_000);
+        assert_eq!(matches[99_999], (299_997, 299_999));
+    }
+
+    #[test]
+    fn searches_store_only_the_states_they_reach() {
+        // A dense set for this program and text would take gigabytes.
+        let regex = Regex::new(&format!("b{}", "a".repeat(50_000)), "").unwrap();
+        let text: Vec<char> = "a".repeat(200_000).chars().collect();
+        let mut visited = Visited::new(regex.program.len());
+        assert_eq!(regex.search(&text, 0, &mut visited), None);
+        assert!(visited.words.len() <= 2 * (text.len() + 1));
+        visited.clear();
+        assert!(visited.words.is_empty());
+    }
+
+    #[test]
+    fn matches_after_the_first_are_found_with_a_cleared_set() {
+        // The empty match at 1 ends in the same state as the match before it.
+        assert_eq!(spans("a|", "ab"), [(0, 1), (1, 1), (2, 2)]);
+        assert_eq!(spans("a+b|a", "aab aab"), [(0, 3), (4, 7)]);
+    }
+}
@@ -0,0 +1,419 @@
+use std::collections::HashMap;
+
+use crate::{
+    codes,
+    error::{Error, Span},
+    expr::{Depth, Expr, MatchArm, Pattern, Rest},
+    stmt::{FunctionDecl, Stmt},
+    token::Token,
+};
+
+/// Runs between the [`Parser`](crate::Parser) and the
+/// [`Interpreter`](crate::Interpreter), recording the scope each variable
+/// reference means so closures keep seeing the variables they were written
+/// against, and rejecting programs that could never run correctly.
+#[derive(Default)]
+pub struct Resolver {
+    /// Local scopes, innermost last, mapping each name to whether its
+    /// declaration has finished. Globals aren't tracked.
+    scopes: Vec<HashMap<String, bool>>,
+    function_depth: usize,
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves top-level statements, whose declarations are globals.
+    pub fn resolve(&mut self, statements: &[Stmt]) -> Result<(), Error> {
+        statements.iter().try_for_each(|stmt| self.statement(stmt))
+    }
+
+    fn statement(&mut self, stmt: &Stmt) -> Result<(), Error> {
+        match stmt {
+            Stmt::Expression(expr) => self.expression(expr)?,
+            Stmt::Print(exprs) => exprs.iter().try_for_each(|expr| self.expression(expr))?,
+            Stmt::Var(name, _, initializer) => {
+                self.declare(&name.lexeme, name.span())?;
+                if let Some(initializer) = initializer {
+                    self.expression(initializer)?;
+                }
+                self.define(&name.lexeme);
+            }
+            Stmt::Block(statements) => self.block(statements)?,
+            Stmt::If(_, condition, then_branch, else_branch) => {
+                self.expression(condition)?;
+                self.statement(then_branch)?;
+                if let Some(else_branch) = else_branch {
+                    self.statement(else_branch)?;
+                }
+            }
+            Stmt::While(_, condition, body) => {
+                self.expression(condition)?;
+                self.statement(body)?;
+            }
+            Stmt::Function(name, declaration) => {
+                // Defined before the body so the boofer can call itself.
+                self.declare(&name.lexeme, name.span())?;
+                self.define(&name.lexeme);
+                self.function(declaration)?;
+            }
+            Stmt::Throw(_, value) => self.expression(value)?,
+            Stmt::Try(body, handler, finally) => {
+                self.block(body)?;
+                if let Some(handler) = handler {
+                    self.scopes.push(HashMap::new());
+                    if let Some(name) = &handler.name {
+                        self.declare(&name.lexeme, name.span())?;
+                        self.define(&name.lexeme);
+                    }
+                    let result = self.resolve(&handler.body);
+                    self.scopes.pop();
+                    result?;
+                }
+                if let Some(finally) = finally {
+                    self.block(finally)?;
+                }
+            }
+            Stmt::Return(keyword, value) => {
+                if self.function_depth == 0 {
+                    return Err(Error::resolving(
+                        codes::RETURN_OUTSIDE_BOOFER,
+                        String::from("Can't return from top-level code."),
+                        keyword.span(),
+                    ));
+                }
+                if let Some(value) = value {
+                    self.expression(value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn expression(&mut self, expr: &Expr) -> Result<(), Error> {
+        match expr {
+            Expr::Binary(left, _, right) | Expr::Logical(left, _, right) => {
+                self.expression(left)?;
+                self.expression(right)?;
+            }
+            Expr::Grouping(expr) | Expr::Unary(_, expr) | Expr::Get(expr, _) => {
+                self.expression(expr)?
+            }
+            Expr::Literal(_) => (),
+            Expr::Variable(name, depth) => {
+                if self.scopes.last().and_then(|scope| scope.get(&name.lexeme)) == Some(&false) {
+                    return Err(Error::resolving(
+                        codes::READ_IN_OWN_INITIALIZER,
+                        format!(
+                            "Can't read local variable '{}' in its own initializer.",
+                            name.lexeme
+                        ),
+                        name.span(),
+                    ));
+                }
+                depth.set(self.depth_of(name));
+            }
+            Expr::Assign(name, value, depth) => {
+                self.expression(value)?;
+                depth.set(self.depth_of(name));
+            }
+            Expr::Call(callee, _, args) => {
+                self.expression(callee)?;
+                args.iter().try_for_each(|arg| self.expression(arg))?;
+            }
+            Expr::Lambda(declaration) => self.function(declaration)?,
+            Expr::List(items) => items.iter().try_for_each(|item| self.expression(item))?,
+            Expr::Map(entries) => entries
+                .iter()
+                .try_for_each(|(_, value)| self.expression(value))?,
+            Expr::Match(keyword, subject, arms) => {
+                self.expression(subject)?;
+                arms.iter().try_for_each(|arm| self.arm(keyword, arm))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolves a boofer body in a scope holding its parameters, the same
+    /// scope the interpreter runs the body in.
+    fn function(&mut self, declaration: &FunctionDecl) -> Result<(), Error> {
+        self.function_depth += 1;
+        self.scopes.push(HashMap::new());
+        let result = declaration
+            .params
+            .iter()
+            .try_for_each(|param| {
+                self.declare(&param.lexeme, param.span())?;
+                self.define(&param.lexeme);
+                Ok(())
+            })
+            .and_then(|()| self.resolve(&declaration.body));
+        self.scopes.pop();
+        self.function_depth -= 1;
+        result
+    }
+
+    /// Resolves a match arm's guard and body in a scope holding the names
+    /// its pattern binds.
+    fn arm(&mut self, keyword: &Token, arm: &MatchArm) -> Result<(), Error> {
+        self.scopes.push(HashMap::new());
+        let result = self.pattern(keyword, &arm.pattern).and_then(|()| {
+            if let Some(guard) = &arm.guard {
+                self.expression(guard)?;
+            }
+            self.expression(&arm.body)
+        });
+        self.scopes.pop();
+        result
+    }
+
+    /// Declares the names `pattern` binds. Alternatives of an or-pattern may
+    /// bind the same name, since only one of them matches.
+    fn pattern(&mut self, keyword: &Token, pattern: &Pattern) -> Result<(), Error> {
+        match pattern {
+            Pattern::Binding(name) => {
+                self.declare(name, keyword.span())?;
+                self.define(name);
+            }
+            Pattern::Wildcard | Pattern::Literal(_) | Pattern::Range(..) => (),
+            Pattern::List(items, rest) => {
+                for item in items {
+                    self.pattern(keyword, item)?;
+                }
+                if let Some(Rest::Binding(name)) = rest {
+                    self.declare(name, keyword.span())?;
+                    self.define(name);
+                }
+            }
+            Pattern::Map(entries) => {
+                for (_, pattern) in entries {
+                    self.pattern(keyword, pattern)?;
+                }
+            }
+            Pattern::Or(alternatives) => {
+                let outer = self.scopes.pop().unwrap_or_default();
+                let mut bound = outer.clone();
+                for alternative in alternatives {
+                    self.scopes.push(outer.clone());
+                    let result = self.pattern(keyword, alternative);
+                    bound.extend(self.scopes.pop().unwrap_or_default());
+                    result?;
+                }
+                self.scopes.push(bound);
+            }
+        }
+        Ok(())
+    }
+
+    fn block(&mut self, statements: &[Stmt]) -> Result<(), Error> {
+        self.scopes.push(HashMap::new());
+        let result = self.resolve(statements);
+        self.scopes.pop();
+        result
+    }
+
+    /// Adds `name` to the innermost local scope, rejecting a second
+    /// declaration of the same name there.
+    fn declare(&mut self, name: &str, span: Span) -> Result<(), Error> {
+        let Some(scope) = self.scopes.last_mut() else {
+            return Ok(());
+        };
+        if scope.contains_key(name) {
+            return Err(Error::resolving(
+                codes::DUPLICATE_DECLARATION,
+                format!("Already a variable named '{name}' in this scope."),
+                span,
+            ));
+        }
+        scope.insert(name.to_string(), false);
+        Ok(())
+    }
+
+    fn define(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), true);
+        }
+    }
+
+    fn depth_of(&self, name: &Token) -> Depth {
+        self.scopes
+            .iter()
+            .rev()
+            .position(|scope| scope.contains_key(&name.lexeme))
+            .map_or(Depth::Global, Depth::Local)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer::Lexer, parser::Parser};
+
+    fn resolved(source: &str) -> Result<Vec<Stmt>, Error> {
+        let tokens = Lexer::from(source.to_string()).scan().unwrap();
+        let statements = Parser::from(tokens).parse().unwrap();
+        Resolver::new().resolve(&statements)?;
+        Ok(statements)
+    }
+
+    /// The depth given to each variable reference in `source`, in order.
+    fn depths(source: &str) -> Vec<(String, Depth)> {
+        let mut depths = vec![];
+        for stmt in &resolved(source).unwrap() {
+            statement_depths(stmt, &mut depths);
+        }
+        depths
+    }
+
+    fn statement_depths(stmt: &Stmt, depths: &mut Vec<(String, Depth)>) {
+        match stmt {
+            Stmt::Expression(expr) | Stmt::Return(_, Some(expr)) | Stmt::Var(_, _, Some(expr)) => {
+                expression_depths(expr, depths)
+            }
+            Stmt::Print(exprs) => exprs
+                .iter()
+                .for_each(|expr| expression_depths(expr, depths)),
+            Stmt::Block(statements) => statements
+                .iter()
+                .for_each(|stmt| statement_depths(stmt, depths)),
+            Stmt::Function(_, declaration) => declaration
+                .body
+                .iter()
+                .for_each(|stmt| statement_depths(stmt, depths)),
+            _ => (),
+        }
+    }
+
+    fn expression_depths(expr: &Expr, depths: &mut Vec<(String, Depth)>) {
+        match expr {
+            Expr::Variable(name, depth) => depths.push((name.lexeme.clone(), depth.get())),
+            Expr::Grouping(inner) => expression_depths(inner, depths),
+            Expr::Assign(name, value, depth) => {
+                expression_depths(value, depths);
+                depths.push((format!("{}=", name.lexeme), depth.get()));
+            }
+            Expr::Binary(left, _, right) => {
+                expression_depths(left, depths);
+                expression_depths(right, depths);
+            }
+            Expr::Call(callee, _, args) => {
+                expression_depths(callee, depths);
+                args.iter().for_each(|arg| expression_depths(arg, depths));
+            }
+            Expr::Lambda(declaration) => declaration
+                .body
+                .iter()
+                .for_each(|stmt| statement_depths(stmt, depths)),
+            Expr::Match(_, subject, arms) => {
+                expression_depths(subject, depths);
+                arms.iter()
+                    .for_each(|arm| expression_depths(&arm.body, depths));
+            }
+            _ => (),
+        }
+    }
+
+    fn error(source: &str) -> (&'static str, String) {
+        let error = resolved(source).err().unwrap();
+        let diagnostic = error.diagnostic().unwrap();
+        (diagnostic.code.id, diagnostic.message.clone())
+    }
+
+    fn depth(name: &str, depth: Depth) -> (String, Depth) {
+        (name.to_string(), depth)
+    }
+
+    #[test]
+    fn globals_are_looked_up_by_name() {
+        assert_eq!(
+            depths("boof x = 1; print x; boofer f() { return later; }"),
+            [depth("x", Depth::Global), depth("later", Depth::Global)]
+        );
+    }
+
+    #[test]
+    fn closures_capture_through_nested_scopes() {
+        let source = "boofer outer(a) {
+            { boof b = 2;
+              boofer inner() { return () => a + b + inner; }
+            }
+        }";
+        assert_eq!(
+            depths(source),
+            [
+                depth("a", Depth::Local(3)),
+                depth("b", Depth::Local(2)),
+                depth("inner", Depth::Local(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn blocks_shadow_outer_variables() {
+        let source = "boof x = 1;
+        { boof x = 2; { print x; x = 3; } print x; }
+        print x;";
+        assert_eq!(
+            depths(source),
+            [
+                depth("x", Depth::Local(1)),
+                depth("x=", Depth::Local(1)),
+                depth("x", Depth::Local(0)),
+                depth("x", Depth::Global),
+            ]
+        );
+    }
+
+    #[test]
+    fn match_arms_bind_in_their_own_scope() {
+        let source = "boofer f(v) { return match (v) { [x, ..rest] => x + rest, _ => v }; }";
+        assert_eq!(
+            depths(source),
+            [
+                depth("v", Depth::Local(0)),
+                depth("x", Depth::Local(0)),
+                depth("rest", Depth::Local(0)),
+                depth("v", Depth::Local(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_reading_a_local_in_its_initializer() {
+        assert_eq!(
+            error("{ boof a = a + 1; }"),
+            (
+                "B0201",
+                "Can't read local variable 'a' in its own initializer.".to_string()
+            )
+        );
+        // An outer `a` can't be read either, since the new one shadows it.
+        assert_eq!(error("boofer f(a) { { boof a = a; } }").0, "B0201");
+        assert!(resolved("boof a = 1; boof a = a;").is_ok());
+    }
+
+    #[test]
+    fn rejects_return_at_top_level() {
+        assert_eq!(
+            error("if (true) return 1;"),
+            ("B0112", "Can't return from top-level code.".to_string())
+        );
+        assert!(resolved("boof f = boofer() { return 1; };").is_ok());
+    }
+
+    #[test]
+    fn rejects_duplicate_declarations() {
+        assert_eq!(
+            error("{ boof a = 1; boof a = 2; }"),
+            (
+                "B0202",
+                "Already a variable named 'a' in this scope.".to_string()
+            )
+        );
+        assert_eq!(error("boofer f(a, a) {}").0, "B0202");
+        assert_eq!(error("boofer f(a) { boof a = 1; }").0, "B0202");
+        assert!(resolved("boof a = 1; boof a = 2; { boof b = 1; } { boof b = 2; }").is_ok());
+    }
+}
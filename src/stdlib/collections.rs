@@ -0,0 +1,369 @@
+//! Global boofers over lists and maps. They are also methods of lists, and
+//! `keys`, `values` and `entries` are methods of maps.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use crate::{
+    convert::argument,
+    error::{ErrorKind, RuntimeError},
+    expr::LiteralValue,
+    function::Arity,
+    interpreter::Interpreter,
+};
+
+const LIST_METHODS: &[&str] = &[
+    "map",
+    "filter",
+    "reduce",
+    "sort",
+    "reverse",
+    "zip",
+    "enumerate",
+    "any",
+    "all",
+    "sum",
+];
+const MAP_METHODS: &[&str] = &["keys", "values", "entries"];
+
+/// The most numbers `range` will build, so a huge bound fails instead of
+/// exhausting memory.
+const MAX_RANGE_LENGTH: usize = 1 << 24;
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_native("map", Arity::Fixed(2), map);
+    interpreter.register_native("filter", Arity::Fixed(2), filter);
+    interpreter.register_native("reduce", Arity::Range(2, 3), reduce);
+    interpreter.register_native("sort", Arity::Range(1, 2), sort);
+    interpreter.register("reverse", |mut items: Vec<LiteralValue>| {
+        items.reverse();
+        items
+    });
+    interpreter.register("zip", |a: Vec<LiteralValue>, b: Vec<LiteralValue>| {
+        a.into_iter()
+            .zip(b)
+            .map(|(a, b)| vec![a, b])
+            .collect::<Vec<_>>()
+    });
+    interpreter.register("enumerate", |items: Vec<LiteralValue>| {
+        items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| vec![LiteralValue::Number(i as f64), item])
+            .collect::<Vec<_>>()
+    });
+    interpreter.register_native("any", Arity::Range(1, 2), any);
+    interpreter.register_native("all", Arity::Range(1, 2), all);
+    interpreter.register("sum", |numbers: Vec<f64>| numbers.iter().sum::<f64>());
+    interpreter.register_native("range", Arity::Range(1, 3), range);
+    interpreter.register("keys", |entries: BTreeMap<String, LiteralValue>| {
+        entries.into_keys().collect::<Vec<_>>()
+    });
+    interpreter.register("values", |entries: BTreeMap<String, LiteralValue>| {
+        entries.into_values().collect::<Vec<_>>()
+    });
+    interpreter.register("entries", |entries: BTreeMap<String, LiteralValue>| {
+        entries
+            .into_iter()
+            .map(|(key, value)| vec![LiteralValue::String(key), value])
+            .collect::<Vec<_>>()
+    });
+
+    for (type_name, names) in [("list", LIST_METHODS), ("map", MAP_METHODS)] {
+        let methods = names
+            .iter()
+            .filter_map(|name| Some((name.to_string(), interpreter.get_global(name)?)))
+            .collect();
+        interpreter.define_methods(type_name, methods);
+    }
+}
+
+fn map(interpreter: &mut Interpreter, args: &[LiteralValue]) -> Result<LiteralValue, RuntimeError> {
+    let items: Vec<LiteralValue> = argument("map", args, 0)?;
+    let mut mapped = Vec::with_capacity(items.len());
+    for item in items {
+        mapped.push(interpreter.call_value(&args[1], vec![item])?);
+    }
+    Ok(LiteralValue::List(mapped))
+}
+
+fn filter(
+    interpreter: &mut Interpreter,
+    args: &[LiteralValue],
+) -> Result<LiteralValue, RuntimeError> {
+    let items: Vec<LiteralValue> = argument("filter", args, 0)?;
+    let mut kept = vec![];
+    for item in items {
+        if Interpreter::is_truthy(&interpreter.call_value(&args[1], vec![item.clone()])?) {
+            kept.push(item);
+        }
+    }
+    Ok(LiteralValue::List(kept))
+}
+
+/// Folds the list with `f(accumulator, item)`, starting from `initial` or
+/// else the first item.
+fn reduce(
+    interpreter: &mut Interpreter,
+    args: &[LiteralValue],
+) -> Result<LiteralValue, RuntimeError> {
+    let mut items = argument::<Vec<LiteralValue>>("reduce", args, 0)?.into_iter();
+    let initial = match args.get(2) {
+        Some(initial) => Some(initial.clone()),
+        None => items.next(),
+    };
+    let Some(mut accumulator) = initial else {
+        return Err(RuntimeError::native(String::from(
+            "reduce() of an empty list needs an initial value",
+        )));
+    };
+    for item in items {
+        accumulator = interpreter.call_value(&args[1], vec![accumulator, item])?;
+    }
+    Ok(accumulator)
+}
+
+/// Stable sort in natural order, by a one argument key boofer, or by a two
+/// argument comparator returning a negative, zero or positive number.
+fn sort(
+    interpreter: &mut Interpreter,
+    args: &[LiteralValue],
+) -> Result<LiteralValue, RuntimeError> {
+    let items: Vec<LiteralValue> = argument("sort", args, 0)?;
+    let sorted = match args.get(1) {
+        None => merge_sort(items, &mut compare)?,
+        Some(comparator) if takes_two_arguments(comparator) => {
+            merge_sort(items, &mut |a, b| match interpreter
+                .call_value(comparator, vec![a.clone(), b.clone()])?
+            {
+                LiteralValue::Number(n) => Ok(n.partial_cmp(&0.0).unwrap_or(Ordering::Equal)),
+                value => Err(RuntimeError::native(format!(
+                    "sort() comparator must return a number, not {}",
+                    value.type_name()
+                ))
+                .with_kind(ErrorKind::Type)),
+            })?
+        }
+        Some(key) => {
+            let mut keyed = Vec::with_capacity(items.len());
+            for item in items {
+                keyed.push((interpreter.call_value(key, vec![item.clone()])?, item));
+            }
+            merge_sort(keyed, &mut |(a, _), (b, _)| compare(a, b))?
+                .into_iter()
+                .map(|(_, item)| item)
+                .collect()
+        }
+    };
+    Ok(LiteralValue::List(sorted))
+}
+
+/// A stable merge sort that stops at the first error. Unlike
+/// `slice::sort_by` it can't panic when a script's comparator is
+/// inconsistent, which only leaves the order unspecified.
+fn merge_sort<T>(
+    mut items: Vec<T>,
+    compare: &mut impl FnMut(&T, &T) -> Result<Ordering, RuntimeError>,
+) -> Result<Vec<T>, RuntimeError> {
+    if items.len() < 2 {
+        return Ok(items);
+    }
+    let right = items.split_off(items.len() / 2);
+    let mut left = merge_sort(items, compare)?.into_iter().peekable();
+    let mut right = merge_sort(right, compare)?.into_iter().peekable();
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+        // Taking from the left on ties keeps the sort stable.
+        if compare(b, a)? == Ordering::Less {
+            merged.extend(right.next());
+        } else {
+            merged.extend(left.next());
+        }
+    }
+    merged.extend(left);
+    merged.extend(right);
+    Ok(merged)
+}
+
+fn takes_two_arguments(function: &LiteralValue) -> bool {
+    match function {
+        LiteralValue::Function(function) => function.arity() == 2,
+        LiteralValue::NativeFunction(native) => native.arity.accepts(2) && !native.arity.accepts(1),
+        _ => false,
+    }
+}
+
+/// Natural ordering: numbers numerically, strings by code point and lists
+/// element by element.
+fn compare(a: &LiteralValue, b: &LiteralValue) -> Result<Ordering, RuntimeError> {
+    match (a, b) {
+        (LiteralValue::Number(a), LiteralValue::Number(b)) => Ok(a.total_cmp(b)),
+        (LiteralValue::String(a), LiteralValue::String(b)) => Ok(a.cmp(b)),
+        (LiteralValue::List(a), LiteralValue::List(b)) => {
+            for (a, b) in a.iter().zip(b) {
+                match compare(a, b)? {
+                    Ordering::Equal => (),
+                    ordering => return Ok(ordering),
+                }
+            }
+            Ok(a.len().cmp(&b.len()))
+        }
+        _ => Err(RuntimeError::native(format!(
+            "Can't compare {} with {}",
+            a.type_name(),
+            b.type_name()
+        ))
+        .with_kind(ErrorKind::Type)),
+    }
+}
+
+fn any(interpreter: &mut Interpreter, args: &[LiteralValue]) -> Result<LiteralValue, RuntimeError> {
+    let items: Vec<LiteralValue> = argument("any", args, 0)?;
+    for item in items {
+        if test(interpreter, args.get(1), item)? {
+            return Ok(LiteralValue::True);
+        }
+    }
+    Ok(LiteralValue::False)
+}
+
+fn all(interpreter: &mut Interpreter, args: &[LiteralValue]) -> Result<LiteralValue, RuntimeError> {
+    let items: Vec<LiteralValue> = argument("all", args, 0)?;
+    for item in items {
+        if !test(interpreter, args.get(1), item)? {
+            return Ok(LiteralValue::False);
+        }
+    }
+    Ok(LiteralValue::True)
+}
+
+/// Applies the optional predicate of `any` and `all` to an item.
+fn test(
+    interpreter: &mut Interpreter,
+    predicate: Option<&LiteralValue>,
+    item: LiteralValue,
+) -> Result<bool, RuntimeError> {
+    let value = match predicate {
+        Some(predicate) => interpreter.call_value(predicate, vec![item])?,
+        None => item,
+    };
+    Ok(Interpreter::is_truthy(&value))
+}
+
+/// `range(end)`, `range(start, end)` or `range(start, end, step)`, excluding
+/// `end`.
+fn range(_: &mut Interpreter, args: &[LiteralValue]) -> Result<LiteralValue, RuntimeError> {
+    let numbers: Vec<f64> = (0..args.len())
+        .map(|i| argument("range", args, i))
+        .collect::<Result<_, _>>()?;
+    let (start, end, step) = match numbers[..] {
+        [end] => (0.0, end, 1.0),
+        [start, end] => (start, end, 1.0),
+        [start, end, step] => (start, end, step),
+        _ => unreachable!("arity is checked before the call"),
+    };
+    if step == 0.0 {
+        return Err(RuntimeError::native(String::from(
+            "range() step must not be zero",
+        )));
+    }
+    if !(start.is_finite() && end.is_finite() && step.is_finite()) {
+        return Err(
+            RuntimeError::native(String::from("range() bounds and step must be finite"))
+                .with_kind(ErrorKind::Value),
+        );
+    }
+    // Stepping by repeated addition stalls once `step` is below the
+    // spacing of floats near `start`, so each value is worked out from its
+    // index instead.
+    let count = ((end - start) / step).ceil().max(0.0);
+    if count > MAX_RANGE_LENGTH as f64 {
+        return Err(RuntimeError::native(format!(
+            "range() would have more than {MAX_RANGE_LENGTH} numbers"
+        ))
+        .with_kind(ErrorKind::Value));
+    }
+    let values = (0..count as usize)
+        .map(|i| start + i as f64 * step)
+        // Rounding can land the last value on `end`.
+        .take_while(|&n| if step > 0.0 { n < end } else { n > end })
+        .map(LiteralValue::Number)
+        .collect();
+    Ok(LiteralValue::List(values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range_of(bounds: &[f64]) -> Result<Vec<f64>, String> {
+        let args: Vec<LiteralValue> = bounds.iter().map(|&n| LiteralValue::Number(n)).collect();
+        match range(&mut Interpreter::new(), &args) {
+            Ok(LiteralValue::List(items)) => Ok(items
+                .into_iter()
+                .map(|item| match item {
+                    LiteralValue::Number(n) => n,
+                    item => panic!("range() returned {item}"),
+                })
+                .collect()),
+            Ok(value) => panic!("range() returned {value}"),
+            Err(err) => Err(err.message),
+        }
+    }
+
+    #[test]
+    fn range_counts_up_by_one() {
+        assert_eq!(range_of(&[4.0]), Ok(vec![0.0, 1.0, 2.0, 3.0]));
+        assert_eq!(range_of(&[2.0, 5.0]), Ok(vec![2.0, 3.0, 4.0]));
+        assert_eq!(range_of(&[0.5, 3.0]), Ok(vec![0.5, 1.5, 2.5]));
+        assert_eq!(range_of(&[3.0, 3.0]), Ok(vec![]));
+        assert_eq!(range_of(&[5.0, 2.0]), Ok(vec![]));
+    }
+
+    #[test]
+    fn range_counts_down_with_a_negative_step() {
+        assert_eq!(range_of(&[3.0, 0.0, -1.0]), Ok(vec![3.0, 2.0, 1.0]));
+        assert_eq!(
+            range_of(&[0.0, -1.0, -0.25]),
+            Ok(vec![0.0, -0.25, -0.5, -0.75])
+        );
+        assert_eq!(range_of(&[0.0, 3.0, -1.0]), Ok(vec![]));
+    }
+
+    #[test]
+    fn range_with_fractional_steps_excludes_the_end() {
+        let tenths = range_of(&[0.0, 1.0, 0.1]).unwrap();
+        assert_eq!(tenths.len(), 10);
+        assert!(tenths.iter().all(|&n| n < 1.0));
+        assert_eq!(range_of(&[1.0, 2.0, 0.3]), Ok(vec![1.0, 1.3, 1.6, 1.9]));
+        assert_eq!(range_of(&[0.0, 0.3, 0.1]).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn range_ends_near_large_bounds() {
+        // Floats near 1e16 are 2 apart, so adding 1 repeatedly never gets
+        // anywhere.
+        let values = range_of(&[1e16, 1e16 + 4.0]).unwrap();
+        assert_eq!(values, vec![1e16, 1e16, 1e16 + 2.0]);
+        assert_eq!(range_of(&[1e300, 1e300 + 1.0]), Ok(vec![]));
+        assert_eq!(range_of(&[-1e16, -1e16 - 2.0, -1.0]).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn range_rejects_bad_steps_and_lengths() {
+        let too_long = format!("range() would have more than {MAX_RANGE_LENGTH} numbers");
+        assert_eq!(range_of(&[1e300]), Err(too_long.clone()));
+        assert_eq!(range_of(&[-1e308, 1e308]), Err(too_long.clone()));
+        assert_eq!(range_of(&[0.0, 1.0, 1e-300]), Err(too_long));
+        assert_eq!(
+            range_of(&[0.0, 1.0, 0.0]),
+            Err("range() step must not be zero".to_string())
+        );
+        assert_eq!(
+            range_of(&[0.0, f64::INFINITY]),
+            Err("range() bounds and step must be finite".to_string())
+        );
+        assert!(range_of(&[0.0, 1.0, f64::NAN]).is_err());
+        assert!(range_of(&[MAX_RANGE_LENGTH as f64 + 1.0]).is_err());
+    }
+}
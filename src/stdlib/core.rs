@@ -0,0 +1,166 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    error::{ErrorKind, RuntimeError},
+    expr::LiteralValue,
+    function::Arity,
+    interpreter::Interpreter,
+};
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_native("clock", Arity::Fixed(0), clock);
+    interpreter.register_native("type_of", Arity::Fixed(1), type_of);
+    interpreter.register_native("len", Arity::Fixed(1), len);
+    interpreter.register_native("str", Arity::Fixed(1), str);
+}
+
+/// Seconds since the Unix epoch.
+fn clock(_: &mut Interpreter, _: &[LiteralValue]) -> Result<LiteralValue, RuntimeError> {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| RuntimeError::native(e.to_string()))?;
+    Ok(LiteralValue::Number(elapsed.as_secs_f64()))
+}
+
+fn type_of(_: &mut Interpreter, args: &[LiteralValue]) -> Result<LiteralValue, RuntimeError> {
+    Ok(LiteralValue::String(args[0].type_name().to_string()))
+}
+
+/// Number of characters in a string or entries in a list or map.
+fn len(_: &mut Interpreter, args: &[LiteralValue]) -> Result<LiteralValue, RuntimeError> {
+    let len = match &args[0] {
+        LiteralValue::String(s) => s.chars().count(),
+        LiteralValue::List(items) => items.len(),
+        LiteralValue::Map(entries) => entries.len(),
+        value => {
+            return Err(RuntimeError::native(format!(
+                "len() expects a string, list or map, not {}",
+                value.type_name()
+            ))
+            .with_kind(ErrorKind::Type))
+        }
+    };
+    Ok(LiteralValue::Number(len as f64))
+}
+
+fn str(_: &mut Interpreter, args: &[LiteralValue]) -> Result<LiteralValue, RuntimeError> {
+    Ok(LiteralValue::String(args[0].to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Calls the global boofer `name` as a script would, arity check included.
+    fn call(name: &str, args: Vec<LiteralValue>) -> Result<LiteralValue, RuntimeError> {
+        let mut interpreter = Interpreter::new();
+        let function = interpreter.get_global(name).unwrap();
+        interpreter.call_value(&function, args)
+    }
+
+    fn string(s: &str) -> LiteralValue {
+        LiteralValue::String(s.to_string())
+    }
+
+    #[test]
+    fn clock_counts_seconds_since_the_epoch() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs_f64();
+        let LiteralValue::Number(clock) = call("clock", vec![]).unwrap() else {
+            panic!("clock() should return a number");
+        };
+        assert!((clock - now).abs() < 60.0, "{clock} is not close to {now}");
+    }
+
+    #[test]
+    fn type_of_names_each_type() {
+        let len = Interpreter::new().get_global("len").unwrap();
+        let values = [
+            string("a"),
+            LiteralValue::Number(1.0),
+            LiteralValue::List(vec![]),
+            LiteralValue::Map(Default::default()),
+            len,
+            LiteralValue::True,
+            LiteralValue::Nil,
+        ];
+        let names: Vec<String> = values
+            .into_iter()
+            .map(|value| call("type_of", vec![value]).unwrap().to_string())
+            .collect();
+        assert_eq!(
+            names,
+            ["string", "number", "list", "map", "boofer", "bool", "nil"]
+        );
+    }
+
+    #[test]
+    fn len_counts_chars_items_and_entries() {
+        let list = LiteralValue::List(vec![LiteralValue::Nil, LiteralValue::True]);
+        let map = LiteralValue::Map([("a".to_string(), LiteralValue::Nil)].into());
+        for (value, expected) in [(string("héllo"), 5.0), (list, 2.0), (map, 1.0)] {
+            assert_eq!(
+                call("len", vec![value]).unwrap(),
+                LiteralValue::Number(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn len_rejects_other_types() {
+        let err = call("len", vec![LiteralValue::Number(3.0)]).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Type);
+        assert_eq!(
+            err.message,
+            "len() expects a string, list or map, not number"
+        );
+    }
+
+    #[test]
+    fn str_prints_values_as_print_does() {
+        let list = LiteralValue::List(vec![string("a"), LiteralValue::Number(1.5)]);
+        let cases = [
+            (string("as is"), "as is"),
+            (LiteralValue::Number(3.0), "3"),
+            (list, r#"["a", 1.5]"#),
+            (LiteralValue::False, "false"),
+            (LiteralValue::Nil, "nil"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(call("str", vec![value]).unwrap(), string(expected));
+        }
+    }
+
+    #[test]
+    fn natives_check_their_arity() {
+        for (name, args) in [
+            ("clock", vec![LiteralValue::Nil]),
+            ("type_of", vec![]),
+            ("len", vec![string("a"), string("b")]),
+            ("str", vec![]),
+        ] {
+            let err = call(name, args).unwrap_err();
+            assert_eq!(err.kind, ErrorKind::Arity, "{name}");
+        }
+    }
+
+    #[test]
+    fn registered_natives_receive_their_arguments() {
+        let mut interpreter = Interpreter::new();
+        interpreter.register_native("count", Arity::Range(1, 2), |_, args| {
+            Ok(LiteralValue::Number(args.len() as f64))
+        });
+        let count = interpreter.get_global("count").unwrap();
+        let one = vec![LiteralValue::Nil];
+        let three = vec![LiteralValue::Nil; 3];
+        assert_eq!(
+            interpreter.call_value(&count, one).unwrap(),
+            LiteralValue::Number(1.0)
+        );
+        let err = interpreter.call_value(&count, three).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Arity);
+        assert_eq!(err.message, "Expected 1 to 2 arguments but got 3");
+    }
+}
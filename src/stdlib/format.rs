@@ -0,0 +1,258 @@
+//! `format(template, values...)`, which fills `{}` placeholders in a
+//! template.
+//!
+//! A placeholder is `{[argument][:spec]}`. The argument is empty for the next
+//! value, an index such as `{0}`, or a name looked up in a map passed as the
+//! last value. The spec is `[[fill]align][+][0][width][.precision][?]`, where
+//! align is `<`, `^` or `>`, precision fixes the decimals of a number or
+//! truncates other values, and `?` uses the quoted form of strings. `{{` and
+//! `}}` write literal braces.
+
+use crate::{
+    convert::argument, error::RuntimeError, expr::LiteralValue, function::Arity,
+    interpreter::Interpreter,
+};
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_native("format", Arity::Variadic, |_, args| {
+        let template: String = argument("format", args, 0)?;
+        let values = args.get(1..).unwrap_or_default();
+        format(&template, values).map(LiteralValue::String)
+    });
+}
+
+/// Largest width or precision a spec may give, far more than any table
+/// needs while keeping a typo from building a huge string.
+const MAX_WIDTH: usize = 1000;
+
+#[derive(Default)]
+struct Spec {
+    fill: Option<char>,
+    align: Option<char>,
+    sign: bool,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+    debug: bool,
+}
+
+fn format(template: &str, values: &[LiteralValue]) -> Result<String, RuntimeError> {
+    let mut out = String::new();
+    let mut chars = template.chars().peekable();
+    let mut next = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.next_if_eq(&'{').is_some() => out.push('{'),
+            '}' if chars.next_if_eq(&'}').is_some() => out.push('}'),
+            '}' => return Err(error(String::from("unmatched '}' in template"))),
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => return Err(error(String::from("unclosed '{' in template"))),
+                    }
+                }
+                let (name, spec) = placeholder.split_once(':').unwrap_or((&placeholder, ""));
+                let value = if name.is_empty() {
+                    next += 1;
+                    lookup_index(values, next - 1)?
+                } else if let Ok(index) = name.parse() {
+                    lookup_index(values, index)?
+                } else {
+                    lookup_name(values, name)?
+                };
+                Spec::parse(spec)?.write(&mut out, value);
+            }
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+fn lookup_index(values: &[LiteralValue], index: usize) -> Result<&LiteralValue, RuntimeError> {
+    values.get(index).ok_or_else(|| {
+        error(format!(
+            "placeholder {{{index}}} has no value, {} given",
+            values.len()
+        ))
+    })
+}
+
+fn lookup_name<'a>(
+    values: &'a [LiteralValue],
+    name: &str,
+) -> Result<&'a LiteralValue, RuntimeError> {
+    match values.last() {
+        Some(LiteralValue::Map(entries)) => entries
+            .get(name)
+            .ok_or_else(|| error(format!("no value named '{name}' in the map given"))),
+        _ => Err(error(format!(
+            "placeholder {{{name}}} needs a map as the last value"
+        ))),
+    }
+}
+
+impl Spec {
+    fn parse(spec: &str) -> Result<Self, RuntimeError> {
+        let mut result = Spec::default();
+        let chars: Vec<char> = spec.chars().collect();
+        let mut i = 0;
+        if chars.len() >= 2 && matches!(chars[1], '<' | '^' | '>') {
+            result.fill = Some(chars[0]);
+            result.align = Some(chars[1]);
+            i = 2;
+        } else if chars.first().is_some_and(|c| matches!(c, '<' | '^' | '>')) {
+            result.align = Some(chars[0]);
+            i = 1;
+        }
+        if chars.get(i) == Some(&'+') {
+            result.sign = true;
+            i += 1;
+        }
+        if chars.get(i) == Some(&'0') {
+            result.zero = true;
+            i += 1;
+        }
+        let digits = |i: &mut usize, what: &str| -> Result<Option<usize>, RuntimeError> {
+            let start = *i;
+            while chars.get(*i).is_some_and(|c| c.is_ascii_digit()) {
+                *i += 1;
+            }
+            if *i == start {
+                return Ok(None);
+            }
+            let text: String = chars[start..*i].iter().collect();
+            match text.parse() {
+                Ok(n) if n <= MAX_WIDTH => Ok(Some(n)),
+                _ => Err(error(format!(
+                    "{what} {text} in {{:{spec}}} is larger than {MAX_WIDTH}"
+                ))),
+            }
+        };
+        result.width = digits(&mut i, "width")?.unwrap_or(0);
+        if chars.get(i) == Some(&'.') {
+            i += 1;
+            result.precision = Some(
+                digits(&mut i, "precision")?
+                    .ok_or_else(|| error(format!("missing precision after '.' in {{:{spec}}}")))?,
+            );
+        }
+        if chars.get(i) == Some(&'?') {
+            result.debug = true;
+            i += 1;
+        }
+        if i < chars.len() {
+            return Err(error(format!("invalid format spec {{:{spec}}}")));
+        }
+        Ok(result)
+    }
+
+    fn write(&self, out: &mut String, value: &LiteralValue) {
+        let (sign, body) = match value {
+            LiteralValue::Number(n) => {
+                let body = match self.precision {
+                    Some(precision) => format!("{:.precision$}", n.abs()),
+                    None => n.abs().to_string(),
+                };
+                let sign = if n.is_sign_negative() && !n.is_nan() {
+                    "-"
+                } else if self.sign {
+                    "+"
+                } else {
+                    ""
+                };
+                (sign, body)
+            }
+            value => {
+                let text = if self.debug {
+                    value.repr()
+                } else {
+                    value.to_string()
+                };
+                let body = match self.precision {
+                    Some(precision) => text.chars().take(precision).collect(),
+                    None => text,
+                };
+                ("", body)
+            }
+        };
+
+        let len = sign.chars().count() + body.chars().count();
+        let padding = self.width.saturating_sub(len);
+        if self.zero && matches!(value, LiteralValue::Number(_)) {
+            out.push_str(sign);
+            out.extend(std::iter::repeat_n('0', padding));
+            out.push_str(&body);
+            return;
+        }
+        let default_align = match value {
+            LiteralValue::Number(_) => '>',
+            _ => '<',
+        };
+        let (before, after) = match self.align.unwrap_or(default_align) {
+            '<' => (0, padding),
+            '^' => (padding / 2, padding - padding / 2),
+            _ => (padding, 0),
+        };
+        let fill = self.fill.unwrap_or(' ');
+        out.extend(std::iter::repeat_n(fill, before));
+        out.push_str(sign);
+        out.push_str(&body);
+        out.extend(std::iter::repeat_n(fill, after));
+    }
+}
+
+fn error(message: String) -> RuntimeError {
+    RuntimeError::native(format!("format(): {message}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(template: &str, values: &[LiteralValue]) -> Result<String, String> {
+        format(template, values).map_err(|e| e.message)
+    }
+
+    #[test]
+    fn applies_specs() {
+        let pi = LiteralValue::Number(std::f64::consts::PI);
+        let name = LiteralValue::String("boof".to_string());
+        assert_eq!(
+            fill("{:.2}|{:8.3}|{:+}", &[pi.clone(), pi.clone(), pi]),
+            Ok("3.14|   3.142|+3.141592653589793".into())
+        );
+        assert_eq!(
+            fill(
+                "{:>6}|{:*^8}|{:.2}|{:?}",
+                &[name.clone(), name.clone(), name.clone(), name]
+            ),
+            Ok("  boof|**boof**|bo|\"boof\"".into())
+        );
+        assert_eq!(
+            fill("{:05}", &[LiteralValue::Number(-42.0)]),
+            Ok("-0042".into())
+        );
+    }
+
+    #[test]
+    fn limits_width_and_precision() {
+        let n = [LiteralValue::Number(1.0)];
+        assert_eq!(fill("{:1000}", &n).map(|s| s.len()), Ok(1000));
+        assert_eq!(fill("{:.1000}", &n).map(|s| s.len()), Ok(1002));
+        assert_eq!(
+            fill("{:1001}", &n),
+            Err("format(): width 1001 in {:1001} is larger than 1000".into())
+        );
+        assert_eq!(
+            fill("{:.99999999999999999999}", &n),
+            Err("format(): precision 99999999999999999999 in {:.99999999999999999999} is larger than 1000".into())
+        );
+        assert_eq!(
+            fill("{:.}", &n),
+            Err("format(): missing precision after '.' in {:.}".into())
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! The `fs` and `path` modules. Failed operations raise runtime errors
+//! naming the operation and path.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::{
+    convert::argument,
+    error::{io_message, ErrorKind, RuntimeError},
+    expr::LiteralValue,
+    function::Arity,
+    interpreter::Interpreter,
+};
+
+use super::Module;
+
+pub fn register(interpreter: &mut Interpreter) {
+    Module::new("fs")
+        .function("read", |path: String| {
+            fs::read_to_string(&path).map_err(|e| io_error("read file", &path, e))
+        })
+        .function("write", |path: String, contents: String| {
+            fs::write(&path, contents).map_err(|e| io_error("write file", &path, e))
+        })
+        .function("append", append)
+        .function("exists", |path: String| Path::new(&path).exists())
+        .function("list_dir", list_dir)
+        .function("mkdir", |path: String| {
+            fs::create_dir_all(&path).map_err(|e| io_error("create directory", &path, e))
+        })
+        .function("remove", remove)
+        .register(interpreter);
+
+    Module::new("path")
+        .native("join", Arity::Variadic, join)
+        .function("basename", |path: String| {
+            Path::new(&path)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .function("dirname", |path: String| {
+            Path::new(&path)
+                .parent()
+                .map(|parent| parent.to_string_lossy().into_owned())
+        })
+        .function("extension", |path: String| {
+            Path::new(&path)
+                .extension()
+                .map(|extension| extension.to_string_lossy().into_owned())
+        })
+        .register(interpreter);
+}
+
+fn io_error(action: &str, path: &str, err: io::Error) -> RuntimeError {
+    RuntimeError::native(io_message(action, Path::new(path), &err)).with_kind(ErrorKind::Io)
+}
+
+fn append(path: String, contents: String) -> Result<(), RuntimeError> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|e| io_error("append to file", &path, e))
+}
+
+/// Names of the entries in a directory, sorted.
+fn list_dir(path: String) -> Result<Vec<String>, RuntimeError> {
+    let entries = fs::read_dir(&path).map_err(|e| io_error("list directory", &path, e))?;
+    let mut names = vec![];
+    for entry in entries {
+        let entry = entry.map_err(|e| io_error("list directory", &path, e))?;
+        names.push(entry.file_name().to_string_lossy().into_owned());
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Removes a file or an empty directory, or any directory when `recursive`.
+fn remove(path: String, recursive: Option<bool>) -> Result<(), RuntimeError> {
+    let result = if !Path::new(&path).is_dir() {
+        fs::remove_file(&path)
+    } else if recursive.unwrap_or(false) {
+        fs::remove_dir_all(&path)
+    } else {
+        fs::remove_dir(&path)
+    };
+    result.map_err(|e| io_error("remove", &path, e))
+}
+
+fn join(_: &mut Interpreter, args: &[LiteralValue]) -> Result<LiteralValue, RuntimeError> {
+    let mut path = PathBuf::new();
+    for i in 0..args.len() {
+        path.push(argument::<String>("path.join", args, i)?);
+    }
+    Ok(LiteralValue::String(path.to_string_lossy().into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Engine;
+
+    /// A fresh directory for one test, removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("boof-fs-{}-{name}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+
+        /// Runs `source` with the directory's path bound to `dir`.
+        fn eval(&self, source: &str) -> Result<String, (ErrorKind, String)> {
+            let mut engine = Engine::new();
+            let dir = self.0.display().to_string();
+            engine.set_global("dir", LiteralValue::String(dir));
+            engine
+                .eval(source)
+                .map(|value| value.to_string())
+                .map_err(|err| match err {
+                    crate::Error::Runtime(kind, diagnostic) => (kind, diagnostic.message),
+                    err => panic!("unexpected error {err}"),
+                })
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn writes_appends_and_reads_files() {
+        let dir = TempDir::new("write");
+        let source = r#"
+            boof file = path.join(dir, "notes.txt");
+            fs.write(file, "one\n");
+            fs.append(file, "two\n");
+            fs.read(file)"#;
+        assert_eq!(dir.eval(source), Ok("one\ntwo\n".to_string()));
+        assert_eq!(
+            fs::read_to_string(dir.0.join("notes.txt")).unwrap(),
+            "one\ntwo\n"
+        );
+    }
+
+    #[test]
+    fn exists_lists_and_removes_entries() {
+        let dir = TempDir::new("exists");
+        let source = r#"
+            boof sub = path.join(dir, "sub", "deeper");
+            fs.mkdir(sub);
+            fs.write(path.join(dir, "b.txt"), "");
+            boof before = [fs.exists(sub), fs.list_dir(dir)];
+            fs.remove(path.join(dir, "sub"), true);
+            [before, fs.exists(sub), fs.exists(path.join(dir, "b.txt"))]"#;
+        assert_eq!(
+            dir.eval(source),
+            Ok(r#"[[true, ["b.txt", "sub"]], false, true]"#.to_string())
+        );
+    }
+
+    #[test]
+    fn reading_a_missing_file_is_an_io_error() {
+        let dir = TempDir::new("missing");
+        let missing = dir.0.join("missing.txt");
+        let (kind, message) = dir
+            .eval(r#"fs.read(path.join(dir, "missing.txt"))"#)
+            .unwrap_err();
+        assert_eq!(kind, ErrorKind::Io);
+        assert!(
+            message.starts_with(&format!("Failed to read file {missing:?}: ")),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn directories_are_not_read_or_removed_as_files() {
+        let dir = TempDir::new("directory");
+        let (kind, message) = dir.eval("fs.read(dir)").unwrap_err();
+        assert_eq!(kind, ErrorKind::Io);
+        assert!(message.starts_with("Failed to read file"), "{message}");
+        let (kind, message) = dir
+            .eval(r#"fs.write(path.join(dir, "a"), ""); fs.remove(dir)"#)
+            .unwrap_err();
+        assert_eq!(kind, ErrorKind::Io);
+        assert!(message.starts_with("Failed to remove"), "{message}");
+    }
+
+    #[test]
+    fn path_functions_split_and_join() {
+        let dir = TempDir::new("path");
+        assert_eq!(
+            dir.eval(r#"path.join("a", "b", "c.txt")"#),
+            Ok("a/b/c.txt".to_string())
+        );
+        assert_eq!(
+            dir.eval(r#"path.join("a", "/root")"#),
+            Ok("/root".to_string())
+        );
+        assert_eq!(
+            dir.eval(r#"[path.basename("a/b.tar.gz"), path.dirname("a/b.tar.gz"), path.extension("a/b.tar.gz")]"#),
+            Ok(r#"["b.tar.gz", "a", "gz"]"#.to_string())
+        );
+        assert_eq!(
+            dir.eval(r#"[path.basename("/"), path.dirname("/"), path.extension("a/b")]"#),
+            Ok("[nil, nil, nil]".to_string())
+        );
+        assert_eq!(
+            dir.eval(r#"path.join("a", 1)"#).unwrap_err().1,
+            "Argument 2 to path.join(): expected string, found number"
+        );
+    }
+}
@@ -0,0 +1,464 @@
+//! The `json` module. Objects become maps, arrays become lists and `null`
+//! becomes `nil`. Parse errors report the line and column of the problem.
+
+use std::collections::BTreeMap;
+
+use crate::{error::RuntimeError, expr::LiteralValue, interpreter::Interpreter};
+
+use super::Module;
+
+/// How deeply arrays and objects may nest before `json.parse` gives up,
+/// which keeps hostile input from overflowing the stack.
+const MAX_DEPTH: usize = 512;
+
+pub fn register(interpreter: &mut Interpreter) {
+    Module::new("json")
+        .function("parse", |text: String| JsonParser::new(&text).parse())
+        .function("stringify", stringify)
+        .register(interpreter);
+}
+
+struct JsonParser {
+    chars: Vec<char>,
+    current: usize,
+    line: usize,
+    column: usize,
+    /// How many arrays and objects enclose the current position.
+    depth: usize,
+}
+
+impl JsonParser {
+    fn new(text: &str) -> Self {
+        JsonParser {
+            chars: text.chars().collect(),
+            current: 0,
+            line: 1,
+            column: 1,
+            depth: 0,
+        }
+    }
+
+    fn parse(mut self) -> Result<LiteralValue, RuntimeError> {
+        let value = self.value()?;
+        self.skip_whitespace();
+        if let Some(c) = self.peek() {
+            return Err(self.error(format!("unexpected '{c}' after JSON value")));
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<LiteralValue, RuntimeError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c @ ('{' | '[')) => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error(format!("nested deeper than {MAX_DEPTH} levels")));
+                }
+                self.depth += 1;
+                let value = if c == '{' {
+                    self.object()
+                } else {
+                    self.array()
+                };
+                self.depth -= 1;
+                value
+            }
+            Some('"') => self.string().map(LiteralValue::String),
+            Some('t') => self.keyword("true", LiteralValue::True),
+            Some('f') => self.keyword("false", LiteralValue::False),
+            Some('n') => self.keyword("null", LiteralValue::Nil),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) => Err(self.error(format!("unexpected '{c}'"))),
+            None => Err(self.error(String::from("unexpected end of input"))),
+        }
+    }
+
+    fn object(&mut self) -> Result<LiteralValue, RuntimeError> {
+        self.next();
+        let mut entries = BTreeMap::new();
+        self.skip_whitespace();
+        if self.match_char('}') {
+            return Ok(LiteralValue::Map(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.expected("string key"));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            if !self.match_char(':') {
+                return Err(self.expected("':'"));
+            }
+            entries.insert(key, self.value()?);
+            self.skip_whitespace();
+            if self.match_char('}') {
+                return Ok(LiteralValue::Map(entries));
+            }
+            if !self.match_char(',') {
+                return Err(self.expected("',' or '}'"));
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<LiteralValue, RuntimeError> {
+        self.next();
+        let mut items = vec![];
+        self.skip_whitespace();
+        if self.match_char(']') {
+            return Ok(LiteralValue::List(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            if self.match_char(']') {
+                return Ok(LiteralValue::List(items));
+            }
+            if !self.match_char(',') {
+                return Err(self.expected("',' or ']'"));
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, RuntimeError> {
+        self.next();
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(value),
+                Some('\\') => value.push(self.escape()?),
+                Some(c) if (c as u32) < 0x20 => {
+                    return Err(self.error(String::from("control character in string")))
+                }
+                Some(c) => value.push(c),
+                None => return Err(self.error(String::from("unterminated string"))),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, RuntimeError> {
+        match self.next() {
+            Some('"') => Ok('"'),
+            Some('\\') => Ok('\\'),
+            Some('/') => Ok('/'),
+            Some('b') => Ok('\u{8}'),
+            Some('f') => Ok('\u{c}'),
+            Some('n') => Ok('\n'),
+            Some('r') => Ok('\r'),
+            Some('t') => Ok('\t'),
+            Some('u') => {
+                let high = self.hex_escape()?;
+                if !(0xd800..0xdc00).contains(&high) {
+                    return char::from_u32(high)
+                        .ok_or_else(|| self.error(String::from("invalid unicode escape")));
+                }
+                if !(self.match_char('\\') && self.match_char('u')) {
+                    return Err(self.error(String::from("unpaired surrogate in unicode escape")));
+                }
+                let low = self.hex_escape()?;
+                if !(0xdc00..0xe000).contains(&low) {
+                    return Err(self.error(String::from("unpaired surrogate in unicode escape")));
+                }
+                let code = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+                char::from_u32(code)
+                    .ok_or_else(|| self.error(String::from("invalid unicode escape")))
+            }
+            Some(c) => Err(self.error(format!("invalid escape '\\{c}'"))),
+            None => Err(self.error(String::from("unterminated string"))),
+        }
+    }
+
+    fn hex_escape(&mut self) -> Result<u32, RuntimeError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self
+                .next()
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| self.error(String::from("expected 4 hex digits after '\\u'")))?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn number(&mut self) -> Result<LiteralValue, RuntimeError> {
+        let start = self.current;
+        self.match_char('-');
+        if !self.match_char('0') && !self.digits() {
+            return Err(self.expected("digit"));
+        }
+        if self.match_char('.') && !self.digits() {
+            return Err(self.expected("digit after '.'"));
+        }
+        if self.match_char('e') || self.match_char('E') {
+            if !self.match_char('+') {
+                self.match_char('-');
+            }
+            if !self.digits() {
+                return Err(self.expected("digit in exponent"));
+            }
+        }
+        let text: String = self.chars[start..self.current].iter().collect();
+        match text.parse::<f64>() {
+            Ok(n) if n.is_finite() => Ok(LiteralValue::Number(n)),
+            Ok(_) => Err(self.error(format!("number '{text}' is out of range"))),
+            Err(_) => Err(self.error(format!("invalid number '{text}'"))),
+        }
+    }
+
+    /// Consumes a run of digits, returning whether there was at least one.
+    fn digits(&mut self) -> bool {
+        let start = self.current;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.next();
+        }
+        self.current > start
+    }
+
+    fn keyword(&mut self, word: &str, value: LiteralValue) -> Result<LiteralValue, RuntimeError> {
+        for expected in word.chars() {
+            if !self.match_char(expected) {
+                return Err(self.expected(&format!("'{word}'")));
+            }
+        }
+        Ok(value)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.next();
+        }
+    }
+
+    fn match_char(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.next();
+            return true;
+        }
+        false
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.current).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.current += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn expected(&self, what: &str) -> RuntimeError {
+        match self.peek() {
+            Some(c) => self.error(format!("expected {what}, found '{c}'")),
+            None => self.error(format!("expected {what}, found end of input")),
+        }
+    }
+
+    fn error(&self, message: String) -> RuntimeError {
+        RuntimeError::native(format!(
+            "json.parse(): {message} at line {}, column {}",
+            self.line, self.column
+        ))
+    }
+}
+
+/// Serializes a value as JSON, pretty printed when `indent` is given.
+fn stringify(value: LiteralValue, indent: Option<usize>) -> Result<String, RuntimeError> {
+    let mut out = String::new();
+    write_value(&mut out, &value, indent, 0)?;
+    Ok(out)
+}
+
+fn write_value(
+    out: &mut String,
+    value: &LiteralValue,
+    indent: Option<usize>,
+    depth: usize,
+) -> Result<(), RuntimeError> {
+    match value {
+        LiteralValue::Nil => out.push_str("null"),
+        LiteralValue::True => out.push_str("true"),
+        LiteralValue::False => out.push_str("false"),
+        LiteralValue::Number(n) if n.is_finite() => out.push_str(&n.to_string()),
+        LiteralValue::Number(n) => {
+            return Err(RuntimeError::native(format!(
+                "json.stringify(): {n} has no JSON representation"
+            )))
+        }
+        LiteralValue::String(s) => write_string(out, s),
+        LiteralValue::List(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_newline(out, indent, depth + 1);
+                write_value(out, item, indent, depth + 1)?;
+            }
+            if !items.is_empty() {
+                write_newline(out, indent, depth);
+            }
+            out.push(']');
+        }
+        LiteralValue::Map(entries) => {
+            out.push('{');
+            for (i, (key, item)) in entries.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_newline(out, indent, depth + 1);
+                write_string(out, key);
+                out.push(':');
+                if indent.is_some() {
+                    out.push(' ');
+                }
+                write_value(out, item, indent, depth + 1)?;
+            }
+            if !entries.is_empty() {
+                write_newline(out, indent, depth);
+            }
+            out.push('}');
+        }
+        LiteralValue::Function(_) | LiteralValue::NativeFunction(_) | LiteralValue::Regex(_) => {
+            return Err(RuntimeError::native(format!(
+                "json.stringify(): {value} has no JSON representation"
+            )))
+        }
+    }
+    Ok(())
+}
+
+fn write_newline(out: &mut String, indent: Option<usize>, depth: usize) {
+    if let Some(indent) = indent {
+        out.push('\n');
+        out.push_str(&" ".repeat(indent * depth));
+    }
+}
+
+/// Appends `s` as a quoted JSON string.
+pub(crate) fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<LiteralValue, String> {
+        JsonParser::new(text).parse().map_err(|e| e.message)
+    }
+
+    fn round_trip(text: &str) -> String {
+        stringify(parse(text).unwrap(), None).unwrap()
+    }
+
+    #[test]
+    fn round_trips() {
+        for text in [
+            "null",
+            "true",
+            "[]",
+            "{}",
+            "-1.5",
+            r#"[1,"two",[false,null],{"a":{}}]"#,
+            r#"{"a":1,"b":[2,3]}"#,
+        ] {
+            assert_eq!(round_trip(text), text);
+        }
+        assert_eq!(round_trip(" [ 1 , 2e2 ,\n 0.5E-1 ] "), "[1,200,0.05]");
+    }
+
+    #[test]
+    fn pretty_prints() {
+        let value = parse(r#"{"a":[1,2]}"#).unwrap();
+        assert_eq!(
+            stringify(value, Some(2)).unwrap(),
+            "{\n  \"a\": [\n    1,\n    2\n  ]\n}"
+        );
+    }
+
+    #[test]
+    fn escapes() {
+        let value = parse(r#""quote \" slash \/ \\ \b\f\n\r\t \u00e9""#).unwrap();
+        assert_eq!(
+            value,
+            LiteralValue::String("quote \" slash / \\ \u{8}\u{c}\n\r\t é".to_string())
+        );
+        assert_eq!(
+            stringify(LiteralValue::String("a\"\\\n\u{1}".to_string()), None).unwrap(),
+            r#""a\"\\\n\u0001""#
+        );
+    }
+
+    #[test]
+    fn surrogate_pairs() {
+        assert_eq!(
+            parse(r#""\ud83d\ude00""#).unwrap(),
+            LiteralValue::String("😀".to_string())
+        );
+        for text in [r#""\ud83d""#, r#""\ud83dx""#, r#""\ud83d\u0041""#] {
+            let error = parse(text).unwrap_err();
+            assert!(error.contains("unpaired surrogate"), "{text}: {error}");
+        }
+    }
+
+    #[test]
+    fn malformed_input() {
+        for (text, message) in [
+            ("", "unexpected end of input"),
+            ("[1,]", "unexpected ']'"),
+            ("[1 2]", "expected ',' or ']'"),
+            (r#"{"a" 1}"#, "expected ':'"),
+            ("{a: 1}", "expected string key"),
+            (r#""abc"#, "unterminated string"),
+            (r#""\q""#, "invalid escape"),
+            ("01", "unexpected '1' after JSON value"),
+            ("1.", "expected digit after '.'"),
+            ("tru", "expected 'true'"),
+            ("\"a\nb\"", "control character"),
+        ] {
+            let error = parse(text).unwrap_err();
+            assert!(error.contains(message), "{text:?}: {error}");
+        }
+        assert!(parse("[\n  x]")
+            .unwrap_err()
+            .ends_with("at line 2, column 3"));
+    }
+
+    #[test]
+    fn rejects_numbers_out_of_range() {
+        assert!(parse("1e400").unwrap_err().contains("out of range"));
+        assert!(parse("-1e400").unwrap_err().contains("out of range"));
+        assert_eq!(parse("1e-400").unwrap(), LiteralValue::Number(0.0));
+    }
+
+    #[test]
+    fn limits_nesting() {
+        let nested = |depth| "[".repeat(depth) + &"]".repeat(depth);
+        assert!(parse(&nested(MAX_DEPTH)).is_ok());
+        let error = parse(&nested(MAX_DEPTH + 1)).unwrap_err();
+        assert!(error.contains("nested deeper than 512 levels"), "{error}");
+        let error = parse(&"{\"a\":".repeat(100_000)).unwrap_err();
+        assert!(error.contains("nested deeper"), "{error}");
+    }
+}
@@ -0,0 +1,169 @@
+//! The `math` module.
+
+use std::f64::consts;
+
+use crate::{
+    convert::argument,
+    error::{ErrorKind, RuntimeError},
+    expr::LiteralValue,
+    function::Arity,
+    interpreter::Interpreter,
+};
+
+use super::Module;
+
+pub fn register(interpreter: &mut Interpreter) {
+    Module::new("math")
+        .constant("pi", consts::PI)
+        .constant("e", consts::E)
+        .constant("inf", f64::INFINITY)
+        .constant("nan", f64::NAN)
+        .function("floor", f64::floor)
+        .function("ceil", f64::ceil)
+        .function("round", round)
+        .function("abs", f64::abs)
+        .function("sqrt", f64::sqrt)
+        .function("pow", f64::powf)
+        .function("sin", f64::sin)
+        .function("cos", f64::cos)
+        .function("tan", f64::tan)
+        .function("log", |x: f64, base: Option<f64>| match base {
+            Some(base) => x.log(base),
+            None => x.ln(),
+        })
+        .function("exp", f64::exp)
+        .function("is_nan", f64::is_nan)
+        .function("gcd", gcd)
+        .function("lcm", lcm)
+        .native("min", Arity::Variadic, |_, args| {
+            extreme("math.min", args, f64::min)
+        })
+        .native("max", Arity::Variadic, |_, args| {
+            extreme("math.max", args, f64::max)
+        })
+        .register(interpreter);
+}
+
+/// Rounds half away from zero, optionally to a number of decimal places.
+fn round(x: f64, digits: Option<i32>) -> f64 {
+    match digits {
+        Some(digits) => {
+            let scale = 10f64.powi(digits);
+            (x * scale).round() / scale
+        }
+        None => x.round(),
+    }
+}
+
+fn gcd(a: i64, b: i64) -> Result<i64, RuntimeError> {
+    fits(
+        "math.gcd",
+        u128::from(unsigned_gcd(a.unsigned_abs(), b.unsigned_abs())),
+    )
+}
+
+fn lcm(a: i64, b: i64) -> Result<i64, RuntimeError> {
+    let (a, b) = (a.unsigned_abs(), b.unsigned_abs());
+    if a == 0 || b == 0 {
+        return Ok(0);
+    }
+    fits(
+        "math.lcm",
+        u128::from(a / unsigned_gcd(a, b)) * u128::from(b),
+    )
+}
+
+fn unsigned_gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Converts an integer result back to `i64`, failing if it is too large,
+/// as the gcd of `i64::MIN` and 0 would be.
+fn fits(name: &str, n: u128) -> Result<i64, RuntimeError> {
+    i64::try_from(n).map_err(|_| {
+        RuntimeError::native(format!("{name}() result {n} is too large"))
+            .with_kind(ErrorKind::Value)
+    })
+}
+
+/// Folds numbers given either as arguments or as a single list.
+fn extreme(
+    name: &str,
+    args: &[LiteralValue],
+    pick: fn(f64, f64) -> f64,
+) -> Result<LiteralValue, RuntimeError> {
+    let numbers: Vec<f64> = match args {
+        [LiteralValue::List(_)] => argument(name, args, 0)?,
+        _ => (0..args.len())
+            .map(|i| argument(name, args, i))
+            .collect::<Result<_, _>>()?,
+    };
+    numbers
+        .into_iter()
+        .reduce(pick)
+        .map(LiteralValue::Number)
+        .ok_or_else(|| RuntimeError::native(format!("{name}() needs at least one number")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Engine;
+
+    #[test]
+    fn gcd_of_signs_and_zeros() {
+        assert_eq!(gcd(12, 18).unwrap(), 6);
+        assert_eq!(gcd(-12, 18).unwrap(), 6);
+        assert_eq!(gcd(-12, -18).unwrap(), 6);
+        assert_eq!(gcd(0, 5).unwrap(), 5);
+        assert_eq!(gcd(0, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn gcd_of_extreme_values() {
+        assert_eq!(gcd(i64::MAX, i64::MAX).unwrap(), i64::MAX);
+        assert_eq!(gcd(i64::MIN, 6).unwrap(), 2);
+        assert_eq!(gcd(i64::MIN, i64::MAX).unwrap(), 1);
+        let err = gcd(i64::MIN, 0).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Value);
+        assert_eq!(
+            err.message,
+            "math.gcd() result 9223372036854775808 is too large"
+        );
+        assert!(gcd(i64::MIN, i64::MIN).is_err());
+    }
+
+    #[test]
+    fn lcm_of_signs_and_zeros() {
+        assert_eq!(lcm(4, 6).unwrap(), 12);
+        assert_eq!(lcm(-4, 6).unwrap(), 12);
+        assert_eq!(lcm(0, 6).unwrap(), 0);
+        assert_eq!(lcm(i64::MIN, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn lcm_of_large_values() {
+        assert_eq!(lcm(1 << 40, 1 << 50).unwrap(), 1 << 50);
+        assert_eq!(lcm(4294967296, 2147483647).unwrap(), 9223372032559808512);
+        assert_eq!(
+            lcm(i64::MAX, i64::MAX - 1).unwrap_err().message,
+            "math.lcm() result 85070591730234615838173535747377725442 is too large"
+        );
+        assert!(lcm(i64::MIN, 3).is_err());
+    }
+
+    #[test]
+    fn gcd_and_lcm_reject_fractions() {
+        for source in ["math.gcd(1.5, 3)", "math.lcm(4, 0.5)"] {
+            let err = Engine::new().eval(source).unwrap_err();
+            let message = &err.diagnostic().unwrap().message;
+            assert!(
+                message.contains("expected integer in range of i64"),
+                "{message}"
+            );
+        }
+    }
+}
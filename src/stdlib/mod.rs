@@ -0,0 +1,123 @@
+mod collections;
+mod core;
+mod format;
+mod fs;
+mod json;
+mod math;
+mod process;
+mod random;
+mod re;
+mod string;
+mod time;
+
+pub(crate) use json::write_string as write_json_string;
+pub use random::Rng;
+
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use crate::{
+    convert::{IntoNative, ToBoof},
+    error::RuntimeError,
+    expr::LiteralValue,
+    function::{Arity, NativeFunction},
+    interpreter::Interpreter,
+};
+
+/// Defines the built-in boofers available to every script.
+pub fn register(interpreter: &mut Interpreter) {
+    core::register(interpreter);
+    collections::register(interpreter);
+    format::register(interpreter);
+    fs::register(interpreter);
+    json::register(interpreter);
+    math::register(interpreter);
+    process::register(interpreter);
+    random::register(interpreter);
+    re::register(interpreter);
+    string::register(interpreter);
+    time::register(interpreter);
+}
+
+/// A namespace of boofers and constants, exposed to scripts as a global map
+/// such as `string` or `math`.
+struct Module {
+    name: &'static str,
+    entries: BTreeMap<String, LiteralValue>,
+}
+
+impl Module {
+    fn new(name: &'static str) -> Self {
+        Module {
+            name,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    fn function<F, Args>(mut self, name: &str, function: F) -> Self
+    where
+        F: IntoNative<Args>,
+    {
+        let qualified = format!("{}.{name}", self.name);
+        let native = NativeFunction {
+            arity: F::arity(),
+            function: function.into_native(&qualified),
+            name: qualified,
+        };
+        self.entries.insert(
+            name.to_string(),
+            LiteralValue::NativeFunction(Rc::new(native)),
+        );
+        self
+    }
+
+    /// Adds a boofer that takes its arguments unconverted.
+    fn native<F>(mut self, name: &str, arity: Arity, function: F) -> Self
+    where
+        F: Fn(&mut Interpreter, &[LiteralValue]) -> Result<LiteralValue, RuntimeError> + 'static,
+    {
+        let native = NativeFunction {
+            name: format!("{}.{name}", self.name),
+            arity,
+            function: Box::new(function),
+        };
+        self.entries.insert(
+            name.to_string(),
+            LiteralValue::NativeFunction(Rc::new(native)),
+        );
+        self
+    }
+
+    fn constant<T: ToBoof>(mut self, name: &str, value: T) -> Self {
+        self.entries.insert(name.to_string(), value.to_boof());
+        self
+    }
+
+    fn register(self, interpreter: &mut Interpreter) {
+        interpreter.define_global(self.name, LiteralValue::Map(self.entries));
+    }
+
+    /// Registers the module and also makes its boofers methods of `type_name`.
+    fn register_methods(self, interpreter: &mut Interpreter, type_name: &str) {
+        interpreter.define_methods(type_name, self.entries.clone());
+        self.register(interpreter);
+    }
+
+    /// Registers the module and makes only the boofers in `names` methods of
+    /// `type_name`.
+    fn register_methods_named(
+        self,
+        interpreter: &mut Interpreter,
+        type_name: &str,
+        names: &[&str],
+    ) {
+        let methods = self
+            .entries
+            .iter()
+            .filter(|(name, _)| names.contains(&name.as_str()))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        interpreter.define_methods(type_name, methods);
+        self.register(interpreter);
+    }
+}
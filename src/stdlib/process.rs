@@ -0,0 +1,86 @@
+//! Script arguments, environment variables, standard input, `eprint` and
+//! `exit`.
+//!
+//! `env()` without a name returns every variable as a map, and `exit` ends
+//! the script by unwinding to the host rather than ending the process.
+//! Names and values that aren't valid UTF-8 have the invalid bytes replaced
+//! with U+FFFD.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsString;
+use std::io::{self, Read, Write};
+
+use crate::{
+    convert::ToBoof,
+    error::{ErrorKind, RuntimeError},
+    expr::LiteralValue,
+    function::Arity,
+    interpreter::Interpreter,
+};
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_native("args", Arity::Fixed(0), |interpreter, _| {
+        Ok(LiteralValue::List(
+            interpreter
+                .script_args()
+                .iter()
+                .cloned()
+                .map(LiteralValue::String)
+                .collect(),
+        ))
+    });
+    interpreter.register("env", |name: Option<String>| match name {
+        Some(name) => env::var_os(name).map(lossy).to_boof(),
+        None => env::vars_os()
+            .map(|(name, value)| (lossy(name), lossy(value)))
+            .collect::<BTreeMap<_, _>>()
+            .to_boof(),
+    });
+    interpreter.register("exit", |code: Option<i32>| -> Result<(), RuntimeError> {
+        Err(RuntimeError::exit(code.unwrap_or(0)))
+    });
+    interpreter.register_native("eprint", Arity::Variadic, |_, args| {
+        let values: Vec<String> = args.iter().map(LiteralValue::to_string).collect();
+        eprintln!("{}", values.join(" "));
+        Ok(LiteralValue::Nil)
+    });
+    interpreter.register("input", |prompt: Option<String>| {
+        if let Some(prompt) = prompt {
+            print!("{prompt}");
+            io::stdout().flush().map_err(|err| {
+                RuntimeError::native(format!("Failed to write prompt to standard output: {err}"))
+                    .with_kind(ErrorKind::Io)
+            })?;
+        }
+        read_line()
+    });
+    interpreter.register("read_line", read_line);
+    interpreter.register("read_all_stdin", || {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(stdin_error)?;
+        Ok::<_, RuntimeError>(input)
+    });
+}
+
+fn lossy(s: OsString) -> String {
+    s.to_string_lossy().into_owned()
+}
+
+/// The next line of standard input without its line ending, or `nil` at the
+/// end of input.
+fn read_line() -> Result<Option<String>, RuntimeError> {
+    let mut line = String::new();
+    if io::stdin().read_line(&mut line).map_err(stdin_error)? == 0 {
+        return Ok(None);
+    }
+    let trimmed = line.trim_end_matches(['\n', '\r']).len();
+    line.truncate(trimmed);
+    Ok(Some(line))
+}
+
+fn stdin_error(err: io::Error) -> RuntimeError {
+    RuntimeError::native(format!("Failed to read standard input: {err}")).with_kind(ErrorKind::Io)
+}
@@ -0,0 +1,164 @@
+//! The `random` module, backed by a SplitMix64 generator so a seeded script
+//! produces the same sequence on every run and platform.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    convert::argument, error::RuntimeError, expr::LiteralValue, function::Arity,
+    interpreter::Interpreter,
+};
+
+use super::Module;
+
+pub fn register(interpreter: &mut Interpreter) {
+    Module::new("random")
+        .native("random", Arity::Fixed(0), |interpreter, _| {
+            Ok(LiteralValue::Number(interpreter.rng().next_f64()))
+        })
+        .native("randint", Arity::Fixed(2), randint)
+        .native("choice", Arity::Fixed(1), choice)
+        .native("shuffle", Arity::Fixed(1), shuffle)
+        .native("seed", Arity::Fixed(1), |interpreter, args| {
+            let seed: i64 = argument("random.seed", args, 0)?;
+            interpreter.rng().seed(seed as u64);
+            Ok(LiteralValue::Nil)
+        })
+        .register(interpreter);
+}
+
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// A generator seeded from the system clock.
+    pub fn new() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or_default();
+        Rng { state: nanos }
+    }
+
+    pub fn seed(&mut self, seed: u64) {
+        self.state = seed;
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A float in `[0, 1)` with 53 random bits.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// An unbiased integer in `[0, bound)`.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An integer between `a` and `b` inclusive.
+fn randint(
+    interpreter: &mut Interpreter,
+    args: &[LiteralValue],
+) -> Result<LiteralValue, RuntimeError> {
+    let low: i64 = argument("random.randint", args, 0)?;
+    let high: i64 = argument("random.randint", args, 1)?;
+    if low > high {
+        return Err(RuntimeError::native(format!(
+            "random.randint() range is empty: {low} > {high}"
+        )));
+    }
+    let span = high.abs_diff(low).wrapping_add(1);
+    let offset = if span == 0 {
+        interpreter.rng().next_u64()
+    } else {
+        interpreter.rng().below(span)
+    };
+    Ok(LiteralValue::Number(low.wrapping_add(offset as i64) as f64))
+}
+
+fn choice(
+    interpreter: &mut Interpreter,
+    args: &[LiteralValue],
+) -> Result<LiteralValue, RuntimeError> {
+    let items: Vec<LiteralValue> = argument("random.choice", args, 0)?;
+    if items.is_empty() {
+        return Err(RuntimeError::native(String::from(
+            "random.choice() needs a non-empty list",
+        )));
+    }
+    let index = interpreter.rng().below(items.len() as u64) as usize;
+    Ok(items[index].clone())
+}
+
+/// A shuffled copy of the list.
+fn shuffle(
+    interpreter: &mut Interpreter,
+    args: &[LiteralValue],
+) -> Result<LiteralValue, RuntimeError> {
+    let mut items: Vec<LiteralValue> = argument("random.shuffle", args, 0)?;
+    for i in (1..items.len()).rev() {
+        let j = interpreter.rng().below(i as u64 + 1) as usize;
+        items.swap(i, j);
+    }
+    Ok(LiteralValue::List(items))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_the_reference_splitmix64_sequence() {
+        let mut rng = Rng::new();
+        rng.seed(1234567);
+        let values: Vec<u64> = (0..5).map(|_| rng.next_u64()).collect();
+        assert_eq!(
+            values,
+            [
+                6457827717110365317,
+                3203168211198807973,
+                9817491932198370423,
+                4593380528125082431,
+                16408922859458223821,
+            ]
+        );
+    }
+
+    #[test]
+    fn floats_use_the_top_53_bits() {
+        let mut rng = Rng::new();
+        rng.seed(1234567);
+        assert_eq!(
+            rng.next_f64(),
+            (6457827717110365317u64 >> 11) as f64 / 2f64.powi(53)
+        );
+    }
+
+    #[test]
+    fn below_stays_in_range() {
+        let mut rng = Rng::new();
+        rng.seed(7);
+        assert!((0..1000).all(|_| rng.below(3) < 3));
+        assert_eq!(rng.below(1), 0);
+    }
+}
@@ -0,0 +1,219 @@
+//! The `re` module, also available as methods on regex values.
+//!
+//! Patterns may be regex literals like `/(\d+)-(\d+)/i` or strings compiled
+//! without flags. A match is a map with the matched `text`, its `start` and
+//! `end` char positions, the capture `groups` as a list and any `named`
+//! groups as a map; groups that did not take part are `nil`.
+
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use crate::{
+    convert::argument,
+    error::{ErrorKind, RuntimeError},
+    expr::LiteralValue,
+    function::Arity,
+    interpreter::Interpreter,
+    regex::{Captures, Regex},
+};
+
+use super::Module;
+
+const REGEX_METHODS: &[&str] = &["test", "match", "find_all", "replace", "split"];
+
+pub fn register(interpreter: &mut Interpreter) {
+    Module::new("re")
+        .function(
+            "compile",
+            |pattern: String, flags: Option<String>| -> Result<Rc<Regex>, RuntimeError> {
+                Regex::new(&pattern, flags.as_deref().unwrap_or(""))
+                    .map(Rc::new)
+                    .map_err(|err| RuntimeError::native(format!("re.compile(): {err}")))
+            },
+        )
+        .function("escape", escape)
+        .function("test", |regex: Rc<Regex>, text: String| {
+            regex.is_match(&text)
+        })
+        .function("match", |regex: Rc<Regex>, text: String| {
+            let chars: Vec<char> = text.chars().collect();
+            regex
+                .captures_at(&chars, 0)
+                .map(|captures| match_value(&regex, &chars, &captures))
+        })
+        .function("find_all", |regex: Rc<Regex>, text: String| {
+            let chars: Vec<char> = text.chars().collect();
+            regex
+                .captures_all(&chars)
+                .iter()
+                .map(|captures| match_value(&regex, &chars, captures))
+                .collect::<Vec<_>>()
+        })
+        .native("replace", Arity::Range(3, 4), replace)
+        .function(
+            "split",
+            |regex: Rc<Regex>, text: String, limit: Option<usize>| {
+                let chars: Vec<char> = text.chars().collect();
+                let mut parts = vec![];
+                let mut last = 0;
+                for captures in regex.captures_all(&chars) {
+                    if limit.is_some_and(|limit| parts.len() + 1 >= limit) {
+                        break;
+                    }
+                    let (start, end) = captures[0].unwrap_or_default();
+                    if end == 0 || start == chars.len() {
+                        continue;
+                    }
+                    parts.push(slice(&chars, last, start));
+                    last = end;
+                }
+                parts.push(slice(&chars, last, chars.len()));
+                parts
+            },
+        )
+        .register_methods_named(interpreter, "regex", REGEX_METHODS);
+}
+
+/// Escapes every character that has a meaning in a pattern.
+fn escape(text: String) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\^$.|?*+()[]{}/-".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// `re.replace(pattern, text, replacement, count?)` replaces every match, or
+/// the first `count`. The replacement is either a string, where `$1` and
+/// `${name}` insert groups, `$0` the whole match and `$$` a dollar sign, or
+/// a boofer given each match and returning the text to insert.
+fn replace(
+    interpreter: &mut Interpreter,
+    args: &[LiteralValue],
+) -> Result<LiteralValue, RuntimeError> {
+    let regex: Rc<Regex> = argument("re.replace", args, 0)?;
+    let text: String = argument("re.replace", args, 1)?;
+    let count: Option<usize> = argument("re.replace", args, 3)?;
+    let chars: Vec<char> = text.chars().collect();
+
+    let mut out = String::new();
+    let mut last = 0;
+    let matches = regex.captures_all(&chars);
+    for captures in matches.iter().take(count.unwrap_or(usize::MAX)) {
+        let (start, end) = captures[0].unwrap_or_default();
+        out.push_str(&slice(&chars, last, start));
+        match &args[2] {
+            LiteralValue::String(template) => expand(&mut out, template, &regex, &chars, captures)?,
+            callback @ (LiteralValue::Function(_) | LiteralValue::NativeFunction(_)) => {
+                let value = match_value(&regex, &chars, captures);
+                let replacement = interpreter.call_value(callback, vec![value])?;
+                out.push_str(&replacement.to_string());
+            }
+            value => {
+                return Err(RuntimeError::native(format!(
+                    "Argument 3 to re.replace(): expected string or boofer, found {}",
+                    value.type_name()
+                ))
+                .with_kind(ErrorKind::Type))
+            }
+        }
+        last = end;
+    }
+    out.push_str(&slice(&chars, last, chars.len()));
+    Ok(LiteralValue::String(out))
+}
+
+/// Appends a replacement template with its group references filled in.
+fn expand(
+    out: &mut String,
+    template: &str,
+    regex: &Regex,
+    text: &[char],
+    captures: &Captures,
+) -> Result<(), RuntimeError> {
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            out.push(c);
+            continue;
+        }
+        let index = match chars.peek() {
+            Some('$') => {
+                chars.next();
+                out.push('$');
+                continue;
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let mut digits = String::new();
+                while let Some(c) = chars.next_if(char::is_ascii_digit) {
+                    digits.push(c);
+                }
+                digits.parse().unwrap_or(usize::MAX)
+            }
+            Some('{') => {
+                chars.next();
+                let name: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                match name.parse() {
+                    Ok(index) => index,
+                    Err(_) => regex
+                        .group_names()
+                        .iter()
+                        .position(|group| group.as_deref() == Some(name.as_str()))
+                        .ok_or_else(|| {
+                            RuntimeError::native(format!(
+                                "re.replace(): no group named '{name}' in {regex}"
+                            ))
+                        })?,
+                }
+            }
+            _ => {
+                out.push('$');
+                continue;
+            }
+        };
+        match captures.get(index) {
+            Some(group) => {
+                if let Some((start, end)) = group {
+                    out.push_str(&slice(text, *start, *end));
+                }
+            }
+            None => {
+                return Err(RuntimeError::native(format!(
+                    "re.replace(): no group {index} in {regex}"
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+fn match_value(regex: &Regex, text: &[char], captures: &Captures) -> LiteralValue {
+    let group = |span: &Option<(usize, usize)>| match span {
+        Some((start, end)) => LiteralValue::String(slice(text, *start, *end)),
+        None => LiteralValue::Nil,
+    };
+    let (start, end) = captures[0].unwrap_or_default();
+    let named = regex
+        .group_names()
+        .iter()
+        .zip(captures)
+        .filter_map(|(name, span)| Some((name.clone()?, group(span))))
+        .collect();
+    LiteralValue::Map(BTreeMap::from([
+        ("text".to_string(), group(&captures[0])),
+        ("start".to_string(), LiteralValue::Number(start as f64)),
+        ("end".to_string(), LiteralValue::Number(end as f64)),
+        (
+            "groups".to_string(),
+            LiteralValue::List(captures[1..].iter().map(group).collect()),
+        ),
+        ("named".to_string(), LiteralValue::Map(named)),
+    ]))
+}
+
+fn slice(text: &[char], start: usize, end: usize) -> String {
+    text[start..end].iter().collect()
+}
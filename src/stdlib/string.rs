@@ -0,0 +1,212 @@
+//! The `string` module. Its boofers are also methods of strings, so
+//! `string.upper(s)` and `s.upper()` are equivalent. Positions and lengths
+//! count characters rather than bytes.
+
+use crate::{
+    error::{ErrorKind, RuntimeError},
+    expr::LiteralValue,
+    interpreter::Interpreter,
+};
+
+use super::Module;
+
+/// The most bytes `repeat` and the padding boofers will build, so a huge
+/// count fails instead of exhausting memory.
+const MAX_LENGTH: usize = 1 << 24;
+
+pub fn register(interpreter: &mut Interpreter) {
+    Module::new("string")
+        .function("len", |s: String| s.chars().count())
+        .function("upper", |s: String| s.to_uppercase())
+        .function("lower", |s: String| s.to_lowercase())
+        .function("trim", |s: String| s.trim().to_string())
+        .function("split", split)
+        .function("join", join)
+        .function("replace", |s: String, from: String, to: String| {
+            s.replace(&from, &to)
+        })
+        .function("contains", |s: String, part: String| s.contains(&part))
+        .function("starts_with", |s: String, prefix: String| {
+            s.starts_with(&prefix)
+        })
+        .function("ends_with", |s: String, suffix: String| {
+            s.ends_with(&suffix)
+        })
+        .function("find", find)
+        .function("substr", substr)
+        .function("chars", |s: String| {
+            s.chars().map(String::from).collect::<Vec<_>>()
+        })
+        .function("repeat", repeat)
+        .function(
+            "pad_left",
+            |s: String, width: usize, fill: Option<String>| pad(s, width, fill, true),
+        )
+        .function(
+            "pad_right",
+            |s: String, width: usize, fill: Option<String>| pad(s, width, fill, false),
+        )
+        .register_methods(interpreter, "string");
+}
+
+/// Splits on `separator`, on whitespace when it is omitted, or into
+/// characters when it is empty.
+fn split(s: String, separator: Option<String>) -> Vec<String> {
+    match separator.as_deref() {
+        None => s.split_whitespace().map(String::from).collect(),
+        Some("") => s.chars().map(String::from).collect(),
+        Some(separator) => s.split(separator).map(String::from).collect(),
+    }
+}
+
+/// `separator.join(items)`, converting non-string items as `str` does.
+fn join(separator: String, items: Vec<LiteralValue>) -> String {
+    items
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join(&separator)
+}
+
+/// Character index of the first occurrence of `part`, or `nil`.
+fn find(s: String, part: String) -> Option<usize> {
+    s.find(&part).map(|byte| s[..byte].chars().count())
+}
+
+/// Up to `length` characters starting at `start`, where a negative `start`
+/// counts back from the end of the string.
+fn substr(s: String, start: i64, length: Option<usize>) -> String {
+    let count = s.chars().count() as i64;
+    let start = if start < 0 { count + start } else { start };
+    let start = start.clamp(0, count) as usize;
+    let chars = s.chars().skip(start);
+    match length {
+        Some(length) => chars.take(length).collect(),
+        None => chars.collect(),
+    }
+}
+
+fn repeat(s: String, count: usize) -> Result<String, RuntimeError> {
+    check_length(s.len().checked_mul(count))?;
+    Ok(s.repeat(count))
+}
+
+fn pad(s: String, width: usize, fill: Option<String>, left: bool) -> Result<String, RuntimeError> {
+    let fill = fill.unwrap_or_else(|| String::from(" "));
+    let mut fill_chars = fill.chars();
+    let (Some(fill), None) = (fill_chars.next(), fill_chars.next()) else {
+        return Err(RuntimeError::native(String::from(
+            "padding must be a single character",
+        )));
+    };
+    let count = width.saturating_sub(s.chars().count());
+    check_length(
+        count
+            .checked_mul(fill.len_utf8())
+            .and_then(|padding| padding.checked_add(s.len())),
+    )?;
+    let padding: String = std::iter::repeat_n(fill, count).collect();
+    if left {
+        Ok(padding + &s)
+    } else {
+        Ok(s + &padding)
+    }
+}
+
+/// Fails unless a string of `length` bytes, `None` if it overflowed, is
+/// within `MAX_LENGTH`.
+fn check_length(length: Option<usize>) -> Result<(), RuntimeError> {
+    match length {
+        Some(length) if length <= MAX_LENGTH => Ok(()),
+        _ => Err(RuntimeError::native(format!(
+            "Resulting string would be longer than {MAX_LENGTH} bytes"
+        ))
+        .with_kind(ErrorKind::Value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Engine;
+
+    fn eval(source: &str) -> Result<String, String> {
+        Engine::new()
+            .eval(source)
+            .map(|value| value.to_string())
+            .map_err(|err| err.diagnostic().unwrap().message.clone())
+    }
+
+    #[test]
+    fn methods_and_module_functions_agree() {
+        assert_eq!(eval(r#""Boof".upper()"#), Ok("BOOF".to_string()));
+        assert_eq!(eval(r#"string.upper("Boof")"#), Ok("BOOF".to_string()));
+        assert_eq!(
+            eval(r#"" x ".trim().pad_left(3, "-")"#),
+            Ok("--x".to_string())
+        );
+        assert_eq!(
+            eval(r#"boof s = "hi"; s.nope()"#).unwrap_err(),
+            "string has no method 'nope'"
+        );
+    }
+
+    #[test]
+    fn positions_count_characters() {
+        assert_eq!(eval(r#""héllo wörld".len()"#), Ok("11".to_string()));
+        assert_eq!(eval(r#""héllo wörld".find("w")"#), Ok("6".to_string()));
+        assert_eq!(eval(r#""日本語".substr(1)"#), Ok("本語".to_string()));
+        assert_eq!(eval(r#""日本語".substr(-1)"#), Ok("語".to_string()));
+        assert_eq!(eval(r#""naïve".substr(1, 3)"#), Ok("aïv".to_string()));
+        assert_eq!(eval(r#""é".pad_left(3)"#), Ok("  é".to_string()));
+        assert_eq!(eval(r#""ab".pad_right(4, "→")"#), Ok("ab→→".to_string()));
+    }
+
+    #[test]
+    fn substr_clamps_indices_past_either_end() {
+        assert_eq!(substr("abc".to_string(), 5, None), "");
+        assert_eq!(substr("abc".to_string(), -10, Some(2)), "ab");
+        assert_eq!(substr("abc".to_string(), 1, Some(100)), "bc");
+        assert_eq!(find("abc".to_string(), "z".to_string()), None);
+        assert_eq!(
+            eval(r#""abc".substr(1.5)"#).unwrap_err(),
+            "Argument 2 to string.substr(): expected integer in range of i64, found 1.5"
+        );
+    }
+
+    #[test]
+    fn padding_takes_one_character() {
+        assert_eq!(pad("ab".to_string(), 1, None, true).unwrap(), "ab");
+        let err = pad("ab".to_string(), 4, Some("--".to_string()), true).unwrap_err();
+        assert_eq!(err.message, "padding must be a single character");
+        assert!(pad("ab".to_string(), 4, Some(String::new()), false).is_err());
+    }
+
+    #[test]
+    fn built_strings_are_capped() {
+        assert_eq!(
+            repeat("ab".to_string(), MAX_LENGTH / 2).unwrap().len(),
+            MAX_LENGTH
+        );
+        let message = format!("Resulting string would be longer than {MAX_LENGTH} bytes");
+        let err = repeat("ab".to_string(), MAX_LENGTH / 2 + 1).unwrap_err();
+        assert_eq!((err.kind, err.message), (ErrorKind::Value, message.clone()));
+        assert_eq!(
+            repeat("ab".to_string(), usize::MAX).unwrap_err().message,
+            message
+        );
+        assert_eq!(
+            pad("é".to_string(), MAX_LENGTH, Some("é".to_string()), true)
+                .unwrap_err()
+                .message,
+            message
+        );
+        assert_eq!(
+            pad(String::new(), usize::MAX, None, false)
+                .unwrap_err()
+                .message,
+            message
+        );
+        assert_eq!(eval(r#""ab".repeat(100000000)"#).unwrap_err(), message);
+    }
+}
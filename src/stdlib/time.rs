@@ -0,0 +1,512 @@
+//! The `time` module. Timestamps are seconds since the Unix epoch, and
+//! dates are computed with the proleptic Gregorian calendar.
+//!
+//! `format` and `parse` take strftime-style formats supporting `%Y %m %d %H
+//! %M %S %f` (milliseconds), `%j`, `%a %A %b %B`, `%z` and `%%`. Both accept
+//! an optional offset such as `"UTC"`, `"+05:30"` or `"-0800"`; the default
+//! is UTC.
+
+use std::ops::RangeInclusive;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::{error::RuntimeError, interpreter::Interpreter};
+
+use super::Module;
+
+/// The largest timestamp `format` accepts in either direction, 100 million
+/// days from the epoch as in JavaScript, which keeps date arithmetic well
+/// inside `i64`.
+const MAX_TIMESTAMP: f64 = 8.64e12;
+
+const DAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+pub fn register(interpreter: &mut Interpreter) {
+    let start = Instant::now();
+    Module::new("time")
+        .function("now", || -> Result<f64, RuntimeError> {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs_f64())
+                .map_err(|e| RuntimeError::native(e.to_string()))
+        })
+        .function("clock", move || start.elapsed().as_secs_f64())
+        .function("sleep", |ms: f64| -> Result<(), RuntimeError> {
+            let duration = Duration::try_from_secs_f64(ms / 1000.0).map_err(|e| {
+                RuntimeError::native(format!("time.sleep() can't wait {ms} milliseconds: {e}"))
+            })?;
+            thread::sleep(duration);
+            Ok(())
+        })
+        .function("format", format)
+        .function("parse", parse)
+        .register(interpreter);
+}
+
+/// A calendar date and time of day at some offset from UTC.
+struct DateTime {
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    millis: u32,
+    offset: i64,
+}
+
+impl DateTime {
+    /// The date at `timestamp` seconds, seen `offset` minutes from UTC, or
+    /// `None` if it is out of range.
+    fn from_timestamp(timestamp: f64, offset: i64) -> Option<Self> {
+        if !(-MAX_TIMESTAMP..=MAX_TIMESTAMP).contains(&timestamp) {
+            return None;
+        }
+        let millis =
+            ((timestamp * 1000.0).round() as i64).checked_add(offset.checked_mul(60_000)?)?;
+        let days = millis.div_euclid(86_400_000);
+        let time = millis.rem_euclid(86_400_000);
+        let (year, month, day) = civil_from_days(days);
+        Some(DateTime {
+            year,
+            month,
+            day,
+            hour: (time / 3_600_000) as u32,
+            minute: (time / 60_000 % 60) as u32,
+            second: (time / 1000 % 60) as u32,
+            millis: (time % 1000) as u32,
+            offset,
+        })
+    }
+
+    fn timestamp(&self) -> f64 {
+        let days = days_from_civil(self.year, self.month, self.day);
+        let seconds = days * 86_400 + i64::from(self.hour * 3600 + self.minute * 60 + self.second)
+            - self.offset * 60;
+        seconds as f64 + f64::from(self.millis) / 1000.0
+    }
+
+    /// Day of the week, counting from Monday as 0.
+    fn weekday(&self) -> usize {
+        (days_from_civil(self.year, self.month, self.day) + 3).rem_euclid(7) as usize
+    }
+
+    fn day_of_year(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) - days_from_civil(self.year, 1, 1) + 1
+    }
+}
+
+/// Converts days since the epoch to a (year, month, day) date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Converts a date to days since the epoch.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = i64::from((month + 9) % 12);
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Parses an offset like `UTC`, `Z`, `+05:30`, `-0800` or `+02` into minutes.
+fn parse_offset(text: &str) -> Option<i64> {
+    if text.eq_ignore_ascii_case("utc") || text == "Z" {
+        return Some(0);
+    }
+    let (sign, rest) = match text.split_at_checked(1)? {
+        ("+", rest) => (1, rest),
+        ("-", rest) => (-1, rest),
+        _ => return None,
+    };
+    let digits: String = rest.chars().filter(|&c| c != ':').collect();
+    if !digits.chars().all(|c| c.is_ascii_digit()) || !matches!(digits.len(), 2 | 4) {
+        return None;
+    }
+    let hours: i64 = digits[..2].parse().ok()?;
+    let minutes: i64 = digits
+        .get(2..)
+        .filter(|m| !m.is_empty())
+        .map_or(Ok(0), str::parse)
+        .ok()?;
+    (hours < 24 && minutes < 60).then_some(sign * (hours * 60 + minutes))
+}
+
+fn offset_argument(function: &str, offset: Option<String>) -> Result<i64, RuntimeError> {
+    match offset {
+        Some(offset) => parse_offset(&offset).ok_or_else(|| {
+            RuntimeError::native(format!(
+                "{function}() expects an offset like \"UTC\" or \"+05:30\", found {offset:?}"
+            ))
+        }),
+        None => Ok(0),
+    }
+}
+
+/// Formats a timestamp, e.g. `time.format(0, "%Y-%m-%d")` gives `1970-01-01`.
+fn format(timestamp: f64, pattern: String, offset: Option<String>) -> Result<String, RuntimeError> {
+    let offset = offset_argument("time.format", offset)?;
+    let date = DateTime::from_timestamp(timestamp, offset).ok_or_else(|| {
+        RuntimeError::native(format!(
+            "time.format() expects a timestamp within {MAX_TIMESTAMP:e} seconds of the epoch, found {timestamp}"
+        ))
+    })?;
+    let mut out = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('Y') => out.push_str(&format!("{:04}", date.year)),
+            Some('m') => out.push_str(&format!("{:02}", date.month)),
+            Some('d') => out.push_str(&format!("{:02}", date.day)),
+            Some('H') => out.push_str(&format!("{:02}", date.hour)),
+            Some('M') => out.push_str(&format!("{:02}", date.minute)),
+            Some('S') => out.push_str(&format!("{:02}", date.second)),
+            Some('f') => out.push_str(&format!("{:03}", date.millis)),
+            Some('j') => out.push_str(&format!("{:03}", date.day_of_year())),
+            Some('a') => out.push_str(&DAYS[date.weekday()][..3]),
+            Some('A') => out.push_str(DAYS[date.weekday()]),
+            Some('b') => out.push_str(&MONTHS[date.month as usize - 1][..3]),
+            Some('B') => out.push_str(MONTHS[date.month as usize - 1]),
+            Some('z') => {
+                let sign = if date.offset < 0 { '-' } else { '+' };
+                let offset = date.offset.abs();
+                out.push_str(&format!("{sign}{:02}{:02}", offset / 60, offset % 60));
+            }
+            Some('%') => out.push('%'),
+            Some(c) => {
+                return Err(RuntimeError::native(format!(
+                    "time.format(): unknown specifier '%{c}'"
+                )))
+            }
+            None => {
+                return Err(RuntimeError::native(String::from(
+                    "time.format(): format ends with a lone '%'",
+                )))
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Parses `text` according to `pattern` into a timestamp. Fields missing
+/// from the pattern default to the start of 1970-01-01.
+fn parse(text: String, pattern: String, offset: Option<String>) -> Result<f64, RuntimeError> {
+    let mut date = DateTime {
+        year: 1970,
+        month: 1,
+        day: 1,
+        hour: 0,
+        minute: 0,
+        second: 0,
+        millis: 0,
+        offset: offset_argument("time.parse", offset)?,
+    };
+    let mut input = DateInput {
+        chars: text.chars().collect(),
+        current: 0,
+    };
+    let mut pattern = pattern.chars();
+    while let Some(c) = pattern.next() {
+        if c != '%' {
+            input.literal(c)?;
+            continue;
+        }
+        match pattern.next() {
+            Some('Y') => {
+                let negative = input.eat('-');
+                let year = input.number("year", 1, 6)?;
+                date.year = if negative { -year } else { year };
+            }
+            Some('m') => date.month = input.field("month", 2, 1..=12)?,
+            Some('d') => date.day = input.field("day", 2, 1..=31)?,
+            Some('H') => date.hour = input.field("hour", 2, 0..=23)?,
+            Some('M') => date.minute = input.field("minute", 2, 0..=59)?,
+            Some('S') => date.second = input.field("second", 2, 0..=60)?,
+            Some('f') => date.millis = input.field("milliseconds", 3, 0..=999)?,
+            Some('a') => {
+                input.name("weekday", &DAYS, 3)?;
+            }
+            Some('A') => {
+                input.name("weekday", &DAYS, usize::MAX)?;
+            }
+            Some('b') => date.month = input.name("month", &MONTHS, 3)? as u32 + 1,
+            Some('B') => date.month = input.name("month", &MONTHS, usize::MAX)? as u32 + 1,
+            Some('z') => {
+                let start = input.current;
+                while input
+                    .peek()
+                    .is_some_and(|c| matches!(c, '+' | '-' | ':' | 'Z') || c.is_ascii_digit())
+                {
+                    input.current += 1;
+                }
+                let text: String = input.chars[start..input.current].iter().collect();
+                date.offset = parse_offset(&text)
+                    .ok_or_else(|| input.error(&format!("invalid offset {text:?}")))?;
+            }
+            Some('%') => input.literal('%')?,
+            Some(c) => {
+                return Err(RuntimeError::native(format!(
+                    "time.parse(): unknown specifier '%{c}'"
+                )))
+            }
+            None => {
+                return Err(RuntimeError::native(String::from(
+                    "time.parse(): format ends with a lone '%'",
+                )))
+            }
+        }
+    }
+    if input.current < input.chars.len() {
+        return Err(input.error("unexpected trailing text"));
+    }
+    if date.day > days_in_month(date.year, date.month) {
+        return Err(RuntimeError::native(format!(
+            "time.parse(): day {} is out of range for {:04}-{:02}",
+            date.day, date.year, date.month
+        )));
+    }
+    Ok(date.timestamp())
+}
+
+struct DateInput {
+    chars: Vec<char>,
+    current: usize,
+}
+
+impl DateInput {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.current).copied()
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.current += 1;
+            return true;
+        }
+        false
+    }
+
+    fn literal(&mut self, expected: char) -> Result<(), RuntimeError> {
+        if self.eat(expected) {
+            return Ok(());
+        }
+        Err(self.error(&format!("expected '{expected}'")))
+    }
+
+    fn number(&mut self, what: &str, min: usize, max: usize) -> Result<i64, RuntimeError> {
+        let start = self.current;
+        while self.current - start < max && self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.current += 1;
+        }
+        if self.current - start < min {
+            self.current = start;
+            return Err(self.error(&format!("expected {what}")));
+        }
+        let digits: String = self.chars[start..self.current].iter().collect();
+        digits
+            .parse()
+            .map_err(|_| self.error(&format!("invalid {what}")))
+    }
+
+    fn field(
+        &mut self,
+        what: &str,
+        width: usize,
+        range: RangeInclusive<u32>,
+    ) -> Result<u32, RuntimeError> {
+        let start = self.current;
+        let value = self.number(what, 1, width)?;
+        match u32::try_from(value) {
+            Ok(value) if range.contains(&value) => Ok(value),
+            _ => {
+                self.current = start;
+                Err(self.error(&format!("{what} {value} is out of range")))
+            }
+        }
+    }
+
+    /// Matches a day or month name, either in full or its first `len`
+    /// letters, returning its index.
+    fn name(&mut self, what: &str, names: &[&str], len: usize) -> Result<usize, RuntimeError> {
+        for (index, name) in names.iter().enumerate() {
+            let name: Vec<char> = name.chars().take(len).collect();
+            let end = self.current + name.len();
+            if end <= self.chars.len()
+                && self.chars[self.current..end]
+                    .iter()
+                    .zip(&name)
+                    .all(|(a, b)| a.eq_ignore_ascii_case(b))
+            {
+                self.current = end;
+                return Ok(index);
+            }
+        }
+        Err(self.error(&format!("expected {what} name")))
+    }
+
+    fn error(&self, message: &str) -> RuntimeError {
+        RuntimeError::native(format!(
+            "time.parse(): {message} at position {}",
+            self.current + 1
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format_at(timestamp: f64, pattern: &str, offset: Option<&str>) -> String {
+        format(timestamp, pattern.to_string(), offset.map(String::from)).unwrap()
+    }
+
+    fn parse_with(text: &str, pattern: &str, offset: Option<&str>) -> Result<f64, String> {
+        parse(
+            text.to_string(),
+            pattern.to_string(),
+            offset.map(String::from),
+        )
+        .map_err(|e| e.message)
+    }
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(
+            format_at(0.0, "%Y-%m-%d %H:%M:%S", None),
+            "1970-01-01 00:00:00"
+        );
+        assert_eq!(
+            format_at(1_700_000_000.25, "%a %A %b %B %j %f %%", None),
+            "Tue Tuesday Nov November 318 250 %"
+        );
+        assert_eq!(format_at(951_782_400.0, "%Y-%m-%d", None), "2000-02-29");
+        assert_eq!(format_at(-86_400.0, "%Y-%m-%d", None), "1969-12-31");
+    }
+
+    #[test]
+    fn formats_at_offsets() {
+        assert_eq!(format_at(0.0, "%H:%M %z", Some("+05:30")), "05:30 +0530");
+        assert_eq!(
+            format_at(0.0, "%Y-%m-%d %H:%M %z", Some("-0800")),
+            "1969-12-31 16:00 -0800"
+        );
+        assert_eq!(format_at(0.0, "%z", Some("UTC")), "+0000");
+        assert_eq!(format_at(0.0, "%H", Some("+02")), "02");
+    }
+
+    #[test]
+    fn parses_offsets() {
+        assert_eq!(parse_offset("Z"), Some(0));
+        assert_eq!(parse_offset("utc"), Some(0));
+        assert_eq!(parse_offset("+05:30"), Some(330));
+        assert_eq!(parse_offset("-0800"), Some(-480));
+        assert_eq!(parse_offset("+02"), Some(120));
+        for invalid in ["", "+", "05:00", "+5", "+24:00", "+05:60", "+05:3"] {
+            assert_eq!(parse_offset(invalid), None, "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn parses_dates() {
+        assert_eq!(parse_with("1970-01-02", "%Y-%m-%d", None), Ok(86_400.0));
+        assert_eq!(
+            parse_with("2023-11-14 22:13:20.250", "%Y-%m-%d %H:%M:%S.%f", None),
+            Ok(1_700_000_000.25)
+        );
+        assert_eq!(
+            parse_with("Tue, 14 nov 2023", "%a, %d %b %Y", None),
+            Ok(1_699_920_000.0)
+        );
+        assert_eq!(parse_with("01:00 +0100", "%H:%M %z", None), Ok(0.0));
+        assert_eq!(parse_with("05:30", "%H:%M", Some("+05:30")), Ok(0.0));
+    }
+
+    #[test]
+    fn round_trips() {
+        let pattern = "%Y-%m-%dT%H:%M:%S.%f%z";
+        for timestamp in [0.0, 1_700_000_000.125, -1_000_000_000.5] {
+            for offset in ["UTC", "+05:45", "-11:00"] {
+                let text = format_at(timestamp, pattern, Some(offset));
+                assert_eq!(parse_with(&text, pattern, None), Ok(timestamp), "{text}");
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_dates() {
+        for (text, pattern, message) in [
+            (
+                "2023-02-29",
+                "%Y-%m-%d",
+                "day 29 is out of range for 2023-02",
+            ),
+            (
+                "2023-13-01",
+                "%Y-%m-%d",
+                "month 13 is out of range at position 6",
+            ),
+            ("2023/01/01", "%Y-%m-%d", "expected '-' at position 5"),
+            ("2023-01-01x", "%Y-%m-%d", "unexpected trailing text"),
+            ("Smarch", "%B", "expected month name"),
+            ("12", "%q", "unknown specifier '%q'"),
+        ] {
+            let error = parse_with(text, pattern, None).unwrap_err();
+            assert!(error.contains(message), "{text}: {error}");
+        }
+    }
+
+    #[test]
+    fn rejects_timestamps_out_of_range() {
+        assert!(format(1e300, "%Y".to_string(), Some("+05:00".to_string())).is_err());
+        assert!(format(f64::NAN, "%Y".to_string(), None).is_err());
+        assert!(format(-f64::INFINITY, "%Y".to_string(), None).is_err());
+        assert_eq!(format_at(MAX_TIMESTAMP, "%Y", Some("+23:59")), "275760");
+    }
+}
@@ -0,0 +1,53 @@
+use std::rc::Rc;
+
+use crate::{expr::Expr, token::Token, types::Type};
+
+pub enum Stmt {
+    /// <expr>;
+    Expression(Expr),
+
+    /// print (<expr>, ...)?;
+    Print(Vec<Expr>),
+
+    /// boof <identifier> (: <type>)? (= <expr>)?;
+    Var(Token, Option<Type>, Option<Expr>),
+
+    /// { <stmt>* }
+    Block(Vec<Stmt>),
+
+    /// if (<expr>) <stmt> (elseif (<expr>) <stmt>)* (else <stmt>)?
+    If(Token, Expr, Box<Stmt>, Option<Box<Stmt>>),
+
+    /// while (<expr>) <stmt>, which `for` loops also desugar into
+    While(Token, Expr, Box<Stmt>),
+
+    /// boofer <identifier>(<params>) (-> <type>)? { <stmt>* }
+    Function(Token, Rc<FunctionDecl>),
+
+    /// throw <expr>;
+    Throw(Token, Expr),
+
+    /// try { <stmt>* } catch (<identifier>) { <stmt>* } finally { <stmt>* }
+    ///
+    /// Either clause may be left out, but not both, and the catch binding is
+    /// optional.
+    Try(Vec<Stmt>, Option<CatchClause>, Option<Vec<Stmt>>),
+
+    /// return <expr>?;
+    Return(Token, Option<Expr>),
+}
+
+pub struct CatchClause {
+    pub name: Option<Token>,
+    pub body: Vec<Stmt>,
+}
+
+/// Parameters and body shared by named `boofer` declarations, anonymous
+/// `boofer (...) { ... }` expressions and `(...) => <expr>` lambdas.
+pub struct FunctionDecl {
+    pub params: Vec<Token>,
+    /// The annotation of each parameter, in the same order as `params`.
+    pub param_types: Vec<Option<Type>>,
+    pub return_type: Option<Type>,
+    pub body: Vec<Stmt>,
+}
@@ -0,0 +1,166 @@
+//! "Did you mean" suggestions for names that look like typos of a known
+//! variable, key, method or keyword.
+
+use crate::lexer::KEYWORDS;
+
+/// A help note naming the candidate closest to `name`, if any is close
+/// enough to be a likely typo.
+pub fn did_you_mean<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<String> {
+    closest(name, candidates).map(|candidate| format!("help: did you mean `{candidate}`?"))
+}
+
+/// A help note for an identifier that looks like a misspelled keyword, such
+/// as `retrun` or `elsif`.
+pub fn keyword(name: &str) -> Option<String> {
+    did_you_mean(name, KEYWORDS.iter().map(|(keyword, _)| *keyword))
+}
+
+/// Allows one edit for every three chars of `name`, so short names only
+/// match near-identical candidates. Ties go to candidates that contain every
+/// char of `name` in order, since dropped letters are the most common typo,
+/// so `boofr` suggests `boofer` rather than `boof`.
+fn closest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = name.chars().count() / 3;
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| {
+            let dropped = is_subsequence(name, candidate);
+            (distance(name, candidate), !dropped, candidate)
+        })
+        .filter(|(distance, _, _)| *distance <= limit)
+        .min()
+        .map(|(_, _, candidate)| candidate)
+}
+
+fn is_subsequence(name: &str, candidate: &str) -> bool {
+    let mut chars = candidate.chars();
+    name.chars().all(|c| chars.any(|other| other == c))
+}
+
+/// The number of insertions, deletions, substitutions and swaps of adjacent
+/// chars needed to turn `a` into `b`.
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // rows[i][j] is the distance between the first i chars of a and the
+    // first j chars of b.
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Engine;
+
+    fn suggest(name: &str, candidates: &[&str]) -> Option<String> {
+        did_you_mean(name, candidates.iter().copied())
+    }
+
+    /// The notes on the error from running `source`, without the source
+    /// excerpt and stack trace of runtime errors.
+    fn help(source: &str) -> Vec<String> {
+        let error = Engine::new().eval(source).unwrap_err();
+        error
+            .diagnostic()
+            .unwrap()
+            .notes
+            .iter()
+            .filter(|note| note.starts_with("help:"))
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn distance_counts_edits_and_swaps() {
+        assert_eq!(distance("count", "count"), 0);
+        assert_eq!(distance("cout", "count"), 1);
+        assert_eq!(distance("conut", "count"), 1);
+        assert_eq!(distance("cnt", "count"), 2);
+        assert_eq!(distance("", "abc"), 3);
+        assert_eq!(distance("naïve", "naive"), 1);
+    }
+
+    #[test]
+    fn suggests_the_closest_name_within_the_limit() {
+        assert_eq!(
+            suggest("lenght", &["length", "left", "len"]),
+            Some("help: did you mean `length`?".to_string())
+        );
+        assert_eq!(suggest("ab", &["ac"]), None);
+        assert_eq!(suggest("count", &["count"]), None);
+        assert_eq!(suggest("width", &["height", "depth"]), None);
+    }
+
+    #[test]
+    fn ties_prefer_names_with_every_typed_char() {
+        assert_eq!(
+            suggest("boofr", &["boof", "boofer"]),
+            Some("help: did you mean `boofer`?".to_string())
+        );
+        assert_eq!(
+            suggest("totl", &["tool", "total"]),
+            Some("help: did you mean `total`?".to_string())
+        );
+    }
+
+    #[test]
+    fn suggests_keywords() {
+        assert_eq!(
+            keyword("retrun"),
+            Some("help: did you mean `return`?".to_string())
+        );
+        assert_eq!(
+            keyword("whiel"),
+            Some("help: did you mean `while`?".to_string())
+        );
+        assert_eq!(keyword("x"), None);
+    }
+
+    #[test]
+    fn errors_carry_suggestions() {
+        assert_eq!(
+            help("boof count = 1; print conut;"),
+            ["help: did you mean `count`?"]
+        );
+        assert_eq!(
+            help("boof point = {x: 1, y: 2}; point.z1;"),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            help("boof point = {width: 1}; point.widht;"),
+            ["help: did you mean `width`?"]
+        );
+        assert_eq!(help("\"abc\".uper();"), ["help: did you mean `upper`?"]);
+        assert_eq!(
+            help("boofer f() { retrun 1; }"),
+            ["help: did you mean `return`?"]
+        );
+        assert_eq!(
+            help("boof x: nubmer = 1;"),
+            ["help: did you mean `number`?"]
+        );
+    }
+}
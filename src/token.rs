@@ -1,3 +1,8 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::{error::Span, regex::Regex};
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
     Var,
@@ -6,14 +11,19 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     If,
     Else,
     ElseIf,
     SemiColon,
+    Colon,
     Plus,
     Minus,
     Equal,
     EqualEqual,
+    FatArrow,
+    Arrow,
     Greater,
     GreaterEqual,
     Less,
@@ -23,39 +33,59 @@ pub enum TokenType {
     Slash,
     Star,
     Dot,
+    DotDot,
+    DotDotEqual,
     Comma,
+    Pipe,
     Identifier,
     String(String),
-    Number(f32),
+    Number(f64),
+    Regex(Rc<Regex>),
     And,
     Nil,
     While,
     For,
     Or,
     Return,
+    Throw,
+    Try,
+    Catch,
+    Finally,
     Print,
+    Match,
     False,
     True,
-    EOF,
+    Eof,
 }
 
 #[derive(Debug, Clone)]
 pub struct Token {
     pub token_type: TokenType,
     pub lexeme: String,
-    line: usize,
+    pub line: usize,
+    pub column: usize,
 }
 
 impl Token {
-    pub fn from(token_type: TokenType, lexeme: String, line: usize) -> Self {
+    pub fn from(token_type: TokenType, lexeme: String, line: usize, column: usize) -> Self {
         Token {
             token_type,
             line,
+            column,
             lexeme,
         }
     }
 
-    pub fn to_string(&self) -> String {
-        format!("{}", self.lexeme)
+    pub fn span(&self) -> Span {
+        Span {
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.lexeme)
     }
 }
@@ -0,0 +1,82 @@
+use std::fmt;
+
+use crate::expr::LiteralValue;
+
+/// A static type, written in annotations such as `boof x: number = 1;`.
+/// The names are those `type_of` returns, plus `any` for values the
+/// [`Checker`](crate::Checker) can't know.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    Any,
+    Nil,
+    Bool,
+    Number,
+    String,
+    List,
+    Map,
+    Boofer,
+    Regex,
+}
+
+impl Type {
+    /// Every type name, for suggesting one in place of a misspelled name.
+    pub const NAMES: &'static [&'static str] = &[
+        "any", "nil", "bool", "number", "string", "list", "map", "boofer", "regex",
+    ];
+
+    pub fn from_name(name: &str) -> Option<Type> {
+        let ty = match name {
+            "any" => Type::Any,
+            "nil" => Type::Nil,
+            "bool" => Type::Bool,
+            "number" => Type::Number,
+            "string" => Type::String,
+            "list" => Type::List,
+            "map" => Type::Map,
+            "boofer" => Type::Boofer,
+            "regex" => Type::Regex,
+            _ => return None,
+        };
+        Some(ty)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Type::Any => "any",
+            Type::Nil => "nil",
+            Type::Bool => "bool",
+            Type::Number => "number",
+            Type::String => "string",
+            Type::List => "list",
+            Type::Map => "map",
+            Type::Boofer => "boofer",
+            Type::Regex => "regex",
+        }
+    }
+
+    /// The type of a value.
+    pub fn of(value: &LiteralValue) -> Type {
+        match value {
+            LiteralValue::String(_) => Type::String,
+            LiteralValue::Number(_) => Type::Number,
+            LiteralValue::List(_) => Type::List,
+            LiteralValue::Map(_) => Type::Map,
+            LiteralValue::Function(_) | LiteralValue::NativeFunction(_) => Type::Boofer,
+            LiteralValue::Regex(_) => Type::Regex,
+            LiteralValue::True | LiteralValue::False => Type::Bool,
+            LiteralValue::Nil => Type::Nil,
+        }
+    }
+
+    /// Whether a value of this type may be stored where `target` is
+    /// expected. `any` goes both ways, which is what makes checking gradual.
+    pub fn fits(self, target: Type) -> bool {
+        self == target || self == Type::Any || target == Type::Any
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
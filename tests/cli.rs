@@ -0,0 +1,170 @@
+//! Runs the boof executable and checks what it promises to editors and CI:
+//! exit codes, JSON diagnostics and `--explain`.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// A script written to a temp file, removed when dropped.
+struct Script(PathBuf);
+
+impl Script {
+    fn new(name: &str, source: &str) -> Self {
+        let file = format!("boof-cli-{}-{}.boof", std::process::id(), name);
+        let path = env::temp_dir().join(file);
+        fs::write(&path, source).unwrap();
+        Script(path)
+    }
+
+    fn path(&self) -> String {
+        self.0.display().to_string()
+    }
+}
+
+impl Drop for Script {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn boof(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_boof"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn exit_code(name: &str, source: &str) -> Option<i32> {
+    let script = Script::new(name, source);
+    boof(&[&script.path()]).status.code()
+}
+
+#[test]
+fn exit_codes_follow_sysexits() {
+    assert_eq!(exit_code("ok", "print 1;"), Some(0));
+    assert_eq!(exit_code("lex", "print @;"), Some(65));
+    assert_eq!(exit_code("parse", "print 1"), Some(65));
+    assert_eq!(exit_code("resolve", "return 1;"), Some(65));
+    assert_eq!(exit_code("runtime", "nil - 1;"), Some(70));
+    assert_eq!(exit_code("exit", "exit(3);"), Some(3));
+    assert_eq!(
+        exit_code("runtime-io", r#"fs.read("/nonexistent/boof");"#),
+        Some(70)
+    );
+}
+
+#[test]
+fn usage_and_missing_scripts_have_their_own_codes() {
+    assert_eq!(boof(&["--no-such-flag"]).status.code(), Some(64));
+    assert_eq!(boof(&["--seed", "x", "a.boof"]).status.code(), Some(64));
+    assert_eq!(boof(&["/nonexistent/boof.boof"]).status.code(), Some(74));
+}
+
+#[test]
+fn check_exits_with_the_code_of_a_type_error() {
+    let script = Script::new("check", "boof x: number = \"a\";");
+    assert_eq!(boof(&["check", &script.path()]).status.code(), Some(65));
+    let clean = Script::new("check-clean", "boof x: number = 1;");
+    assert_eq!(boof(&["check", &clean.path()]).status.code(), Some(0));
+}
+
+#[test]
+fn json_errors_are_one_line_on_stderr() {
+    let script = Script::new("json", "boof a = 1;\nprint a - nil;");
+    let output = boof(&["--error-format=json", &script.path()]);
+    let file = script.path();
+    assert_eq!(output.status.code(), Some(70));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        format!(
+            concat!(
+                r#"{{"severity":"error","code":"B0301","message":"Operands of '-' must be numbers, not number and nil","#,
+                r#""file":"{file}","span":{{"line":2,"column":9}},"#,
+                r#""notes":[" 2 | print a - nil;\n   |         ^","Stack trace (innermost first):\n  at <script> ({file}:2:9)"]}}"#,
+                "\n"
+            ),
+            file = file
+        )
+    );
+}
+
+#[test]
+fn json_warnings_come_before_errors() {
+    let script = Script::new(
+        "json-warning",
+        "print match (1) { 1 => \"one\" };\nprint 1 +;",
+    );
+    let output = boof(&["--error-format", "json", &script.path()]);
+    let file = script.path();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let lines: Vec<&str> = stderr.lines().collect();
+    assert_eq!(lines.len(), 2, "{stderr}");
+    assert_eq!(
+        lines[0],
+        format!(
+            r#"{{"severity":"warning","code":"B0150","message":"match over literal values has no '_' arm","file":"{file}","span":{{"line":1,"column":7}},"notes":[]}}"#
+        )
+    );
+    assert_eq!(
+        lines[1],
+        format!(
+            r#"{{"severity":"error","code":"B0110","message":"Expected Expression","file":"{file}","span":{{"line":2,"column":10}},"notes":[]}}"#
+        )
+    );
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn json_io_errors_have_no_span() {
+    let output = boof(&["--error-format=json", "/nonexistent/boof.boof"]);
+    assert_eq!(output.status.code(), Some(74));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        concat!(
+            r#"{"severity":"error","code":"B0401","message":"Failed to read file \"/nonexistent/boof.boof\": No such file or directory (os error 2)","#,
+            r#""file":"/nonexistent/boof.boof","span":null,"notes":[]}"#,
+            "\n"
+        )
+    );
+}
+
+#[test]
+fn explain_prints_the_title_and_explanation() {
+    let output = boof(&["--explain", "B0001"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "B0001: unterminated string
+
+A string literal was opened with `\"` but the file ended before the
+closing `\"`. Strings may span lines, so the error is reported where the
+string starts.
+
+Wrong:
+
+    print \"hello;
+
+Correct:
+
+    print \"hello\";
+"
+    );
+    let lowercase = boof(&["--explain", "b0001"]);
+    assert_eq!(lowercase.stdout, output.stdout);
+}
+
+#[test]
+fn explain_rejects_unknown_or_missing_codes() {
+    let output = boof(&["--explain", "B9999"]);
+    assert_eq!(output.status.code(), Some(64));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "Unknown error code 'B9999'. Codes look like B0102.\n"
+    );
+    let output = boof(&["--explain"]);
+    assert_eq!(output.status.code(), Some(64));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("Usage: boof"));
+}
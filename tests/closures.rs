@@ -0,0 +1,91 @@
+//! Runs boofers that capture variables from the scope they were declared
+//! in, whether named, anonymous or written as arrow lambdas.
+
+use boof::Engine;
+
+fn eval(source: &str) -> String {
+    Engine::new().eval(source).unwrap().to_string()
+}
+
+#[test]
+fn counters_keep_their_own_state() {
+    let source = "
+        boofer make_counter() {
+            boof count = 0;
+            boofer increment() {
+                count = count + 1;
+                return count;
+            }
+            return increment;
+        }
+        boof a = make_counter();
+        boof b = make_counter();
+        a();
+        a();
+        b();
+        [a(), b()]";
+    assert_eq!(eval(source), "[3, 2]");
+}
+
+#[test]
+fn closures_see_later_assignments() {
+    assert_eq!(
+        eval("boof x = 1; boofer get() { return x; } x = 2; get()"),
+        "2"
+    );
+}
+
+#[test]
+fn closures_capture_the_scope_they_are_declared_in() {
+    let source = r#"
+        boof a = "global";
+        boof seen;
+        {
+            boofer show() { return a; }
+            boof a = "block";
+            seen = [show(), a];
+        }
+        seen"#;
+    assert_eq!(eval(source), r#"["global", "block"]"#);
+}
+
+#[test]
+fn anonymous_boofers_are_values() {
+    assert_eq!(
+        eval("boof add = boofer(a, b) { return a + b; }; add(2, 3)"),
+        "5"
+    );
+    assert_eq!(
+        eval("boofer apply(f, x) { return f(x); } apply(boofer(n) { return n * n; }, 7)"),
+        "49"
+    );
+    assert_eq!(eval("str(boofer() {})"), "<boofer>");
+}
+
+#[test]
+fn arrow_lambdas_return_their_expression() {
+    assert_eq!(eval("boof double = (x) => x * 2; double(4)"), "8");
+    assert_eq!(eval("boof answer = () => 42; answer()"), "42");
+    assert_eq!(eval("map([1, 2, 3], (x) => x * 10)"), "[10, 20, 30]");
+    assert_eq!(eval("reduce([1, 2, 3], (sum, x) => sum + x, 0)"), "6");
+}
+
+#[test]
+fn arrow_lambdas_capture_their_environment() {
+    let source = "
+        boofer adder(n) { return (x) => x + n; }
+        boof add3 = adder(3);
+        boof add10 = adder(10);
+        [add3(4), add10(4)]";
+    assert_eq!(eval(source), "[7, 14]");
+    assert_eq!(eval("boof n = 1; boof f = () => n; n = 5; f()"), "5");
+}
+
+#[test]
+fn lambda_errors_are_traced_as_lambdas() {
+    let error = Engine::new()
+        .eval("boof f = (x) => x + nil;\nf(1);")
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("at <lambda> (<input>:1:"), "{error}");
+}
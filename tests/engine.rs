@@ -0,0 +1,89 @@
+//! Checks how `Engine` runs code it is given directly: the line numbers it
+//! reports, the arity errors it raises and how it stops runaway recursion.
+
+use boof::Engine;
+
+fn error_line(result: Result<boof::Value, boof::Error>) -> String {
+    let error = result.unwrap_err().to_string();
+    error.lines().next().unwrap().to_string()
+}
+
+#[test]
+fn eval_counts_lines_from_each_snippet() {
+    let mut engine = Engine::new();
+    engine.eval("boof x = 1;\nboof y = 2;").unwrap();
+    assert_eq!(
+        error_line(engine.eval("\nnil - 1;")),
+        "[line: 2] Runtime Error[B0301]: Operands of '-' must be numbers, not nil and number"
+    );
+    assert!(error_line(engine.eval("nil - 1;")).starts_with("[line: 1]"));
+}
+
+#[test]
+fn eval_line_continues_the_numbering() {
+    let mut engine = Engine::new();
+    engine.eval_line("boof x = 1;\n").unwrap();
+    engine.eval("boof y = 2;\nboof z = 3;").unwrap();
+    engine.eval_line("\n").unwrap();
+    assert!(error_line(engine.eval_line("nil - 1;\n")).starts_with("[line: 3]"));
+    let error = engine.eval_line("x +\n  nil;\n").unwrap_err().to_string();
+    assert!(error.starts_with("[line: 4]"), "{error}");
+    assert!(error.contains(" 4 | x +"), "{error}");
+}
+
+#[test]
+fn arity_errors_pluralize_the_expected_count() {
+    let mut engine = Engine::new();
+    engine
+        .eval("boof one = boofer(a) {};\nboof two = boofer(a, b) {};")
+        .unwrap();
+    assert_eq!(
+        error_line(engine.eval("one(1, 2);")),
+        "[line: 1] Runtime Error[B0303]: Expected 1 argument but got 2"
+    );
+    assert_eq!(
+        error_line(engine.eval("two(1);")),
+        "[line: 1] Runtime Error[B0303]: Expected 2 arguments but got 1"
+    );
+}
+
+const RUNAWAY: &str = "boofer f(n) { return f(n + 1); }\nf(0);";
+
+#[test]
+fn runaway_recursion_fails_without_aborting_the_host() {
+    let path = std::env::temp_dir().join(format!("boof-engine-{}.boof", std::process::id()));
+    std::fs::write(&path, RUNAWAY).unwrap();
+    let path_for_thread = path.clone();
+    let (evaluated, ran) = std::thread::spawn(move || {
+        let mut engine = Engine::new();
+        (
+            engine.eval(RUNAWAY).unwrap_err(),
+            engine.run_file(&path_for_thread).unwrap_err(),
+        )
+    })
+    .join()
+    .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    for error in [evaluated, ran] {
+        assert_eq!(error.diagnostic().unwrap().code.id, "B0307");
+        assert_eq!(error.code(), 70);
+    }
+}
+
+#[test]
+fn a_raised_stack_limit_allows_deeper_calls() {
+    let depth = std::thread::Builder::new()
+        .stack_size(256 * 1024 * 1024)
+        .spawn(|| {
+            let mut engine = Engine::new();
+            engine.set_stack_limit(128 * 1024 * 1024);
+            engine
+                .eval("boofer count(n) { if (n == 0) return 0; return 1 + count(n - 1); }\ncount(999)")
+                .unwrap()
+                .to_string()
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(depth, "999");
+}
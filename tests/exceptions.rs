@@ -0,0 +1,129 @@
+//! Runs `throw` and `try`/`catch`/`finally`, checking which blocks run and
+//! what `catch` receives.
+
+use boof::{Engine, Error};
+
+fn eval(source: &str) -> String {
+    Engine::new().eval(source).unwrap().to_string()
+}
+
+#[test]
+fn finally_runs_after_return_in_try() {
+    let source = r#"
+        boof log = [];
+        boofer f() {
+            try {
+                return "from try";
+            } finally {
+                log = ["finally ran"];
+            }
+            return "after";
+        }
+        [f(), log]"#;
+    assert_eq!(eval(source), r#"["from try", ["finally ran"]]"#);
+}
+
+#[test]
+fn finally_runs_when_catch_throws() {
+    let source = r#"
+        boof log = "";
+        boofer f() {
+            try {
+                throw "first";
+            } catch (e) {
+                log = log + "catch ";
+                throw "second after " + e;
+            } finally {
+                log = log + "finally";
+            }
+        }
+        boof caught;
+        try { f(); } catch (e) { caught = e; }
+        [caught, log]"#;
+    assert_eq!(eval(source), r#"["second after first", "catch finally"]"#);
+}
+
+#[test]
+fn finally_runs_when_nothing_catches() {
+    let source = r#"
+        boof log = "";
+        boof caught;
+        try {
+            try { throw 1; } finally { log = "inner finally"; }
+        } catch (e) {
+            caught = e;
+        }
+        [caught, log]"#;
+    assert_eq!(eval(source), r#"[1, "inner finally"]"#);
+}
+
+#[test]
+fn rethrown_errors_keep_their_value() {
+    let source = r#"
+        boof inner;
+        boof outer;
+        try {
+            try { nil - 1; } catch (e) { inner = e; throw e; }
+        } catch (e) {
+            outer = e;
+        }
+        [inner == outer, outer.kind, outer.message]"#;
+    assert_eq!(
+        eval(source),
+        r#"[true, "type", "Operands of '-' must be numbers, not nil and number"]"#
+    );
+}
+
+#[test]
+fn uncaught_rethrow_reads_like_the_original() {
+    let error = Engine::new()
+        .eval("try { nil - 1; } catch (e) { throw e; }")
+        .unwrap_err();
+    let diagnostic = error.diagnostic().unwrap();
+    assert_eq!(diagnostic.code.id, "B0306");
+    assert_eq!(
+        diagnostic.message,
+        "Operands of '-' must be numbers, not nil and number"
+    );
+}
+
+#[test]
+fn catch_receives_thrown_values_unchanged() {
+    assert_eq!(
+        eval("boof got; try { throw 42; } catch (e) { got = e; } got"),
+        "42"
+    );
+    assert_eq!(
+        eval(r#"boof got; try { throw {code: 7, message: "bad"}; } catch (e) { got = e; } got"#),
+        r#"{code: 7, message: "bad"}"#
+    );
+    assert_eq!(
+        eval(r#"boof ran = false; try { throw "x"; } catch { ran = true; } ran"#),
+        "true"
+    );
+}
+
+#[test]
+fn catch_receives_runtime_errors_as_maps() {
+    let source = "
+        boof got;
+        try {
+            boof x = 1;
+            len(x);
+        } catch (e) {
+            got = e;
+        }
+        got";
+    assert_eq!(
+        eval(source),
+        r#"{column: 16, kind: "type", line: 5, message: "len() expects a string, list or map, not number"}"#
+    );
+}
+
+#[test]
+fn exit_is_not_caught() {
+    let mut engine = Engine::new();
+    let result = engine.eval("boof ran = false; try { exit(3); } catch (e) { ran = true; } finally { ran = \"finally\"; }");
+    assert!(matches!(result, Err(Error::Exit(3))));
+    assert_eq!(engine.get_global("ran").unwrap().to_string(), "finally");
+}
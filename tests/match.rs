@@ -0,0 +1,103 @@
+//! Runs `match` expressions over each kind of pattern.
+
+use boof::Engine;
+
+fn eval(source: &str) -> String {
+    Engine::new().eval(source).unwrap().to_string()
+}
+
+/// Matches each subject with `arms` and joins the results.
+fn classify(arms: &str, subjects: &[&str]) -> String {
+    let results: Vec<String> = subjects
+        .iter()
+        .map(|subject| eval(&format!("match ({subject}) {{ {arms} }}")))
+        .collect();
+    results.join(" ")
+}
+
+#[test]
+fn literal_patterns() {
+    let arms = r#"0 => "zero", -1 => "minus one", "hi" => "greeting", true => "yes", nil => "nothing", _ => "other""#;
+    assert_eq!(
+        classify(arms, &["0", "-1", r#""hi""#, "true", "nil", "false", "2"]),
+        "zero minus one greeting yes nothing other other"
+    );
+}
+
+#[test]
+fn range_patterns() {
+    let arms = r#"0..10 => "digit", 10..=99 => "two digits", _ => "big""#;
+    assert_eq!(
+        classify(arms, &["0", "9.5", "10", "99", "100", r#""5""#]),
+        "digit digit two digits two digits big big"
+    );
+}
+
+#[test]
+fn list_patterns() {
+    let arms = r#"[] => "empty", [x] => "one " + str(x), [1, ..] => "starts with 1", [first, ..rest] => str(first) + " then " + str(rest), _ => "not a list""#;
+    assert_eq!(
+        classify(arms, &["[]", "[7]", "[1, 2]", "[2, 3, 4]", r#""[]""#]),
+        "empty one 7 starts with 1 2 then [3, 4] not a list"
+    );
+}
+
+#[test]
+fn map_patterns() {
+    let arms = r#"{kind: "circle", r} => r * r * 3, {kind: "square", side: s} => s * s, {} => "some map", _ => "not a map""#;
+    assert_eq!(
+        classify(
+            arms,
+            &[
+                r#"{kind: "circle", r: 2}"#,
+                r#"{kind: "square", side: 3, color: "red"}"#,
+                r#"{kind: "square"}"#,
+                "1",
+            ]
+        ),
+        "12 9 some map not a map"
+    );
+}
+
+#[test]
+fn or_patterns() {
+    let arms = r#"1 | 2 | 3 => "small", [x] | [x, _] => "short " + str(x), _ => "other""#;
+    assert_eq!(
+        classify(arms, &["2", "[5]", "[6, 7]", "4"]),
+        "small short 5 short 6 other"
+    );
+}
+
+#[test]
+fn guards_fall_through_to_later_arms() {
+    let arms = r#"n if n < 0 => "negative", n if n == 0 => "zero", n => "positive " + str(n)"#;
+    assert_eq!(
+        classify(arms, &["-3", "0", "4"]),
+        "negative zero positive 4"
+    );
+}
+
+#[test]
+fn bindings_are_scoped_to_their_arm() {
+    let source = r#"
+        boof x = "outer";
+        boof inner = match ([1, 2]) { [x, y] => x + y };
+        [inner, x]"#;
+    assert_eq!(eval(source), r#"[3, "outer"]"#);
+    assert_eq!(
+        eval("boof limit = 5; match (3) { n if n < limit => limit - n, _ => 0 }"),
+        "2"
+    );
+}
+
+#[test]
+fn no_matching_arm_is_a_runtime_error() {
+    let error = Engine::new()
+        .eval("match (3) { 1 => \"one\", n if n > 5 => \"big\" }")
+        .unwrap_err();
+    assert_eq!(error.diagnostic().unwrap().code.id, "B0304");
+    assert!(
+        error.to_string().contains("No match arm matched value 3"),
+        "{error}"
+    );
+}
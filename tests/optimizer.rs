@@ -0,0 +1,144 @@
+//! Runs programs with and without `--no-opt` and checks that the optimizer
+//! doesn't change what they print, what errors they report or how they exit.
+
+use std::env;
+use std::fs;
+use std::process::{Command, Output};
+
+use boof::{Engine, Value};
+
+/// Each program's name, source and expected stdout.
+const PROGRAMS: &[(&str, &str, &str)] = &[
+    (
+        "arithmetic",
+        "print 1 + 2 * 3, 10 - 2 - 3, -(4 - 6), 1 / 0, 7 / 2;",
+        "7 5 2 inf 3.5\n",
+    ),
+    (
+        "strings",
+        r#"print "a" + "b" + "c", "x" == "x", "x" != "y";"#,
+        "abc true true\n",
+    ),
+    (
+        "logic",
+        r#"print !true, !nil, true and "yes", nil or 5, false and 1 - "a";"#,
+        "false true yes 5 false\n",
+    ),
+    (
+        "mixed",
+        "boof x = 4; print x * (2 + 3), (x) + 1 * 2;",
+        "20 6\n",
+    ),
+    (
+        "branches",
+        r#"
+        if (1 + 1 == 2) print "taken"; else print "not taken";
+        if (false) print "no"; elseif (nil) print "no"; else { boof x = 2; print x; }
+        if (true) if (false) print "no";
+        while (false) print "never";
+        "#,
+        "taken\n2\n",
+    ),
+    (
+        "dead code",
+        r#"
+        boofer f(n) {
+            return n * (2 + 3);
+            print "dead";
+        }
+        boofer g() {
+            while (true) { return "out"; }
+            print "dead";
+        }
+        print f(2), g();
+        "#,
+        "10 out\n",
+    ),
+    (
+        "closures",
+        r#"
+        boof a = "global";
+        {
+            boofer show() { print a; }
+            if (true) { show(); }
+            boof a = "block";
+            show();
+        }
+        boof add = (n) => n + 10 * 10;
+        print add(1);
+        "#,
+        "global\nglobal\n101\n",
+    ),
+    (
+        "match",
+        r#"print match (2 + 1) { 3 if 1 < 2 => "three", _ => "other" };"#,
+        "three\n",
+    ),
+    (
+        "try",
+        r#"
+        try { throw 1 + 1; print "dead"; } catch (e) { print "caught", e; }
+        finally { print "finally"; }
+        "#,
+        "caught 2\nfinally\n",
+    ),
+    (
+        "type error",
+        r#"print "before"; print "a" - 1; print "after";"#,
+        "before\n",
+    ),
+    ("error in folded branch", "if (2 > 1) { print -nil; }", ""),
+    ("uncaught throw", "throw 1 + 2;", ""),
+];
+
+fn run(name: &str, source: &str, optimize: bool) -> Output {
+    let file = format!("boof-optimizer-{}-{}.boof", std::process::id(), name);
+    let path = env::temp_dir().join(file.replace(' ', "-"));
+    fs::write(&path, source).unwrap();
+    let mut command = Command::new(env!("CARGO_BIN_EXE_boof"));
+    if !optimize {
+        command.arg("--no-opt");
+    }
+    let output = command.arg(&path).output().unwrap();
+    fs::remove_file(&path).unwrap();
+    output
+}
+
+#[test]
+fn optimized_programs_behave_the_same() {
+    for (name, source, expected) in PROGRAMS {
+        let optimized = run(name, source, true);
+        let unoptimized = run(name, source, false);
+        let stdout = String::from_utf8_lossy(&optimized.stdout);
+        assert_eq!(stdout, *expected, "stdout of {name}");
+        assert_eq!(
+            optimized.stdout, unoptimized.stdout,
+            "stdout of {name} with --no-opt"
+        );
+        assert_eq!(
+            String::from_utf8_lossy(&optimized.stderr),
+            String::from_utf8_lossy(&unoptimized.stderr),
+            "stderr of {name}"
+        );
+        assert_eq!(optimized.status, unoptimized.status, "exit of {name}");
+    }
+}
+
+#[test]
+fn eval_returns_the_same_values() {
+    let sources = [
+        "1 + 2 * 3",
+        r#""a" + "b""#,
+        "if (true) 1 + 2;",
+        "boof x = 1; x + 2 * 3",
+        "true and !false",
+    ];
+    for source in sources {
+        let mut optimized = Engine::new();
+        let mut unoptimized = Engine::new();
+        unoptimized.set_optimize(false);
+        let value = optimized.eval(source).unwrap();
+        assert_eq!(value, unoptimized.eval(source).unwrap(), "{source}");
+    }
+    assert_eq!(Engine::new().eval("if (true) 1 + 2;").unwrap(), Value::Nil);
+}
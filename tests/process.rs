@@ -0,0 +1,52 @@
+//! Runs scripts that read the environment the boof executable was started
+//! with.
+
+use std::env;
+use std::fs;
+use std::process::{Command, Output};
+
+fn run(name: &str, source: &str, configure: impl FnOnce(&mut Command)) -> Output {
+    let file = format!("boof-process-{}-{}.boof", std::process::id(), name);
+    let path = env::temp_dir().join(file);
+    fs::write(&path, source).unwrap();
+    let mut command = Command::new(env!("CARGO_BIN_EXE_boof"));
+    command.arg(&path);
+    configure(&mut command);
+    let output = command.output().unwrap();
+    fs::remove_file(&path).unwrap();
+    output
+}
+
+#[test]
+fn env_reads_one_or_every_variable() {
+    let output = run(
+        "env",
+        r#"print env("BOOF_TEST_VAR"), env("BOOF_TEST_UNSET"), env().BOOF_TEST_VAR;"#,
+        |command| {
+            command
+                .env("BOOF_TEST_VAR", "set")
+                .env_remove("BOOF_TEST_UNSET");
+        },
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "set nil set\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn env_replaces_invalid_utf8() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let output = run(
+        "invalid",
+        r#"print env("BOOF_TEST_BAD"), env().BOOF_TEST_BAD, len(env()) > 0;"#,
+        |command| {
+            command.env("BOOF_TEST_BAD", OsStr::from_bytes(b"a\xffb"));
+        },
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "a\u{fffd}b a\u{fffd}b true\n"
+    );
+    assert!(output.status.success());
+}
@@ -0,0 +1,62 @@
+//! Runs scripts that use the `random` module with a fixed seed, whose
+//! output must be the same on every run.
+
+use std::env;
+use std::fs;
+use std::process::Command;
+
+use boof::Engine;
+
+const SCRIPT: &str = "print random.random(), random.randint(1, 100);
+print random.choice([\"a\", \"b\", \"c\"]), random.shuffle([1, 2, 3, 4, 5]);";
+
+fn run(seed: &str) -> String {
+    let file = format!("boof-random-{}-{seed}.boof", std::process::id());
+    let path = env::temp_dir().join(file);
+    fs::write(&path, SCRIPT).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_boof"))
+        .args(["--seed", seed])
+        .arg(&path)
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn draw(engine: &mut Engine) -> String {
+    engine
+        .eval("[random.random(), random.randint(1, 6), random.shuffle([1, 2, 3])]")
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn seed_flag_repeats_output() {
+    let output = run("42");
+    assert_eq!(output, "0.7415648787718233 92\na [4, 2, 1, 3, 5]\n");
+    assert_eq!(run("42"), output);
+    assert_ne!(run("42"), run("43"));
+}
+
+#[test]
+fn seeded_engines_repeat_output() {
+    let mut first = Engine::new();
+    let mut second = Engine::new();
+    first.seed_random(2024);
+    second.seed_random(2024);
+    assert_eq!(draw(&mut first), draw(&mut second));
+    assert_eq!(draw(&mut first), draw(&mut second));
+}
+
+#[test]
+fn random_seed_restarts_the_sequence() {
+    let mut engine = Engine::new();
+    let twice = engine
+        .eval("random.seed(5); boof a = random.random(); random.seed(5); [a, random.random()]")
+        .unwrap();
+    let boof::Value::List(values) = twice else {
+        panic!("expected a list");
+    };
+    assert_eq!(values[0], values[1]);
+}
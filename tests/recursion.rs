@@ -0,0 +1,73 @@
+//! Runs scripts whose boofer calls nest too deeply, which must end in a
+//! runtime error rather than overflowing the interpreter's own stack.
+
+use std::env;
+use std::fs;
+use std::process::{Command, Output};
+
+fn run(name: &str, source: &str) -> Output {
+    let file = format!("boof-recursion-{}-{}.boof", std::process::id(), name);
+    let path = env::temp_dir().join(file);
+    fs::write(&path, source).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_boof"))
+        .arg(&path)
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    output
+}
+
+#[test]
+fn runaway_recursion_is_catchable() {
+    let output = run(
+        "caught",
+        "boofer f(n) { return f(n + 1); }
+        try { f(0); } catch (e) { print e.kind, e.message; }
+        boofer g(n) { return 1 + len(map([n], (x) => g(x + 1))); }
+        try { g(0); } catch (e) { print e.kind; }",
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "recursion Stack overflow (max call depth 1000)\nrecursion\n"
+    );
+    assert!(output.status.success());
+}
+
+#[test]
+fn uncaught_overflow_reports_a_runtime_error() {
+    let output = run("uncaught", "boofer f(n) { return f(n + 1); }\nf(0);");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Runtime Error[B0307]"), "{stderr}");
+    assert!(stderr.contains("... repeated"), "{stderr}");
+    assert_eq!(output.status.code(), Some(70));
+}
+
+#[test]
+fn deep_recursion_below_the_limit_runs() {
+    let output = run(
+        "deep",
+        "boofer count(n) { if (n == 0) return 0; return 1 + count(n - 1); }
+        print count(999);",
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "999\n");
+}
+
+#[test]
+fn engine_overflow_is_catchable_on_a_default_thread() {
+    let kinds = std::thread::spawn(|| {
+        let mut engine = boof::Engine::new();
+        engine
+            .eval(
+                r#"boofer f(n) { return f(n + 1); }
+                boofer g(n) { return 1 + len(map([n], (x) => g(x + 1))); }
+                boof kinds = "";
+                try { f(0); } catch (e) { kinds = kinds + e.kind + " "; }
+                try { g(0); } catch (e) { kinds = kinds + e.kind + " "; }
+                kinds"#,
+            )
+            .map(|kinds| kinds.to_string())
+    })
+    .join()
+    .unwrap();
+    assert_eq!(kinds.unwrap(), "recursion recursion ");
+}